@@ -0,0 +1,187 @@
+use crate::{
+    errors::ShareTokenError,
+    storage::{self, ShareAllowance},
+};
+use soroban_sdk::{panic_with_error, Address, Env};
+
+/// Set the number of shares a spender can spend on behalf of a user
+///
+/// ### Arguments
+/// * `from` - The user who owns the shares
+/// * `spender` - The user allowed to spend the shares
+/// * `amount` - The number of shares the spender can spend
+/// * `expiration_ledger` - The ledger sequence the allowance expires at
+///
+/// ### Panics
+/// * If the amount is negative
+/// * If the expiration ledger is in the past or past the max TTL and the amount is not 0
+pub fn approve(e: &Env, from: &Address, spender: &Address, amount: i128, expiration_ledger: u32) {
+    require_nonnegative(e, amount);
+    if amount > 0
+        && (expiration_ledger < e.ledger().sequence()
+            || expiration_ledger > e.ledger().max_live_until_ledger())
+    {
+        panic_with_error!(e, ShareTokenError::InvalidExpirationLedger);
+    }
+    storage::set_allowance(
+        e,
+        from,
+        spender,
+        &ShareAllowance {
+            amount,
+            expiration_ledger,
+        },
+    );
+}
+
+/// Spend shares from a spender's allowance
+///
+/// ### Arguments
+/// * `from` - The user who owns the shares
+/// * `spender` - The user spending the shares
+/// * `amount` - The number of shares to spend
+///
+/// ### Panics
+/// * If the amount is negative
+/// * If the spender's allowance is less than the amount
+pub fn spend_allowance(e: &Env, from: &Address, spender: &Address, amount: i128) {
+    require_nonnegative(e, amount);
+    let mut allowance = storage::get_allowance(e, from, spender);
+    if allowance.amount < amount {
+        panic_with_error!(e, ShareTokenError::AllowanceError);
+    }
+    if amount > 0 {
+        allowance.amount -= amount;
+        storage::set_allowance(e, from, spender, &allowance);
+    }
+}
+
+fn require_nonnegative(e: &Env, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(e, ShareTokenError::NegativeAmountError);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ShareToken;
+    use soroban_sdk::testutils::{Address as _, Ledger as _};
+
+    fn register_share_token(e: &Env) -> Address {
+        e.ledger().set_sequence_number(100);
+        e.ledger().set_max_entry_ttl(365 * storage::ONE_DAY_LEDGERS);
+        e.register(ShareToken {}, (Address::generate(e), Address::generate(e)))
+    }
+
+    #[test]
+    fn test_approve_and_spend_allowance() {
+        let e = Env::default();
+        let token = register_share_token(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&token, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve(&e, &samwise, &frodo, 50_0000000, expiration_ledger);
+            let allowance = storage::get_allowance(&e, &samwise, &frodo);
+            assert_eq!(allowance.amount, 50_0000000);
+            assert_eq!(allowance.expiration_ledger, expiration_ledger);
+
+            spend_allowance(&e, &samwise, &frodo, 20_0000000);
+            let allowance = storage::get_allowance(&e, &samwise, &frodo);
+            assert_eq!(allowance.amount, 30_0000000);
+            assert_eq!(allowance.expiration_ledger, expiration_ledger);
+
+            // allowances are scoped to the owner and spender
+            assert_eq!(storage::get_allowance(&e, &frodo, &samwise).amount, 0);
+
+            // approving 0 with an expired ledger clears the allowance
+            approve(&e, &samwise, &frodo, 0, 0);
+            assert_eq!(storage::get_allowance(&e, &samwise, &frodo).amount, 0);
+        });
+    }
+
+    #[test]
+    fn test_allowance_expires() {
+        let e = Env::default();
+        let token = register_share_token(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&token, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve(&e, &samwise, &frodo, 50_0000000, expiration_ledger);
+        });
+
+        e.ledger().set_sequence_number(200);
+        e.as_contract(&token, || {
+            assert_eq!(
+                storage::get_allowance(&e, &samwise, &frodo).amount,
+                50_0000000
+            );
+        });
+
+        e.ledger().set_sequence_number(201);
+        e.as_contract(&token, || {
+            assert_eq!(storage::get_allowance(&e, &samwise, &frodo).amount, 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #109)")]
+    fn test_approve_expired_ledger() {
+        let e = Env::default();
+        let token = register_share_token(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&token, || {
+            let expiration_ledger = e.ledger().sequence() - 1;
+            approve(&e, &samwise, &frodo, 1, expiration_ledger);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #109)")]
+    fn test_approve_past_max_ttl() {
+        let e = Env::default();
+        let token = register_share_token(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&token, || {
+            let expiration_ledger = e.ledger().max_live_until_ledger() + 1;
+            approve(&e, &samwise, &frodo, 1, expiration_ledger);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #8)")]
+    fn test_approve_negative() {
+        let e = Env::default();
+        let token = register_share_token(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&token, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve(&e, &samwise, &frodo, -1, expiration_ledger);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #9)")]
+    fn test_spend_allowance_over_allowance() {
+        let e = Env::default();
+        let token = register_share_token(&e);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+
+        e.as_contract(&token, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve(&e, &samwise, &frodo, 50_0000000, expiration_ledger);
+            spend_allowance(&e, &samwise, &frodo, 50_0000001);
+        });
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{allowance, events::ShareTokenEvents, metadata, storage, vault::FeeVaultClient};
+use soroban_sdk::{contract, contractimpl, token::TokenInterface, Address, Env, String};
+
+/// The SEP-41 token for a reserve vault's shares. The fee vault holds the shares, so balances,
+/// transfers and burns are forwarded to it. Allowances are kept by the share token.
+#[contract]
+pub struct ShareToken;
+
+#[contractimpl]
+impl ShareToken {
+    /// Initialize the contract
+    ///
+    /// ### Arguments
+    /// * `vault` - The fee vault address
+    /// * `reserve` - The address of the reserve whose vault shares the token represents
+    pub fn __constructor(e: Env, vault: Address, reserve: Address) {
+        storage::set_vault(&e, &vault);
+        storage::set_reserve(&e, &reserve);
+    }
+
+    /// Fetch the fee vault address
+    pub fn vault(e: Env) -> Address {
+        storage::get_vault(&e)
+    }
+
+    /// Fetch the address of the reserve whose vault shares the token represents
+    pub fn reserve(e: Env) -> Address {
+        storage::get_reserve(&e)
+    }
+}
+
+#[contractimpl]
+impl TokenInterface for ShareToken {
+    fn allowance(e: Env, from: Address, spender: Address) -> i128 {
+        storage::get_allowance(&e, &from, &spender).amount
+    }
+
+    fn approve(e: Env, from: Address, spender: Address, amount: i128, expiration_ledger: u32) {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        allowance::approve(&e, &from, &spender, amount, expiration_ledger);
+
+        ShareTokenEvents::approve(&e, &from, &spender, amount, expiration_ledger);
+    }
+
+    fn balance(e: Env, id: Address) -> i128 {
+        FeeVaultClient::new(&e, &storage::get_vault(&e)).get_shares(&storage::get_reserve(&e), &id)
+    }
+
+    fn transfer(e: Env, from: Address, to: Address, amount: i128) {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        FeeVaultClient::new(&e, &storage::get_vault(&e)).share_transfer(
+            &storage::get_reserve(&e),
+            &from,
+            &to,
+            &amount,
+        );
+
+        ShareTokenEvents::transfer(&e, &from, &to, amount);
+    }
+
+    fn transfer_from(e: Env, spender: Address, from: Address, to: Address, amount: i128) {
+        storage::extend_instance(&e);
+        spender.require_auth();
+
+        allowance::spend_allowance(&e, &from, &spender, amount);
+        FeeVaultClient::new(&e, &storage::get_vault(&e)).share_transfer(
+            &storage::get_reserve(&e),
+            &from,
+            &to,
+            &amount,
+        );
+
+        ShareTokenEvents::transfer(&e, &from, &to, amount);
+    }
+
+    fn burn(e: Env, from: Address, amount: i128) {
+        storage::extend_instance(&e);
+        from.require_auth();
+
+        FeeVaultClient::new(&e, &storage::get_vault(&e)).share_burn(
+            &storage::get_reserve(&e),
+            &from,
+            &amount,
+        );
+
+        ShareTokenEvents::burn(&e, &from, amount);
+    }
+
+    fn burn_from(e: Env, spender: Address, from: Address, amount: i128) {
+        storage::extend_instance(&e);
+        spender.require_auth();
+
+        allowance::spend_allowance(&e, &from, &spender, amount);
+        FeeVaultClient::new(&e, &storage::get_vault(&e)).share_burn(
+            &storage::get_reserve(&e),
+            &from,
+            &amount,
+        );
+
+        ShareTokenEvents::burn(&e, &from, amount);
+    }
+
+    fn decimals(e: Env) -> u32 {
+        metadata::decimals(&e, &storage::get_reserve(&e))
+    }
+
+    fn name(e: Env) -> String {
+        metadata::name(&e, &storage::get_reserve(&e))
+    }
+
+    fn symbol(e: Env) -> String {
+        metadata::symbol(&e, &storage::get_reserve(&e))
+    }
+}
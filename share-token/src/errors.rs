@@ -0,0 +1,13 @@
+use soroban_sdk::contracterror;
+
+/// The error codes for the contract. Codes match the fee vault's.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum ShareTokenError {
+    // Default errors to align with built-in contract
+    NegativeAmountError = 8,
+    AllowanceError = 9,
+
+    InvalidExpirationLedger = 109,
+}
@@ -0,0 +1,38 @@
+use soroban_sdk::{Address, Env, Symbol};
+
+pub struct ShareTokenEvents {}
+
+impl ShareTokenEvents {
+    /// Emitted when an allowance is set
+    ///
+    /// - topics - `["approve", from: Address, spender: Address]`
+    /// - data - `[amount: i128, expiration_ledger: u32]`
+    pub fn approve(
+        e: &Env,
+        from: &Address,
+        spender: &Address,
+        amount: i128,
+        expiration_ledger: u32,
+    ) {
+        let topics = (Symbol::new(e, "approve"), from.clone(), spender.clone());
+        e.events().publish(topics, (amount, expiration_ledger));
+    }
+
+    /// Emitted when shares are transferred
+    ///
+    /// - topics - `["transfer", from: Address, to: Address]`
+    /// - data - `amount: i128`
+    pub fn transfer(e: &Env, from: &Address, to: &Address, amount: i128) {
+        let topics = (Symbol::new(e, "transfer"), from.clone(), to.clone());
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when shares are burnt
+    ///
+    /// - topics - `["burn", from: Address]`
+    /// - data - `amount: i128`
+    pub fn burn(e: &Env, from: &Address, amount: i128) {
+        let topics = (Symbol::new(e, "burn"), from.clone());
+        e.events().publish(topics, amount);
+    }
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+#[cfg(test)]
+extern crate std;
+
+pub mod allowance;
+pub mod contract;
+pub mod errors;
+pub mod events;
+pub mod metadata;
+pub mod storage;
+pub mod vault;
+
+pub use contract::*;
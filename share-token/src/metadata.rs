@@ -0,0 +1,36 @@
+use soroban_sdk::{token::TokenClient, Address, Env, String};
+
+const NAME_PREFIX: &str = "Fee Vault ";
+const SYMBOL_PREFIX: &str = "fv";
+const MAX_NAME_LENGTH: usize = 128;
+
+/// Fetch the name of a reserve vault's shares, derived from the reserve's name
+pub fn name(e: &Env, reserve: &Address) -> String {
+    let reserve_name = TokenClient::new(e, reserve).name();
+    prefix_string(e, NAME_PREFIX, &reserve_name)
+}
+
+/// Fetch the symbol of a reserve vault's shares, derived from the reserve's symbol
+pub fn symbol(e: &Env, reserve: &Address) -> String {
+    let reserve_symbol = TokenClient::new(e, reserve).symbol();
+    prefix_string(e, SYMBOL_PREFIX, &reserve_symbol)
+}
+
+/// Fetch the decimals of a reserve vault's shares. Shares are minted 1:1 with bTokens for the
+/// first depositor, so they use the reserve's decimals.
+pub fn decimals(e: &Env, reserve: &Address) -> u32 {
+    TokenClient::new(e, reserve).decimals()
+}
+
+fn prefix_string(e: &Env, prefix: &str, value: &String) -> String {
+    let prefix_len = prefix.len();
+    let total_len = prefix_len + value.len() as usize;
+    let mut buffer = [0u8; MAX_NAME_LENGTH];
+    if total_len > MAX_NAME_LENGTH {
+        // fallback to the bare prefix if the value does not fit in the buffer
+        return String::from_str(e, prefix.trim_end());
+    }
+    buffer[..prefix_len].copy_from_slice(prefix.as_bytes());
+    value.copy_into_slice(&mut buffer[prefix_len..total_len]);
+    String::from_bytes(e, &buffer[..total_len])
+}
@@ -0,0 +1,125 @@
+use soroban_sdk::{contracttype, unwrap::UnwrapOptimized, Address, Env, Symbol};
+
+//********** Storage Keys **********//
+
+const VAULT_KEY: &str = "Vault";
+const RESERVE_KEY: &str = "Reserve";
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AllowanceKey {
+    from: Address,    // the user who owns the shares
+    spender: Address, // the user allowed to spend the shares
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ShareTokenDataKey {
+    Allowance(AllowanceKey),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ShareAllowance {
+    pub amount: i128,           // the number of shares the spender can spend
+    pub expiration_ledger: u32, // the ledger sequence the allowance expires at
+}
+
+//********** Storage Utils **********//
+
+pub const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5 seconds per ledger on average
+
+const LEDGER_BUMP_SHARED: u32 = 31 * ONE_DAY_LEDGERS;
+const LEDGER_THRESHOLD_SHARED: u32 = LEDGER_BUMP_SHARED - ONE_DAY_LEDGERS;
+
+/// Bump the instance lifetime by the defined amount
+pub fn extend_instance(e: &Env) {
+    e.storage()
+        .instance()
+        .extend_ttl(LEDGER_THRESHOLD_SHARED, LEDGER_BUMP_SHARED);
+}
+
+/********** Instance **********/
+
+/// Get the fee vault address
+pub fn get_vault(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, VAULT_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the fee vault address
+///
+/// ### Arguments
+/// * `vault` - The fee vault address
+pub fn set_vault(e: &Env, vault: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, VAULT_KEY), vault);
+}
+
+/// Get the reserve address
+pub fn get_reserve(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, RESERVE_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the reserve address
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+pub fn set_reserve(e: &Env, reserve: &Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, RESERVE_KEY), reserve);
+}
+
+/********** Temporary **********/
+
+/// Get the number of shares a spender is allowed to spend on behalf of a user. Returns an
+/// allowance of 0 if none exists or if it has expired.
+///
+/// ### Arguments
+/// * `from` - The address of the user who owns the shares
+/// * `spender` - The address of the spender
+pub fn get_allowance(e: &Env, from: &Address, spender: &Address) -> ShareAllowance {
+    let key = ShareTokenDataKey::Allowance(AllowanceKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    let result = e
+        .storage()
+        .temporary()
+        .get::<ShareTokenDataKey, ShareAllowance>(&key);
+    match result {
+        Some(allowance) if allowance.expiration_ledger >= e.ledger().sequence() => allowance,
+        _ => ShareAllowance {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+/// Set the number of shares a spender is allowed to spend on behalf of a user. The entry lives
+/// until the allowance expires.
+///
+/// ### Arguments
+/// * `from` - The address of the user who owns the shares
+/// * `spender` - The address of the spender
+/// * `allowance` - The allowance
+pub fn set_allowance(e: &Env, from: &Address, spender: &Address, allowance: &ShareAllowance) {
+    let key = ShareTokenDataKey::Allowance(AllowanceKey {
+        from: from.clone(),
+        spender: spender.clone(),
+    });
+    e.storage()
+        .temporary()
+        .set::<ShareTokenDataKey, ShareAllowance>(&key, allowance);
+    if allowance.amount > 0 {
+        let live_for = allowance.expiration_ledger - e.ledger().sequence();
+        e.storage().temporary().extend_ttl(&key, live_for, live_for);
+    }
+}
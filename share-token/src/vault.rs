@@ -0,0 +1,16 @@
+use soroban_sdk::{contractclient, Address, Env};
+
+/// The fee vault functions the share token calls. The fee vault holds the shares, and only
+/// accepts share transfers and burns from the reserve's share token.
+#[allow(dead_code)]
+#[contractclient(name = "FeeVaultClient")]
+pub trait FeeVault {
+    /// Fetch a user's position in shares
+    fn get_shares(e: Env, reserve: Address, user: Address) -> i128;
+
+    /// Transfer shares between users
+    fn share_transfer(e: Env, reserve: Address, from: Address, to: Address, amount: i128);
+
+    /// Burn a user's shares
+    fn share_burn(e: Env, reserve: Address, from: Address, amount: i128);
+}
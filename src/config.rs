@@ -0,0 +1,230 @@
+use soroban_sdk::{panic_with_error, Address, Env};
+
+use crate::{
+    constants::SCALAR_7,
+    emissions,
+    errors::FeeVaultError,
+    events::FeeVaultEvents,
+    reserve_vault,
+    storage::{self, CompoundConfig, ConfigChange, ConfigKey, FeeMode, Role},
+    validator::{
+        require_has_reserve, require_role, require_valid_fee_mode, require_valid_fee_recipients,
+    },
+};
+
+/// Fetch the config a change applies to
+pub fn config_key(change: &ConfigChange) -> ConfigKey {
+    match change {
+        ConfigChange::FeeMode(_) => ConfigKey::FeeMode,
+        ConfigChange::ReserveFeeMode(reserve, _) | ConfigChange::ClearReserveFeeMode(reserve) => {
+            ConfigKey::ReserveFeeMode(reserve.clone())
+        }
+        ConfigChange::EmissionsTakeRate(_) => ConfigKey::EmissionsTakeRate,
+        ConfigChange::CompoundConfig(_, _) => ConfigKey::CompoundConfig,
+        ConfigChange::ConfigDelay(_) => ConfigKey::ConfigDelay,
+        ConfigChange::FeeRecipients(_) => ConfigKey::FeeRecipients,
+    }
+}
+
+/// Require auth from the holder of the role that manages a config. Fee configs are managed by the
+/// fee manager, the compounding config by the reserve manager, the fee recipients by the treasurer
+/// and the config delay by the admin.
+pub fn require_auth(e: &Env, key: &ConfigKey) {
+    match key {
+        ConfigKey::FeeMode | ConfigKey::ReserveFeeMode(_) | ConfigKey::EmissionsTakeRate => {
+            require_role(e, &Role::FeeManager);
+        }
+        ConfigKey::CompoundConfig => {
+            require_role(e, &Role::ReserveManager);
+        }
+        ConfigKey::FeeRecipients => {
+            require_role(e, &Role::Treasurer);
+        }
+        ConfigKey::ConfigDelay => storage::get_admin(e).require_auth(),
+    }
+}
+
+/// Require that a config change is valid
+///
+/// ### Panics
+/// * `InvalidFeeModeValue` - If a fee mode value is not within 0 and 1_000_0000
+/// * `InvalidEmissionsTakeRate` - If the take rate is not within 0 and 1_000_0000
+/// * `ReserveNotFound` - If the reserve does not have a vault
+/// * `InvalidFeeRecipients` - If the fee recipients are not valid
+pub fn require_valid_change(e: &Env, change: &ConfigChange) {
+    match change {
+        ConfigChange::FeeMode(fee_mode) => require_valid_fee_mode(e, fee_mode),
+        ConfigChange::ReserveFeeMode(reserve, fee_mode) => {
+            require_has_reserve(e, reserve);
+            require_valid_fee_mode(e, fee_mode);
+        }
+        ConfigChange::ClearReserveFeeMode(reserve) => require_has_reserve(e, reserve),
+        ConfigChange::EmissionsTakeRate(take_rate) => {
+            if !(0..=SCALAR_7).contains(take_rate) {
+                panic_with_error!(e, FeeVaultError::InvalidEmissionsTakeRate);
+            }
+        }
+        ConfigChange::FeeRecipients(fee_recipients) => {
+            require_valid_fee_recipients(e, fee_recipients)
+        }
+        ConfigChange::CompoundConfig(_, _) | ConfigChange::ConfigDelay(_) => {}
+    }
+}
+
+/// Check if a config change can skip the timelock. This is true if no delay is set, or if the
+/// change only lowers fees or lengthens the delay.
+pub fn can_apply_immediately(e: &Env, change: &ConfigChange) -> bool {
+    let delay = storage::get_config_delay(e);
+    if delay == 0 {
+        return true;
+    }
+    match change {
+        ConfigChange::FeeMode(fee_mode) => is_fee_decrease(&storage::get_fee_mode(e), fee_mode),
+        ConfigChange::ReserveFeeMode(reserve, fee_mode) => {
+            is_fee_decrease(&reserve_vault::get_fee_mode(e, reserve), fee_mode)
+        }
+        ConfigChange::ClearReserveFeeMode(reserve) => is_fee_decrease(
+            &reserve_vault::get_fee_mode(e, reserve),
+            &storage::get_fee_mode(e),
+        ),
+        ConfigChange::EmissionsTakeRate(take_rate) => {
+            *take_rate <= storage::get_emissions_take_rate(e)
+        }
+        ConfigChange::CompoundConfig(_, _) | ConfigChange::FeeRecipients(_) => false,
+        ConfigChange::ConfigDelay(new_delay) => *new_delay >= delay,
+    }
+}
+
+/// Apply a config change
+pub fn apply_change(e: &Env, change: &ConfigChange) {
+    match change {
+        ConfigChange::FeeMode(fee_mode) => {
+            // Accrue interest for all reserves prior to updating the fee-mode, to avoid any retroactive effect
+            reserve_vault::accrue_interest_for_all_reserves(e);
+
+            storage::set_fee_mode(e, fee_mode.clone());
+
+            FeeVaultEvents::fee_mode_updated(e, fee_mode.clone());
+        }
+        ConfigChange::ReserveFeeMode(reserve, fee_mode) => {
+            set_reserve_fee_mode(e, reserve, Some(fee_mode.clone()))
+        }
+        ConfigChange::ClearReserveFeeMode(reserve) => set_reserve_fee_mode(e, reserve, None),
+        ConfigChange::EmissionsTakeRate(take_rate) => {
+            // Distribute emissions for all reserves prior to updating the take rate, to avoid any retroactive effect
+            emissions::update_emissions_for_all_reserves(e);
+
+            storage::set_emissions_take_rate(e, *take_rate);
+
+            FeeVaultEvents::emissions_take_rate_updated(e, *take_rate);
+        }
+        ConfigChange::CompoundConfig(router, keeper) => {
+            storage::set_compound_config(
+                e,
+                &CompoundConfig {
+                    router: router.clone(),
+                    keeper: keeper.clone(),
+                },
+            );
+
+            FeeVaultEvents::compound_config_updated(e, router, keeper);
+        }
+        ConfigChange::ConfigDelay(delay) => {
+            storage::set_config_delay(e, *delay);
+
+            FeeVaultEvents::config_delay_updated(e, *delay);
+        }
+        ConfigChange::FeeRecipients(fee_recipients) => {
+            storage::set_fee_recipients(e, fee_recipients);
+
+            FeeVaultEvents::fee_recipients_updated(e, fee_recipients.clone());
+        }
+    }
+}
+
+fn set_reserve_fee_mode(e: &Env, reserve: &Address, fee_mode: Option<FeeMode>) {
+    // Accrue interest for the reserve prior to updating its fee mode, to avoid any retroactive effect
+    let vault = reserve_vault::get_reserve_vault_updated(e, reserve);
+    storage::set_reserve_vault(e, reserve, &vault);
+
+    storage::set_reserve_fee_mode(e, reserve, &fee_mode);
+
+    FeeVaultEvents::reserve_fee_mode_updated(e, reserve, fee_mode);
+}
+
+/// Check if moving from one fee mode to another can only lower the admin's fees. Each fee mode is
+/// compared as a take rate up to an APR cap, where a take rate has no cap and an APR cap takes
+/// nothing below the cap.
+fn is_fee_decrease(old: &FeeMode, new: &FeeMode) -> bool {
+    let (old_take_rate, old_apr_cap) = as_hybrid(old);
+    let (new_take_rate, new_apr_cap) = as_hybrid(new);
+    new_take_rate <= old_take_rate && new_apr_cap >= old_apr_cap
+}
+
+fn as_hybrid(fee_mode: &FeeMode) -> (i128, i128) {
+    match fee_mode {
+        FeeMode::TakeRate(take_rate) => (*take_rate, i128::MAX),
+        FeeMode::AprCap(apr_cap) => (0, *apr_cap),
+        FeeMode::Hybrid(take_rate, apr_cap) => (*take_rate, *apr_cap),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_fee_decrease() {
+        // same mode
+        assert!(is_fee_decrease(
+            &FeeMode::TakeRate(0_2000000),
+            &FeeMode::TakeRate(0_1000000)
+        ));
+        assert!(!is_fee_decrease(
+            &FeeMode::TakeRate(0_1000000),
+            &FeeMode::TakeRate(0_2000000)
+        ));
+        assert!(is_fee_decrease(
+            &FeeMode::AprCap(0_0500000),
+            &FeeMode::AprCap(0_0800000)
+        ));
+        assert!(!is_fee_decrease(
+            &FeeMode::AprCap(0_0800000),
+            &FeeMode::AprCap(0_0500000)
+        ));
+        assert!(is_fee_decrease(
+            &FeeMode::Hybrid(0_1000000, 0_0800000),
+            &FeeMode::Hybrid(0_1000000, 0_0800000)
+        ));
+        assert!(!is_fee_decrease(
+            &FeeMode::Hybrid(0_1000000, 0_0800000),
+            &FeeMode::Hybrid(0_0500000, 0_0500000)
+        ));
+
+        // across modes
+        assert!(is_fee_decrease(
+            &FeeMode::Hybrid(0_1000000, 0_0800000),
+            &FeeMode::TakeRate(0_1000000)
+        ));
+        assert!(is_fee_decrease(
+            &FeeMode::Hybrid(0_1000000, 0_0800000),
+            &FeeMode::AprCap(0_0800000)
+        ));
+        assert!(is_fee_decrease(
+            &FeeMode::AprCap(0_0800000),
+            &FeeMode::Hybrid(0, 0_1000000)
+        ));
+        assert!(!is_fee_decrease(
+            &FeeMode::TakeRate(0_1000000),
+            &FeeMode::Hybrid(0_1000000, 0_0800000)
+        ));
+        assert!(!is_fee_decrease(
+            &FeeMode::TakeRate(0),
+            &FeeMode::AprCap(1_000_0000)
+        ));
+        assert!(!is_fee_decrease(
+            &FeeMode::AprCap(0_0800000),
+            &FeeMode::TakeRate(0_0100000)
+        ));
+    }
+}
@@ -2,5 +2,7 @@
 pub const SCALAR_7: i128 = 1_0000000;
 /// 1 with 12 decimal places
 pub const SCALAR_12: i128 = 1_000_000_000_000;
+/// 100% in basis points
+pub const MAX_BPS: u32 = 10_000;
 // seconds per year
 pub const SECONDS_PER_YEAR: i128 = 31536000;
@@ -1,13 +1,27 @@
 use crate::{
+    config, emissions,
     errors::FeeVaultError,
     events::FeeVaultEvents,
-    pool,
-    reserve_vault::{self, ReserveVault},
-    storage,
-    validator::{require_has_reserve, require_positive},
+    fees, pool,
+    reserve_vault::{self, ReserveVault, VaultAction},
+    router, share_token,
+    storage::{
+        self, ConfigChange, ConfigKey, DepositCaps, FeeMode, FeeRecipient, FeeRecipients,
+        PauseState, PendingAdmin, QueuedConfigChange, Role,
+    },
+    validator::{
+        is_deposits_allowed, is_fee_claims_allowed, require_allowlisted, require_before_deadline,
+        require_deposits_allowed, require_fee_claims_allowed, require_has_reserve,
+        require_max_shares_in, require_min_shares_out, require_nonnegative, require_not_deprecated,
+        require_not_frozen, require_positive, require_role, require_share_token,
+        require_valid_batch, require_valid_fee_mode, require_withdrawals_allowed,
+    },
 };
 
-use soroban_sdk::{contract, contractimpl, panic_with_error, Address, Env, Vec};
+use soroban_sdk::{
+    contract, contractimpl, panic_with_error, token::TokenClient, unwrap::UnwrapOptimized, Address,
+    BytesN, Env, Vec,
+};
 
 #[contract]
 pub struct FeeVault;
@@ -19,26 +33,28 @@ impl FeeVault {
     /// ### Arguments
     /// * `admin` - The admin address
     /// * `pool` - The blend pool address
-    /// * `is_apr_capped` - Whether the vault will be APR capped
-    /// * `value` - The APR cap if `is_apr_capped`, the admin take_rate otherwise
+    /// * `blnd` - The BLND token address the pool emits
+    /// * `fee_mode` - The fee mode for the vault
+    /// * `share_token_wasm_hash` - The wasm hash of the share token deployed for each reserve vault
     ///
     /// ### Panics
-    /// * `InvalidFeeModeValue` - If the value is not within 0 and 1_000_0000
-    pub fn __constructor(e: Env, admin: Address, pool: Address, is_apr_capped: bool, value: i128) {
+    /// * `InvalidFeeModeValue` - If a fee mode value is not within 0 and 1_000_0000
+    pub fn __constructor(
+        e: Env,
+        admin: Address,
+        pool: Address,
+        blnd: Address,
+        fee_mode: FeeMode,
+        share_token_wasm_hash: BytesN<32>,
+    ) {
         admin.require_auth();
-        if value < 0 || value > 1_000_0000 {
-            panic_with_error!(&e, FeeVaultError::InvalidFeeModeValue);
-        }
+        require_valid_fee_mode(&e, &fee_mode);
 
         storage::set_admin(&e, admin);
         storage::set_pool(&e, pool);
-        storage::set_fee_mode(
-            &e,
-            storage::FeeMode {
-                is_apr_capped,
-                value,
-            },
-        );
+        storage::set_blnd(&e, blnd);
+        storage::set_fee_mode(&e, fee_mode);
+        storage::set_share_token_wasm_hash(&e, share_token_wasm_hash);
     }
 
     //********** Read-Only ***********//
@@ -56,6 +72,18 @@ impl FeeVault {
         storage::get_reserve_vault_shares(&e, &reserve, &user)
     }
 
+    /// Fetch the address of a reserve vault's SEP-41 share token
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    pub fn get_share_token(e: Env, reserve: Address) -> Address {
+        require_has_reserve(&e, &reserve);
+        storage::get_share_token(&e, &reserve).unwrap_optimized()
+    }
+
     /// Fetch a user's position in bTokens
     ///
     /// ### Arguments
@@ -109,208 +137,1912 @@ impl FeeVault {
         }
     }
 
-    /// Get the blend pool address
+    /// Preview the number of shares minted for depositing `amount` underlying tokens
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    /// * `amount` - The amount of underlying tokens to deposit
     ///
     /// ### Returns
-    /// * `Address` - The blend pool address
-    pub fn get_pool(e: Env) -> Address {
-        storage::get_pool(&e)
+    /// * `i128` - The number of shares that would be minted
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not exist
+    pub fn preview_deposit(e: Env, reserve: Address, amount: i128) -> i128 {
+        let vault = reserve_vault::get_reserve_vault_updated(&e, &reserve);
+        let b_tokens = vault.underlying_to_b_tokens_down(amount);
+        vault.b_tokens_to_shares_down(b_tokens)
     }
 
-    /// Get the reserve vault data
+    /// Preview the amount of underlying tokens required to mint `shares`
     ///
     /// ### Arguments
     /// * `reserve` - The asset address of the reserve
+    /// * `shares` - The number of shares to mint
     ///
     /// ### Returns
-    /// * `ReserveData` - The reserve data
+    /// * `i128` - The amount of underlying tokens that would be supplied
     ///
     /// ### Panics
     /// * `ReserveNotFound` - If the reserve does not exist
-    pub fn get_reserve_vault(e: Env, reserve: Address) -> ReserveVault {
-        reserve_vault::get_reserve_vault_updated(&e, &reserve)
+    pub fn preview_mint(e: Env, reserve: Address, shares: i128) -> i128 {
+        let vault = reserve_vault::get_reserve_vault_updated(&e, &reserve);
+        let b_tokens = vault.shares_to_b_tokens_up(shares);
+        vault.b_tokens_to_underlying_up(b_tokens)
     }
 
-    //********** Read-Write Admin Only ***********//
-
-    /// ADMIN ONLY
-    /// Sets the Fee mode for the fee vault
+    /// Preview the number of shares burnt for withdrawing `amount` underlying tokens
     ///
     /// ### Arguments
-    /// * `e` - The environment object
-    /// * `is_apr_capped` - Whether the vault will be APR capped
-    /// * `value` - The APR cap if `is_apr_capped`, the admin take_rate otherwise
+    /// * `reserve` - The asset address of the reserve
+    /// * `amount` - The amount of underlying tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares that would be burnt
     ///
     /// ### Panics
-    /// * `InvalidFeeModeValue` - If the value is not within 0 and 1_000_0000
-    pub fn set_fee_mode(e: Env, is_apr_capped: bool, value: i128) {
-        storage::extend_instance(&e);
-        storage::get_admin(&e).require_auth();
-        if value < 0 || value > 1_000_0000 {
-            panic_with_error!(&e, FeeVaultError::InvalidFeeModeValue);
-        }
-
-        // Accrue interest for all reserves prior to updating the fee-mode, to avoid any retroactive effect
-        reserve_vault::accrue_interest_for_all_reserves(&e);
-
-        storage::set_fee_mode(
-            &e,
-            storage::FeeMode {
-                is_apr_capped,
-                value,
-            },
-        );
-
-        FeeVaultEvents::fee_mode_updated(&e, is_apr_capped, value);
+    /// * `ReserveNotFound` - If the reserve does not exist
+    pub fn preview_withdraw(e: Env, reserve: Address, amount: i128) -> i128 {
+        let vault = reserve_vault::get_reserve_vault_updated(&e, &reserve);
+        let b_tokens = vault.underlying_to_b_tokens_up(amount);
+        vault.b_tokens_to_shares_up(b_tokens)
     }
 
-    /// ADMIN ONLY
-    /// Sets the admin address for the fee vault
+    /// Preview the amount of underlying tokens withdrawn for redeeming `shares`
     ///
     /// ### Arguments
-    /// * `e` - The environment object
-    /// * `admin` - The new admin address to set
-    pub fn set_admin(e: Env, admin: Address) {
-        storage::extend_instance(&e);
-        storage::get_admin(&e).require_auth();
-        admin.require_auth();
-        storage::set_admin(&e, admin);
+    /// * `reserve` - The asset address of the reserve
+    /// * `shares` - The number of shares to redeem
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of underlying tokens that would be withdrawn
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not exist
+    pub fn preview_redeem(e: Env, reserve: Address, shares: i128) -> i128 {
+        let vault = reserve_vault::get_reserve_vault_updated(&e, &reserve);
+        let b_tokens = vault.shares_to_b_tokens_down(shares);
+        vault.b_tokens_to_underlying_down(b_tokens)
     }
 
-    /// ADMIN ONLY
-    /// Add a new reserve vault
+    /// Fetch the maximum amount of underlying tokens a user can withdraw. This is limited by both
+    /// the user's position and the liquidity available in the pool.
     ///
     /// ### Arguments
-    /// * `reserve_address` - The address of the reserve to add
+    /// * `reserve` - The asset address of the reserve
+    /// * `user` - The address of the user
     ///
-    /// ### Panics
-    /// * `ReserveAlreadyExists` - If the reserve already has a vault
-    pub fn add_reserve_vault(e: Env, reserve_address: Address) {
-        storage::extend_instance(&e);
-        storage::get_admin(&e).require_auth();
-        if storage::has_reserve_vault(&e, &reserve_address) {
-            panic_with_error!(&e, FeeVaultError::ReserveAlreadyExists);
+    /// ### Returns
+    /// * `i128` - The maximum amount of underlying tokens the user can withdraw, or 0 if the reserve
+    ///            does not have a vault or the user has no shares
+    pub fn max_withdraw(e: Env, reserve: Address, user: Address) -> i128 {
+        let shares = storage::get_reserve_vault_shares(&e, &reserve, &user);
+        if shares > 0 {
+            let vault = reserve_vault::get_reserve_vault_updated(&e, &reserve);
+            let b_tokens = vault.shares_to_b_tokens_down(shares);
+            let underlying = vault.b_tokens_to_underlying_down(b_tokens);
+            underlying.min(pool::reserve_available_liquidity(&e, &reserve))
         } else {
-            storage::set_reserve_vault(
-                &e,
-                &reserve_address,
-                &ReserveVault {
-                    address: reserve_address.clone(),
-                    b_rate: pool::reserve_b_rate(&e, &reserve_address),
-                    last_update_timestamp: e.ledger().timestamp(),
-                    total_shares: 0,
-                    total_b_tokens: 0,
-                    accrued_fees: 0,
-                },
-            );
-
-            storage::add_reserve_to_reserves(&e, reserve_address.clone());
-            FeeVaultEvents::new_reserve_vault(&e, &reserve_address);
+            0
         }
     }
 
-    /// ADMIN ONLY
-    /// Claims emissions for the given reserves from the pool. This is a passthrough function
-    /// that invokes the pool's "claim" function as the contract. More details can be found
-    /// here: https://github.com/blend-capital/blend-contracts/blob/v1.0.0/pool/src/contract.rs#L192
+    /// Fetch the maximum amount of underlying tokens a user can deposit, as limited by the pool's
+    /// supply cap for the reserve and the reserve vault's deposit caps
     ///
     /// ### Arguments
-    /// * `reserve_token_ids` - The ids of the reserves to claiming emissions for
-    /// * `to` - The address to send the emissions to
+    /// * `reserve` - The asset address of the reserve
+    /// * `user` - The address of the user receiving the shares
     ///
     /// ### Returns
-    /// * `i128` - The amount of blnd tokens claimed
-    pub fn claim_emissions(e: Env, reserve_token_ids: Vec<u32>, to: Address) -> i128 {
-        storage::extend_instance(&e);
-        let admin = storage::get_admin(&e);
-        admin.require_auth();
-        let emissions = pool::claim(&e, &reserve_token_ids, &to);
-        FeeVaultEvents::vault_emissions_claim(&e, &admin, reserve_token_ids, emissions);
-        emissions
+    /// * `i128` - The maximum amount of underlying tokens that can be deposited, or 0 if the reserve
+    ///            does not have a vault, deposits into it are blocked or the user can't deposit
+    pub fn max_deposit(e: Env, reserve: Address, user: Address) -> i128 {
+        if !storage::has_reserve_vault(&e, &reserve)
+            || !is_deposits_allowed(&e, &reserve)
+            || (storage::get_allowlist_enabled(&e) && !storage::is_allowlisted(&e, &user))
+            || storage::is_frozen(&e, &reserve, &user)
+        {
+            return 0;
+        }
+        pool::reserve_available_supply(&e, &reserve)
+            .min(reserve_vault::deposit_capacity(&e, &reserve, &user))
     }
 
-    /// ADMIN ONLY
-    /// Claims fees for the given reserves from the vault
+    /// Fetch the amount of underlying tokens a user can deposit before reaching the reserve vault's
+    /// deposit caps
     ///
     /// ### Arguments
-    /// * `reserve` - The address of the reserve to claim fees for
-    /// * `to` - The address to send the fees to
+    /// * `reserve` - The asset address of the reserve
+    /// * `user` - The address of the user
     ///
     /// ### Returns
-    /// * `i128` - The number of b_tokens burnt
+    /// * `i128` - The capacity left under the deposit caps, i128::MAX if the reserve vault is
+    ///            uncapped, or 0 if the reserve does not have a vault
+    pub fn get_deposit_capacity(e: Env, reserve: Address, user: Address) -> i128 {
+        if storage::has_reserve_vault(&e, &reserve) {
+            reserve_vault::deposit_capacity(&e, &reserve, &user)
+        } else {
+            0
+        }
+    }
+
+    /// Fetch the deposit caps of a reserve vault
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
     ///
     /// ### Panics
     /// * `ReserveNotFound` - If the reserve does not have a vault
-    /// * `InsufficientAccruedFees` - If there are no fees to claim
-    pub fn claim_fees(e: Env, reserve: Address, to: Address) -> i128 {
-        storage::extend_instance(&e);
-        let admin = storage::get_admin(&e);
-        admin.require_auth();
+    pub fn get_deposit_caps(e: Env, reserve: Address) -> DepositCaps {
         require_has_reserve(&e, &reserve);
+        storage::get_deposit_caps(&e, &reserve)
+    }
 
-        let (b_tokens_burnt, amount) = reserve_vault::claim_fees(&e, &reserve);
-        pool::withdraw(&e, &reserve, &to, amount);
-
-        FeeVaultEvents::vault_fee_claim(&e, &reserve, &admin, amount, b_tokens_burnt);
-        b_tokens_burnt
+    /// Fetch a user's accrued BLND emissions for a reserve vault. Emissions earned by the vault
+    /// since the reserve's last deposit, withdrawal, transfer or claim are not included.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    /// * `user` - The address of the user
+    ///
+    /// ### Returns
+    /// * `i128` - The user's accrued BLND emissions
+    pub fn get_user_emissions(e: Env, reserve: Address, user: Address) -> i128 {
+        emissions::get_user_accrued(&e, &reserve, &user)
     }
 
-    //********** Read-Write ***********//
+    /// Fetch the admin's take rate on emissions
+    ///
+    /// ### Returns
+    /// * `i128` - The admin's take rate on emissions, 7 decimal precision
+    pub fn get_emissions_take_rate(e: Env) -> i128 {
+        storage::get_emissions_take_rate(&e)
+    }
 
-    /// Deposits tokens into the fee vault for a specific reserve
+    /// Fetch the number of shares a spender can withdraw on behalf of a user
     ///
     /// ### Arguments
-    /// * `reserve` - The address of the reserve to deposit
-    /// * `user` - The address of the user making the deposit
-    /// * `amount` - The amount of tokens to deposit
+    /// * `reserve` - The asset address of the reserve
+    /// * `owner` - The address of the user who owns the shares
+    /// * `spender` - The address of the spender
     ///
     /// ### Returns
-    /// * `i128` - The number of shares minted for the user
+    /// * `i128` - The number of shares the spender can withdraw, or 0 if the allowance has expired
+    pub fn withdraw_allowance(e: Env, reserve: Address, owner: Address, spender: Address) -> i128 {
+        storage::get_withdraw_allowance(&e, &reserve, &owner, &spender).amount
+    }
+
+    /// Fetch the pause state set for a reserve vault, or for the whole vault if no reserve is given.
+    /// A reserve vault is bound by the stricter of its own pause state and the whole vault's.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve, or None for the whole vault
     ///
     /// ### Panics
     /// * `ReserveNotFound` - If the reserve does not have a vault
-    /// * `InvalidAmount` - If the amount is less than or equal to 0
-    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
-    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
-    pub fn deposit(e: Env, reserve: Address, user: Address, amount: i128) -> i128 {
-        storage::extend_instance(&e);
-        user.require_auth();
-        require_has_reserve(&e, &reserve);
-        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+    pub fn get_pause_state(e: Env, reserve: Option<Address>) -> PauseState {
+        match reserve {
+            Some(reserve) => {
+                require_has_reserve(&e, &reserve);
+                storage::get_reserve_pause_state(&e, &reserve)
+            }
+            None => storage::get_pause_state(&e),
+        }
+    }
 
-        pool::supply(&e, &reserve, &user, amount);
-        let (b_tokens_minted, new_shares) = reserve_vault::deposit(&e, &reserve, &user, amount);
+    /// Fetch whether deposits are restricted to allowlisted addresses
+    pub fn is_allowlist_enabled(e: Env) -> bool {
+        storage::get_allowlist_enabled(&e)
+    }
 
-        FeeVaultEvents::vault_deposit(&e, &reserve, &user, amount, new_shares, b_tokens_minted);
-        new_shares
+    /// Fetch whether a user is on the deposit allowlist
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user
+    pub fn is_allowlisted(e: Env, user: Address) -> bool {
+        storage::is_allowlisted(&e, &user)
     }
 
-    /// Withdraws tokens from the fee vault for a specific reserve
+    /// Fetch whether a user's position in a reserve vault is frozen
     ///
     /// ### Arguments
-    /// * `reserve` - The address of the reserve to withdraw
-    /// * `user` - The address of the user making the withdrawal
-    /// * `amount` - The amount of tokens to withdraw
+    /// * `reserve` - The asset address of the reserve
+    /// * `user` - The address of the user
+    pub fn is_frozen(e: Env, reserve: Address, user: Address) -> bool {
+        storage::is_frozen(&e, &reserve, &user)
+    }
+
+    /// Fetch the recipients fees are distributed to, if set
+    pub fn get_fee_recipients(e: Env) -> Option<FeeRecipients> {
+        storage::get_fee_recipients(&e)
+    }
+
+    /// Fetch the pending admin proposal, if one exists. The proposal may have expired.
+    pub fn get_pending_admin(e: Env) -> Option<PendingAdmin> {
+        storage::get_pending_admin(&e)
+    }
+
+    /// Fetch the address holding a role. This is the admin if the role has not been granted.
+    ///
+    /// ### Arguments
+    /// * `role` - The role
+    pub fn get_role(e: Env, role: Role) -> Address {
+        storage::get_role(&e, &role).unwrap_or_else(|| storage::get_admin(&e))
+    }
+
+    /// Fetch the delay, in seconds, queued config changes wait before they can be applied
+    pub fn get_config_delay(e: Env) -> u64 {
+        storage::get_config_delay(&e)
+    }
+
+    /// Fetch the config change queued for a config, if one exists
+    ///
+    /// ### Arguments
+    /// * `key` - The config the change applies to
+    pub fn get_queued_config_change(e: Env, key: ConfigKey) -> Option<QueuedConfigChange> {
+        storage::get_queued_config(&e, &key)
+    }
+
+    /// Fetch a reserve vault's drawdown, the percentage its bRate is below its highest bRate.
+    /// Fees are not accrued until the bRate recovers past its highest bRate.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
     ///
     /// ### Returns
-    /// * `i128` - The number of shares burnt
+    /// * `i128` - The drawdown with 7 decimals, or 0 if the reserve does not have a vault
+    pub fn get_drawdown(e: Env, reserve: Address) -> i128 {
+        if storage::has_reserve_vault(&e, &reserve) {
+            reserve_vault::get_reserve_vault_updated(&e, &reserve).drawdown()
+        } else {
+            0
+        }
+    }
+
+    /// Fetch the fee mode of a reserve vault. Reserve vaults without their own fee mode use the
+    /// global fee mode.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    pub fn get_fee_mode(e: Env, reserve: Address) -> FeeMode {
+        reserve_vault::get_fee_mode(&e, &reserve)
+    }
+
+    /// Check if a reserve vault compounds its emissions into the reserve
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    ///
+    /// ### Returns
+    /// * `bool` - True if the reserve vault compounds its emissions, false otherwise or if the
+    ///            reserve does not have a vault
+    pub fn is_compounding_vault(e: Env, reserve: Address) -> bool {
+        storage::get_reserve_is_compounding(&e, &reserve)
+    }
+
+    /// Check if a reserve vault is deprecated
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    ///
+    /// ### Returns
+    /// * `bool` - True if the reserve vault is deprecated, false otherwise or if the reserve does not
+    ///            have a vault
+    pub fn is_deprecated_vault(e: Env, reserve: Address) -> bool {
+        storage::get_reserve_is_deprecated(&e, &reserve)
+    }
+
+    /// Check if a reserve vault supplies to the pool as collateral
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    ///
+    /// ### Returns
+    /// * `bool` - True if the reserve vault supplies as collateral, false otherwise or if the reserve
+    ///            does not have a vault
+    pub fn is_collateral_vault(e: Env, reserve: Address) -> bool {
+        storage::get_reserve_is_collateral(&e, &reserve)
+    }
+
+    /// Get the blend pool address
+    ///
+    /// ### Returns
+    /// * `Address` - The blend pool address
+    pub fn get_pool(e: Env) -> Address {
+        storage::get_pool(&e)
+    }
+
+    /// Get the reserve vault data
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    ///
+    /// ### Returns
+    /// * `ReserveData` - The reserve data
     ///
     /// ### Panics
-    /// * `ReserveNotFound` - If the reserve does not have a vault
-    /// * `InvalidAmount` - If the amount is less than or equal to 0
-    /// * `BalanceError` - If the user does not have enough shares to withdraw the amount
-    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
-    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
-    pub fn withdraw(e: Env, reserve: Address, user: Address, amount: i128) -> i128 {
-        storage::extend_instance(&e);
-        user.require_auth();
-        require_has_reserve(&e, &reserve);
-        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+    /// * `ReserveNotFound` - If the reserve does not exist
+    pub fn get_reserve_vault(e: Env, reserve: Address) -> ReserveVault {
+        reserve_vault::get_reserve_vault_updated(&e, &reserve)
+    }
 
-        pool::withdraw(&e, &reserve, &user, amount);
-        let (b_tokens_burnt, burnt_shares) = reserve_vault::withdraw(&e, &reserve, &user, amount);
+    //********** Read-Write Admin Only ***********//
 
-        FeeVaultEvents::vault_withdraw(&e, &reserve, &user, amount, burnt_shares, b_tokens_burnt);
-        burnt_shares
+    /// FEE MANAGER ONLY
+    /// Sets the global Fee mode for the fee vault, used by reserve vaults without their own fee mode
+    ///
+    /// ### Arguments
+    /// * `e` - The environment object
+    /// * `fee_mode` - The new fee mode
+    ///
+    /// ### Panics
+    /// * `InvalidFeeModeValue` - If a fee mode value is not within 0 and 1_000_0000
+    /// * `TimelockRequired` - If the change raises fees and a config delay is set
+    pub fn set_fee_mode(e: Env, fee_mode: FeeMode) {
+        FeeVault::execute_config_change(&e, ConfigChange::FeeMode(fee_mode));
+    }
+
+    /// FEE MANAGER ONLY
+    /// Sets a reserve vault's own fee mode, which takes precedence over the global fee mode
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `fee_mode` - The reserve vault's fee mode, or None to use the global fee mode
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `InvalidFeeModeValue` - If a fee mode value is not within 0 and 1_000_0000
+    /// * `TimelockRequired` - If the change raises fees and a config delay is set
+    pub fn set_reserve_fee_mode(e: Env, reserve: Address, fee_mode: Option<FeeMode>) {
+        let change = match fee_mode {
+            Some(fee_mode) => ConfigChange::ReserveFeeMode(reserve, fee_mode),
+            None => ConfigChange::ClearReserveFeeMode(reserve),
+        };
+        FeeVault::execute_config_change(&e, change);
+    }
+
+    /// FEE MANAGER ONLY
+    /// Sets the admin's take rate on the BLND emissions earned by depositors. This is separate
+    /// from the fee mode, which only applies to interest.
+    ///
+    /// ### Arguments
+    /// * `take_rate` - The admin's take rate on emissions, 7 decimal precision
+    ///
+    /// ### Panics
+    /// * `InvalidEmissionsTakeRate` - If the take rate is not within 0 and 1_000_0000
+    /// * `TimelockRequired` - If the change raises the take rate and a config delay is set
+    pub fn set_emissions_take_rate(e: Env, take_rate: i128) {
+        FeeVault::execute_config_change(&e, ConfigChange::EmissionsTakeRate(take_rate));
+    }
+
+    /// RESERVE MANAGER ONLY
+    /// Sets the swap router and keeper used to compound emissions
+    ///
+    /// ### Arguments
+    /// * `router` - The swap router used to swap BLND for reserve assets
+    /// * `keeper` - The address allowed to compound emissions
+    ///
+    /// ### Panics
+    /// * `TimelockRequired` - If a config delay is set
+    pub fn set_compound_config(e: Env, router: Address, keeper: Address) {
+        FeeVault::execute_config_change(&e, ConfigChange::CompoundConfig(router, keeper));
+    }
+
+    /// TREASURER ONLY
+    /// Sets the recipients fees are distributed to by `distribute_fees`
+    ///
+    /// ### Arguments
+    /// * `recipients` - The recipients and their weights in basis points, which must sum to 10_000
+    /// * `dust_recipient` - The address paid any rounding dust
+    ///
+    /// ### Panics
+    /// * `InvalidFeeRecipients` - If the fee recipients are not valid
+    /// * `TimelockRequired` - If a config delay is set
+    pub fn set_fee_recipients(e: Env, recipients: Vec<FeeRecipient>, dust_recipient: Address) {
+        FeeVault::execute_config_change(
+            &e,
+            ConfigChange::FeeRecipients(FeeRecipients {
+                recipients,
+                dust_recipient,
+            }),
+        );
+    }
+
+    /// ADMIN ONLY
+    /// Sets the delay, in seconds, queued config changes wait before they can be applied
+    ///
+    /// ### Arguments
+    /// * `delay` - The delay in seconds
+    ///
+    /// ### Panics
+    /// * `TimelockRequired` - If the change shortens the delay
+    pub fn set_config_delay(e: Env, delay: u64) {
+        FeeVault::execute_config_change(&e, ConfigChange::ConfigDelay(delay));
+    }
+
+    /// ROLE ONLY
+    /// Queues a config change. It can be applied with `apply_config_change` once the config
+    /// delay has passed. Replaces any change already queued for the same config. Requires auth
+    /// from the holder of the role that manages the config.
+    ///
+    /// ### Arguments
+    /// * `change` - The config change to queue
+    ///
+    /// ### Returns
+    /// * `u64` - The timestamp the change can be applied at
+    ///
+    /// ### Panics
+    /// * If the change is invalid
+    pub fn queue_config_change(e: Env, change: ConfigChange) -> u64 {
+        storage::extend_instance(&e);
+        config::require_auth(&e, &config::config_key(&change));
+        config::require_valid_change(&e, &change);
+
+        let unlock_time = e.ledger().timestamp() + storage::get_config_delay(&e);
+        storage::set_queued_config(
+            &e,
+            &config::config_key(&change),
+            &QueuedConfigChange {
+                change: change.clone(),
+                unlock_time,
+            },
+        );
+
+        FeeVaultEvents::config_change_queued(&e, change, unlock_time);
+        unlock_time
+    }
+
+    /// Applies a queued config change once its unlock time has passed. Can be called by anyone.
+    ///
+    /// ### Arguments
+    /// * `key` - The config the change applies to
+    ///
+    /// ### Panics
+    /// * `ConfigChangeNotFound` - If no change is queued for the config
+    /// * `ConfigChangeLocked` - If the change's unlock time has not passed
+    pub fn apply_config_change(e: Env, key: ConfigKey) {
+        storage::extend_instance(&e);
+        let queued = storage::get_queued_config(&e, &key)
+            .unwrap_or_else(|| panic_with_error!(&e, FeeVaultError::ConfigChangeNotFound));
+        if e.ledger().timestamp() < queued.unlock_time {
+            panic_with_error!(&e, FeeVaultError::ConfigChangeLocked);
+        }
+        // the vault may have changed since the change was queued
+        config::require_valid_change(&e, &queued.change);
+
+        storage::del_queued_config(&e, &key);
+        config::apply_change(&e, &queued.change);
+    }
+
+    /// ROLE ONLY
+    /// Cancels a queued config change. Requires auth from the holder of the role that manages the
+    /// config.
+    ///
+    /// ### Arguments
+    /// * `key` - The config the change applies to
+    ///
+    /// ### Panics
+    /// * `ConfigChangeNotFound` - If no change is queued for the config
+    pub fn cancel_config_change(e: Env, key: ConfigKey) {
+        storage::extend_instance(&e);
+        config::require_auth(&e, &key);
+        if storage::get_queued_config(&e, &key).is_none() {
+            panic_with_error!(&e, FeeVaultError::ConfigChangeNotFound);
+        }
+
+        storage::del_queued_config(&e, &key);
+
+        FeeVaultEvents::config_change_cancelled(&e, key);
+    }
+
+    /// RESERVE MANAGER ONLY
+    /// Sets whether a reserve vault compounds its emissions into the reserve instead of
+    /// distributing them to depositors
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `is_compounding` - Whether the reserve vault compounds its emissions
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    pub fn set_compounding(e: Env, reserve: Address, is_compounding: bool) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::ReserveManager);
+        require_has_reserve(&e, &reserve);
+
+        // Distribute emissions earned under the current mode before switching
+        let total_shares = storage::get_reserve_vault(&e, &reserve).total_shares;
+        emissions::update_reserve_emissions(&e, &reserve, total_shares);
+
+        storage::set_reserve_is_compounding(&e, &reserve, is_compounding);
+
+        FeeVaultEvents::compounding_updated(&e, &reserve, is_compounding);
+    }
+
+    /// RESERVE MANAGER ONLY
+    /// Sets the deposit caps of a reserve vault, in underlying tokens. Deposits that would take the
+    /// reserve vault's total deposits or the depositor's position over a cap are rejected. Existing
+    /// positions are not affected if a cap is lowered below them.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `reserve_cap` - The most the reserve vault's depositors can hold, or None for no cap
+    /// * `user_cap` - The most a single depositor can hold, or None for no cap
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `NegativeAmountError` - If a cap is negative
+    pub fn set_deposit_caps(
+        e: Env,
+        reserve: Address,
+        reserve_cap: Option<i128>,
+        user_cap: Option<i128>,
+    ) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::ReserveManager);
+        require_has_reserve(&e, &reserve);
+        for cap in [reserve_cap, user_cap].into_iter().flatten() {
+            require_nonnegative(&e, cap);
+        }
+
+        let caps = DepositCaps {
+            reserve_cap,
+            user_cap,
+        };
+        storage::set_deposit_caps(&e, &reserve, &caps);
+
+        FeeVaultEvents::deposit_caps_updated(&e, &reserve, caps);
+    }
+
+    /// ADMIN ONLY
+    /// Sets the admin address for the fee vault
+    ///
+    /// ### Arguments
+    /// * `e` - The environment object
+    /// * `admin` - The new admin address to set
+    pub fn set_admin(e: Env, admin: Address) {
+        storage::extend_instance(&e);
+        let old_admin = storage::get_admin(&e);
+        old_admin.require_auth();
+        admin.require_auth();
+        storage::set_admin(&e, admin.clone());
+        storage::del_pending_admin(&e);
+
+        FeeVaultEvents::admin_updated(&e, &old_admin, &admin);
+    }
+
+    /// ADMIN ONLY
+    /// Proposes a new admin for the fee vault. The new admin must call `accept_admin` before the
+    /// proposal expires. Replaces any pending proposal.
+    ///
+    /// ### Arguments
+    /// * `new_admin` - The proposed admin address
+    ///
+    /// ### Returns
+    /// * `u32` - The ledger sequence the proposal expires at
+    pub fn propose_admin(e: Env, new_admin: Address) -> u32 {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        let expiration_ledger = e.ledger().sequence() + storage::ADMIN_PROPOSAL_LEDGERS;
+        storage::set_pending_admin(
+            &e,
+            &PendingAdmin {
+                admin: new_admin.clone(),
+                expiration_ledger,
+            },
+        );
+
+        FeeVaultEvents::admin_proposed(&e, &admin, &new_admin, expiration_ledger);
+        expiration_ledger
+    }
+
+    /// PENDING ADMIN ONLY
+    /// Accepts a pending admin proposal, making the caller the admin
+    ///
+    /// ### Panics
+    /// * `NoPendingAdmin` - If there is no pending admin proposal
+    /// * `AdminProposalExpired` - If the proposal has expired
+    pub fn accept_admin(e: Env) {
+        storage::extend_instance(&e);
+        let pending = storage::get_pending_admin(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, FeeVaultError::NoPendingAdmin));
+        if e.ledger().sequence() > pending.expiration_ledger {
+            panic_with_error!(&e, FeeVaultError::AdminProposalExpired);
+        }
+        pending.admin.require_auth();
+
+        let old_admin = storage::get_admin(&e);
+        storage::set_admin(&e, pending.admin.clone());
+        storage::del_pending_admin(&e);
+
+        FeeVaultEvents::admin_updated(&e, &old_admin, &pending.admin);
+    }
+
+    /// ADMIN ONLY
+    /// Cancels a pending admin proposal
+    ///
+    /// ### Panics
+    /// * `NoPendingAdmin` - If there is no pending admin proposal
+    pub fn cancel_admin_proposal(e: Env) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+        let pending = storage::get_pending_admin(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, FeeVaultError::NoPendingAdmin));
+
+        storage::del_pending_admin(&e);
+
+        FeeVaultEvents::admin_proposal_cancelled(&e, &admin, &pending.admin);
+    }
+
+    /// GUARDIAN ONLY
+    /// Pauses a reserve vault, or the whole vault if no reserve is given. The guardian can only make
+    /// the pause state stricter. A reserve vault is bound by the stricter of its own pause state and
+    /// the whole vault's.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to pause, or None to pause the whole vault
+    /// * `state` - The new pause state
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `InvalidPauseState` - If the new pause state is not stricter than the current one
+    pub fn pause(e: Env, reserve: Option<Address>, state: PauseState) {
+        storage::extend_instance(&e);
+        let guardian = require_role(&e, &Role::Guardian);
+        if state <= FeeVault::get_pause_state(e.clone(), reserve.clone()) {
+            panic_with_error!(&e, FeeVaultError::InvalidPauseState);
+        }
+
+        FeeVault::execute_set_pause_state(&e, &guardian, reserve, state);
+    }
+
+    /// ADMIN ONLY
+    /// Unpauses a reserve vault, or the whole vault if no reserve is given. The admin can only make
+    /// the pause state less strict.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to unpause, or None to unpause the whole vault
+    /// * `state` - The new pause state
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `InvalidPauseState` - If the new pause state is not less strict than the current one
+    pub fn unpause(e: Env, reserve: Option<Address>, state: PauseState) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+        if state >= FeeVault::get_pause_state(e.clone(), reserve.clone()) {
+            panic_with_error!(&e, FeeVaultError::InvalidPauseState);
+        }
+
+        FeeVault::execute_set_pause_state(&e, &admin, reserve, state);
+    }
+
+    /// ADMIN ONLY
+    /// Sets whether deposits are restricted to allowlisted addresses. While enabled, only
+    /// allowlisted addresses can receive shares through deposits or transfers. Withdrawals stay
+    /// open to existing holders.
+    ///
+    /// ### Arguments
+    /// * `enabled` - Whether the allowlist is enabled
+    pub fn set_allowlist_enabled(e: Env, enabled: bool) {
+        storage::extend_instance(&e);
+        let admin = storage::get_admin(&e);
+        admin.require_auth();
+
+        storage::set_allowlist_enabled(&e, enabled);
+
+        FeeVaultEvents::allowlist_enabled_updated(&e, enabled);
+    }
+
+    /// ADMIN ONLY
+    /// Adds addresses to the deposit allowlist
+    ///
+    /// ### Arguments
+    /// * `users` - The addresses to add
+    pub fn add_to_allowlist(e: Env, users: Vec<Address>) {
+        FeeVault::execute_update_allowlist(&e, users, true);
+    }
+
+    /// ADMIN ONLY
+    /// Removes addresses from the deposit allowlist. Removed addresses keep their shares and can
+    /// still withdraw.
+    ///
+    /// ### Arguments
+    /// * `users` - The addresses to remove
+    pub fn remove_from_allowlist(e: Env, users: Vec<Address>) {
+        FeeVault::execute_update_allowlist(&e, users, false);
+    }
+
+    /// COMPLIANCE ONLY
+    /// Freezes a user's position in a reserve vault. A frozen user cannot deposit, withdraw or
+    /// transfer shares in the reserve vault.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `user` - The address of the user to freeze
+    /// * `reason` - The reason code for the freeze
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is already frozen
+    pub fn freeze(e: Env, reserve: Address, user: Address, reason: u32) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::Compliance);
+        require_has_reserve(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+
+        storage::set_frozen(&e, &reserve, &user, true);
+
+        FeeVaultEvents::user_frozen(&e, &reserve, &user, reason);
+    }
+
+    /// COMPLIANCE ONLY
+    /// Unfreezes a user's position in a reserve vault
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `user` - The address of the user to unfreeze
+    /// * `reason` - The reason code for the unfreeze
+    ///
+    /// ### Panics
+    /// * `UserNotFrozen` - If the user's position is not frozen
+    pub fn unfreeze(e: Env, reserve: Address, user: Address, reason: u32) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::Compliance);
+        if !storage::is_frozen(&e, &reserve, &user) {
+            panic_with_error!(&e, FeeVaultError::UserNotFrozen);
+        }
+
+        storage::set_frozen(&e, &reserve, &user, false);
+
+        FeeVaultEvents::user_unfrozen(&e, &reserve, &user, reason);
+    }
+
+    /// COMPLIANCE ONLY
+    /// Redeems all of a frozen user's shares in a reserve vault, withdrawing the underlying tokens
+    /// to a designated address. The user's position stays frozen. Forced exits are allowed while
+    /// the reserve vault or the whole vault is paused.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `user` - The address of the frozen user
+    /// * `to` - The address to send the underlying tokens to
+    /// * `reason` - The reason code for the forced exit
+    ///
+    /// ### Returns
+    /// * `VaultAction` - The shares burnt, bTokens burnt and underlying tokens withdrawn
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserNotFrozen` - If the user's position is not frozen
+    /// * `BalanceError` - If the user does not have any shares
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn force_exit(
+        e: Env,
+        reserve: Address,
+        user: Address,
+        to: Address,
+        reason: u32,
+    ) -> VaultAction {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::Compliance);
+        require_has_reserve(&e, &reserve);
+        if !storage::is_frozen(&e, &reserve, &user) {
+            panic_with_error!(&e, FeeVaultError::UserNotFrozen);
+        }
+        let shares = storage::get_reserve_vault_shares(&e, &reserve, &user);
+        require_positive(&e, shares, FeeVaultError::BalanceError);
+
+        let action = Self::execute_redeem(&e, &reserve, &user, &to, shares);
+
+        FeeVaultEvents::forced_exit(&e, &reserve, &user, &to, reason, action.underlying, shares);
+        action
+    }
+
+    /// ADMIN ONLY
+    /// Grants a role to an address, replacing any address the role was granted to
+    ///
+    /// ### Arguments
+    /// * `role` - The role to grant
+    /// * `account` - The address to grant the role to
+    pub fn grant_role(e: Env, role: Role, account: Address) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+
+        storage::set_role(&e, &role, &account);
+
+        FeeVaultEvents::role_granted(&e, role, &account);
+    }
+
+    /// ADMIN ONLY
+    /// Revokes a role grant, returning the role to the admin
+    ///
+    /// ### Arguments
+    /// * `role` - The role to revoke
+    ///
+    /// ### Panics
+    /// * `RoleNotGranted` - If the role has not been granted
+    pub fn revoke_role(e: Env, role: Role) {
+        storage::extend_instance(&e);
+        storage::get_admin(&e).require_auth();
+        if storage::get_role(&e, &role).is_none() {
+            panic_with_error!(&e, FeeVaultError::RoleNotGranted);
+        }
+
+        storage::del_role(&e, &role);
+
+        FeeVaultEvents::role_revoked(&e, role);
+    }
+
+    /// RESERVE MANAGER ONLY
+    /// Add a new reserve vault, and deploy the SEP-41 token for its shares
+    ///
+    /// ### Arguments
+    /// * `reserve_address` - The address of the reserve to add
+    /// * `is_collateral` - Whether the reserve vault supplies to the pool as collateral
+    /// * `fee_mode` - The reserve vault's own fee mode, or None to use the global fee mode
+    ///
+    /// ### Panics
+    /// * `ReserveAlreadyExists` - If the reserve already has a vault
+    /// * `InvalidFeeModeValue` - If a fee mode value is not within 0 and 1_000_0000
+    pub fn add_reserve_vault(
+        e: Env,
+        reserve_address: Address,
+        is_collateral: bool,
+        fee_mode: Option<FeeMode>,
+    ) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::ReserveManager);
+        if let Some(fee_mode) = &fee_mode {
+            require_valid_fee_mode(&e, fee_mode);
+        }
+        if storage::has_reserve_vault(&e, &reserve_address) {
+            panic_with_error!(&e, FeeVaultError::ReserveAlreadyExists);
+        } else {
+            let b_rate = pool::reserve_b_rate(&e, &reserve_address);
+            storage::set_reserve_vault(
+                &e,
+                &reserve_address,
+                &ReserveVault {
+                    address: reserve_address.clone(),
+                    b_rate,
+                    high_water_b_rate: b_rate,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    total_shares: 0,
+                    total_b_tokens: 0,
+                    accrued_fees: 0,
+                },
+            );
+
+            storage::set_reserve_is_collateral(&e, &reserve_address, is_collateral);
+            storage::set_reserve_fee_mode(&e, &reserve_address, &fee_mode);
+
+            share_token::deploy(&e, &reserve_address);
+
+            storage::add_reserve_to_reserves(&e, reserve_address.clone());
+            FeeVaultEvents::new_reserve_vault(&e, &reserve_address, is_collateral);
+        }
+    }
+
+    /// RESERVE MANAGER ONLY
+    /// Deprecates a reserve vault. Deposits into a deprecated reserve vault are blocked, while
+    /// withdrawals and fee claims continue as normal.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to deprecate
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `ReserveDeprecated` - If the reserve vault is already deprecated
+    pub fn deprecate_reserve_vault(e: Env, reserve: Address) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::ReserveManager);
+        require_has_reserve(&e, &reserve);
+        require_not_deprecated(&e, &reserve);
+
+        storage::set_reserve_is_deprecated(&e, &reserve, true);
+
+        FeeVaultEvents::reserve_vault_deprecated(&e, &reserve);
+    }
+
+    /// RESERVE MANAGER ONLY
+    /// Removes a deprecated reserve vault once it holds no shares and no fees. The reserve is
+    /// dropped from the vault's reserves, and its vault data, config and emission data are deleted.
+    ///
+    /// The reserve's emissions are settled first. The admin's emissions, any emissions held for
+    /// compounding and any bToken rounding dust left without shares are sent to the treasurer.
+    /// Users can still claim the emissions they accrued with `claim_user_emissions`.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to remove
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `ReserveNotDeprecated` - If the reserve vault is not deprecated
+    /// * `ReserveNotEmpty` - If the reserve vault has shares or accrued fees
+    pub fn remove_reserve_vault(e: Env, reserve: Address) {
+        storage::extend_instance(&e);
+        require_role(&e, &Role::ReserveManager);
+        require_has_reserve(&e, &reserve);
+        if !storage::get_reserve_is_deprecated(&e, &reserve) {
+            panic_with_error!(&e, FeeVaultError::ReserveNotDeprecated);
+        }
+        let vault = reserve_vault::get_reserve_vault_updated(&e, &reserve);
+        if vault.total_shares != 0 || vault.accrued_fees != 0 {
+            panic_with_error!(&e, FeeVaultError::ReserveNotEmpty);
+        }
+        let treasurer = Self::get_role(e.clone(), Role::Treasurer);
+
+        // with no shares left, all unclaimed emissions belong to the admin
+        let emissions_data = emissions::update_reserve_emissions(&e, &reserve, 0);
+        let emissions = emissions_data.admin_accrued + emissions_data.compound_accrued;
+        if emissions > 0 {
+            TokenClient::new(&e, &storage::get_blnd(&e)).transfer(
+                &e.current_contract_address(),
+                &treasurer,
+                &emissions,
+            );
+        }
+
+        // bTokens left without shares are rounding dust no depositor can redeem
+        let dust = vault.b_tokens_to_underlying_down(vault.total_b_tokens);
+        if dust > 0 {
+            pool::withdraw(&e, &reserve, &treasurer, dust);
+        }
+
+        storage::del_reserve_vault(&e, &reserve);
+        storage::remove_reserve_from_reserves(&e, &reserve);
+
+        FeeVaultEvents::reserve_vault_removed(&e, &reserve, &treasurer, dust, emissions);
+    }
+
+    /// TREASURER ONLY
+    /// Claims the admin's share of emissions for the given reserves. The emissions the vault has
+    /// earned for each reserve are claimed from the pool and distributed first, so depositors keep
+    /// their share. Ids that do not belong to a reserve vault's bTokens are ignored.
+    ///
+    /// ### Arguments
+    /// * `reserve_token_ids` - The ids of the reserves to claiming emissions for
+    /// * `to` - The address to send the emissions to
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of blnd tokens claimed
+    pub fn claim_emissions(e: Env, reserve_token_ids: Vec<u32>, to: Address) -> i128 {
+        storage::extend_instance(&e);
+        let treasurer = require_role(&e, &Role::Treasurer);
+
+        let mut reserves: Vec<Address> = Vec::new(&e);
+        let mut claimed_ids: Vec<u32> = Vec::new(&e);
+        for reserve in storage::get_reserves(&e) {
+            let reserve_token_id = pool::reserve_b_token_id(&e, &reserve);
+            if reserve_token_ids.contains(reserve_token_id) {
+                reserves.push_back(reserve);
+                claimed_ids.push_back(reserve_token_id);
+            }
+        }
+        FeeVault::execute_claim_emissions(&e, &treasurer, reserves, claimed_ids, &to)
+    }
+
+    /// TREASURER ONLY
+    /// Claims the admin's share of emissions for all reserve vaults. The reserve token ids are
+    /// derived from each reserve's index in the pool.
+    ///
+    /// ### Arguments
+    /// * `to` - The address to send the emissions to
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of blnd tokens claimed
+    pub fn claim_all_emissions(e: Env, to: Address) -> i128 {
+        storage::extend_instance(&e);
+        let treasurer = require_role(&e, &Role::Treasurer);
+
+        let reserves = storage::get_reserves(&e);
+        let mut reserve_token_ids: Vec<u32> = Vec::new(&e);
+        for reserve in reserves.iter() {
+            reserve_token_ids.push_back(pool::reserve_b_token_id(&e, &reserve));
+        }
+        FeeVault::execute_claim_emissions(&e, &treasurer, reserves, reserve_token_ids, &to)
+    }
+
+    /// TREASURER ONLY
+    /// Claims fees for the given reserves from the vault
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to claim fees for
+    /// * `to` - The address to send the fees to
+    ///
+    /// ### Returns
+    /// * `i128` - The number of b_tokens burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `WithdrawOnly` or `VaultPaused` - If fee claims are paused
+    /// * `InsufficientAccruedFees` - If there are no fees to claim
+    pub fn claim_fees(e: Env, reserve: Address, to: Address) -> i128 {
+        storage::extend_instance(&e);
+        let treasurer = require_role(&e, &Role::Treasurer);
+        require_has_reserve(&e, &reserve);
+        require_fee_claims_allowed(&e, &reserve);
+
+        let (b_tokens_burnt, amount) = reserve_vault::claim_fees(&e, &reserve);
+        pool::withdraw(&e, &reserve, &to, amount);
+
+        FeeVaultEvents::vault_fee_claim(&e, &reserve, &treasurer, &to, amount, b_tokens_burnt);
+        b_tokens_burnt
+    }
+
+    /// TREASURER ONLY
+    /// Claims the fees accrued by every reserve vault, skipping reserve vaults without fees or with
+    /// fee claims paused. Each reserve vault's claim is capped at the liquidity available in the
+    /// pool, so an illiquid reserve does not block the others. The fees are withdrawn from the pool
+    /// with a single submit.
+    ///
+    /// ### Arguments
+    /// * `to` - The address to send the fees to
+    ///
+    /// ### Returns
+    /// * `Vec<(Address, i128)>` - The amount of underlying tokens claimed for each reserve
+    pub fn claim_all_fees(e: Env, to: Address) -> Vec<(Address, i128)> {
+        storage::extend_instance(&e);
+        let treasurer = require_role(&e, &Role::Treasurer);
+
+        let mut claims: Vec<(Address, i128)> = Vec::new(&e);
+        for reserve in storage::get_reserves(&e) {
+            if !is_fee_claims_allowed(&e, &reserve) {
+                continue;
+            }
+
+            let (b_tokens_burnt, amount) = reserve_vault::claim_available_fees(&e, &reserve);
+            if amount <= 0 {
+                continue;
+            }
+            FeeVaultEvents::vault_fee_claim(&e, &reserve, &treasurer, &to, amount, b_tokens_burnt);
+            claims.push_back((reserve, amount));
+        }
+        if !claims.is_empty() {
+            pool::withdraw_many(&e, &to, &claims);
+        }
+        claims
+    }
+
+    /// TREASURER ONLY
+    /// Claims part of the fees accrued by a reserve vault. This allows fees to be claimed when the
+    /// pool lacks the liquidity to withdraw all of them.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to claim fees for
+    /// * `to` - The address to send the fees to
+    /// * `amount` - The amount of underlying tokens to claim
+    ///
+    /// ### Returns
+    /// * `i128` - The number of b_tokens burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `WithdrawOnly` or `VaultPaused` - If fee claims are paused
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InsufficientAccruedFees` - If the amount is more than the accrued fees
+    pub fn claim_fees_amount(e: Env, reserve: Address, to: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        let treasurer = require_role(&e, &Role::Treasurer);
+        require_has_reserve(&e, &reserve);
+        require_fee_claims_allowed(&e, &reserve);
+
+        let b_tokens_burnt = reserve_vault::claim_fees_amount(&e, &reserve, amount);
+        pool::withdraw(&e, &reserve, &to, amount);
+
+        FeeVaultEvents::vault_fee_claim(&e, &reserve, &treasurer, &to, amount, b_tokens_burnt);
+        b_tokens_burnt
+    }
+
+    /// TREASURER ONLY
+    /// Converts the fees accrued by a reserve vault into shares of the vault. The shares earn
+    /// interest alongside depositors and can be redeemed later. Nothing is withdrawn from the pool,
+    /// so fees can be realized when the pool lacks liquidity.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to convert fees for
+    /// * `to` - The address to mint the shares to
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares minted
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `WithdrawOnly` or `VaultPaused` - If fee claims are paused
+    /// * `InsufficientAccruedFees` - If there are no fees to convert
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    /// * `ReserveCapExceeded` or `UserCapExceeded` - If the conversion exceeds a deposit cap
+    /// * `NotAllowlisted` - If the allowlist is enabled and `to` is not on it
+    /// * `UserFrozen` - If `to`'s position is frozen
+    pub fn convert_fees_to_shares(e: Env, reserve: Address, to: Address) -> i128 {
+        storage::extend_instance(&e);
+        let treasurer = require_role(&e, &Role::Treasurer);
+        require_has_reserve(&e, &reserve);
+        require_not_deprecated(&e, &reserve);
+        require_fee_claims_allowed(&e, &reserve);
+        require_allowlisted(&e, &to);
+        require_not_frozen(&e, &reserve, &to);
+
+        let (b_tokens, shares) = reserve_vault::convert_fees(&e, &reserve, &to);
+
+        FeeVaultEvents::vault_fee_conversion(&e, &reserve, &treasurer, &to, b_tokens, shares);
+        shares
+    }
+
+    /// Distributes the fees accrued by a reserve vault to the fee recipients by weight. Can be
+    /// called by anyone.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to distribute fees for
+    ///
+    /// ### Returns
+    /// * `i128` - The number of b_tokens burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `WithdrawOnly` or `VaultPaused` - If fee claims are paused
+    /// * `FeeRecipientsNotSet` - If no fee recipients are set
+    /// * `InsufficientAccruedFees` - If there are no fees to distribute
+    pub fn distribute_fees(e: Env, reserve: Address) -> i128 {
+        storage::extend_instance(&e);
+        require_has_reserve(&e, &reserve);
+        require_fee_claims_allowed(&e, &reserve);
+        let fee_recipients = storage::get_fee_recipients(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, FeeVaultError::FeeRecipientsNotSet));
+
+        let (b_tokens_burnt, amount) = reserve_vault::claim_fees(&e, &reserve);
+        let vault_address = e.current_contract_address();
+        pool::withdraw(&e, &reserve, &vault_address, amount);
+
+        let amounts = fees::split_fees(&e, &fee_recipients, amount);
+        let token = TokenClient::new(&e, &reserve);
+        for (recipient, recipient_amount) in amounts.iter() {
+            if recipient_amount > 0 {
+                token.transfer(&vault_address, &recipient, &recipient_amount);
+            }
+        }
+
+        FeeVaultEvents::vault_fee_distribution(&e, &reserve, amounts, b_tokens_burnt);
+        b_tokens_burnt
+    }
+
+    //********** Read-Write ***********//
+
+    /// Deposits tokens into the fee vault for a specific reserve
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to deposit
+    /// * `user` - The address of the user making the deposit
+    /// * `amount` - The amount of tokens to deposit
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares minted for the user
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `DepositsBlocked`, `WithdrawOnly` or `VaultPaused` - If deposits are paused
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `NotAllowlisted` - If the allowlist is enabled and the user is not on it
+    /// * `ReserveCapExceeded` or `UserCapExceeded` - If the deposit exceeds a deposit cap
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    pub fn deposit(e: Env, reserve: Address, user: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_deposits_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        require_allowlisted(&e, &user);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+
+        Self::execute_deposit(&e, &reserve, &user, &user, amount)
+    }
+
+    /// Deposits tokens into the fee vault for a specific reserve, failing if the deposit mints
+    /// fewer shares than `min_shares_out` or executes after `deadline`
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to deposit
+    /// * `user` - The address of the user making the deposit
+    /// * `amount` - The amount of tokens to deposit
+    /// * `min_shares_out` - The optional minimum number of shares to mint
+    /// * `deadline` - The optional ledger timestamp the deposit must execute by
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares minted for the user
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `DepositsBlocked`, `WithdrawOnly` or `VaultPaused` - If deposits are paused
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `NotAllowlisted` - If the allowlist is enabled and the user is not on it
+    /// * `ReserveCapExceeded` or `UserCapExceeded` - If the deposit exceeds a deposit cap
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `DeadlineExpired` - If the ledger timestamp is past the deadline
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    /// * `MinSharesOutNotMet` - If fewer shares than `min_shares_out` are minted
+    pub fn deposit_with_limits(
+        e: Env,
+        reserve: Address,
+        user: Address,
+        amount: i128,
+        min_shares_out: Option<i128>,
+        deadline: Option<u64>,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_deposits_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        require_allowlisted(&e, &user);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+        require_before_deadline(&e, deadline);
+
+        let new_shares = Self::execute_deposit(&e, &reserve, &user, &user, amount);
+        require_min_shares_out(&e, new_shares, min_shares_out);
+        new_shares
+    }
+
+    /// Deposits tokens into the fee vault for a specific reserve on behalf of another user
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to deposit
+    /// * `from` - The address of the user supplying the tokens
+    /// * `to` - The address of the user receiving the shares
+    /// * `amount` - The amount of tokens to deposit
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares minted for `to`
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If `to`'s position is frozen
+    /// * `DepositsBlocked`, `WithdrawOnly` or `VaultPaused` - If deposits are paused
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `NotAllowlisted` - If the allowlist is enabled and `to` is not on it
+    /// * `ReserveCapExceeded` or `UserCapExceeded` - If the deposit exceeds a deposit cap
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    pub fn deposit_for(e: Env, reserve: Address, from: Address, to: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        from.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_deposits_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &to);
+        require_allowlisted(&e, &to);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+
+        Self::execute_deposit(&e, &reserve, &from, &to, amount)
+    }
+
+    /// Deposits tokens into the fee vault for several reserves with a single pool submit
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user making the deposits
+    /// * `deposits` - The (reserve, amount) pairs to deposit
+    ///
+    /// ### Returns
+    /// * `Vec<i128>` - The number of shares minted for the user for each deposit
+    ///
+    /// ### Panics
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `DepositsBlocked`, `WithdrawOnly` or `VaultPaused` - If deposits are paused
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `NotAllowlisted` - If the allowlist is enabled and the user is not on it
+    /// * `ReserveCapExceeded` or `UserCapExceeded` - If the deposit exceeds a deposit cap
+    /// * `InvalidAmount` - If no deposits are provided or an amount is less than or equal to 0
+    /// * `ReserveNotFound` - If a reserve does not have a vault
+    /// * `DuplicateReserve` - If a reserve is included more than once
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    /// * `InvalidSharesMinted` - If the amount of shares minted is less than or equal to 0
+    pub fn deposit_many(e: Env, user: Address, deposits: Vec<(Address, i128)>) -> Vec<i128> {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_valid_batch(&e, &deposits);
+        require_allowlisted(&e, &user);
+        for (reserve, _) in deposits.iter() {
+            require_deposits_allowed(&e, &reserve);
+            require_not_frozen(&e, &reserve, &user);
+        }
+
+        pool::supply_many(&e, &user, &deposits);
+        let mut minted_shares = Vec::new(&e);
+        for (reserve, amount) in deposits.iter() {
+            let (b_tokens_minted, new_shares) = reserve_vault::deposit(&e, &reserve, &user, amount);
+
+            FeeVaultEvents::vault_deposit(
+                &e,
+                &reserve,
+                &user,
+                &user,
+                amount,
+                new_shares,
+                b_tokens_minted,
+            );
+            minted_shares.push_back(new_shares);
+        }
+        minted_shares
+    }
+
+    /// Withdraws tokens from the fee vault for a specific reserve
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to withdraw
+    /// * `user` - The address of the user making the withdrawal
+    /// * `amount` - The amount of tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `BalanceError` - If the user does not have enough shares to withdraw the amount
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn withdraw(e: Env, reserve: Address, user: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+
+        Self::execute_withdraw(&e, &reserve, &user, &user, amount)
+    }
+
+    /// Withdraws tokens from the fee vault for several reserves with a single pool submit
+    ///
+    /// ### Arguments
+    /// * `user` - The address of the user making the withdrawals
+    /// * `withdrawals` - The (reserve, amount) pairs to withdraw
+    ///
+    /// ### Returns
+    /// * `Vec<i128>` - The number of shares burnt for each withdrawal
+    ///
+    /// ### Panics
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `InvalidAmount` - If no withdrawals are provided or an amount is less than or equal to 0
+    /// * `ReserveNotFound` - If a reserve does not have a vault
+    /// * `DuplicateReserve` - If a reserve is included more than once
+    /// * `BalanceError` - If the user does not have enough shares to withdraw an amount
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawals
+    pub fn withdraw_many(e: Env, user: Address, withdrawals: Vec<(Address, i128)>) -> Vec<i128> {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_valid_batch(&e, &withdrawals);
+        for (reserve, _) in withdrawals.iter() {
+            require_withdrawals_allowed(&e, &reserve);
+            require_not_frozen(&e, &reserve, &user);
+        }
+
+        pool::withdraw_many(&e, &user, &withdrawals);
+        let mut burnt_shares = Vec::new(&e);
+        for (reserve, amount) in withdrawals.iter() {
+            let (b_tokens_burnt, shares) = reserve_vault::withdraw(&e, &reserve, &user, amount);
+
+            FeeVaultEvents::vault_withdraw(
+                &e,
+                &reserve,
+                &user,
+                &user,
+                amount,
+                shares,
+                b_tokens_burnt,
+            );
+            burnt_shares.push_back(shares);
+        }
+        burnt_shares
+    }
+
+    /// Withdraws tokens from the fee vault for a specific reserve, failing if the withdrawal burns
+    /// more shares than `max_shares_in` or executes after `deadline`
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to withdraw
+    /// * `user` - The address of the user making the withdrawal
+    /// * `amount` - The amount of tokens to withdraw
+    /// * `max_shares_in` - The optional maximum number of shares to burn
+    /// * `deadline` - The optional ledger timestamp the withdrawal must execute by
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `DeadlineExpired` - If the ledger timestamp is past the deadline
+    /// * `BalanceError` - If the user does not have enough shares to withdraw the amount
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    /// * `MaxSharesInExceeded` - If more shares than `max_shares_in` are burnt
+    pub fn withdraw_with_limits(
+        e: Env,
+        reserve: Address,
+        user: Address,
+        amount: i128,
+        max_shares_in: Option<i128>,
+        deadline: Option<u64>,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+        require_before_deadline(&e, deadline);
+
+        let burnt_shares = Self::execute_withdraw(&e, &reserve, &user, &user, amount);
+        require_max_shares_in(&e, burnt_shares, max_shares_in);
+        burnt_shares
+    }
+
+    /// Withdraws tokens from the fee vault for a specific reserve to another address
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to withdraw
+    /// * `from` - The address of the user whose shares are burnt
+    /// * `to` - The address receiving the withdrawn tokens
+    /// * `amount` - The amount of tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If `from`'s position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `BalanceError` - If the user does not have enough shares to withdraw the amount
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn withdraw_to(e: Env, reserve: Address, from: Address, to: Address, amount: i128) -> i128 {
+        storage::extend_instance(&e);
+        from.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &from);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+
+        Self::execute_withdraw(&e, &reserve, &from, &to, amount)
+    }
+
+    /// Withdraws tokens from the fee vault for a specific reserve on behalf of a user, spending
+    /// the spender's withdraw allowance
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to withdraw
+    /// * `spender` - The address of the spender making the withdrawal
+    /// * `owner` - The address of the user whose shares are burnt
+    /// * `to` - The address receiving the withdrawn tokens
+    /// * `amount` - The amount of tokens to withdraw
+    ///
+    /// ### Returns
+    /// * `i128` - The number of shares burnt
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the owner's position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `InvalidAmount` - If the amount is less than or equal to 0
+    /// * `BalanceError` - If the owner does not have enough shares to withdraw the amount
+    /// * `AllowanceError` - If the spender's withdraw allowance is less than the shares burnt
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn withdraw_from(
+        e: Env,
+        reserve: Address,
+        spender: Address,
+        owner: Address,
+        to: Address,
+        amount: i128,
+    ) -> i128 {
+        storage::extend_instance(&e);
+        spender.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &owner);
+        require_positive(&e, amount, FeeVaultError::InvalidAmount);
+
+        let burnt_shares = Self::execute_withdraw(&e, &reserve, &owner, &to, amount);
+        share_token::spend_withdraw_allowance(&e, &reserve, &owner, &spender, burnt_shares);
+        burnt_shares
+    }
+
+    /// Set the number of shares a spender can withdraw on behalf of a user
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve
+    /// * `owner` - The address of the user who owns the shares
+    /// * `spender` - The address of the spender
+    /// * `shares` - The number of shares the spender can withdraw
+    /// * `expiration_ledger` - The ledger sequence the allowance expires at
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `NegativeAmountError` - If the number of shares is negative
+    /// * `InvalidExpirationLedger` - If the shares are positive and the expiration ledger is in the past
+    ///   or past the max TTL of a storage entry
+    pub fn approve_withdraw(
+        e: Env,
+        reserve: Address,
+        owner: Address,
+        spender: Address,
+        shares: i128,
+        expiration_ledger: u32,
+    ) {
+        storage::extend_instance(&e);
+        owner.require_auth();
+        require_has_reserve(&e, &reserve);
+
+        share_token::approve_withdraw(&e, &reserve, &owner, &spender, shares, expiration_ledger);
+
+        FeeVaultEvents::approve_withdraw(&e, &reserve, &owner, &spender, shares, expiration_ledger);
+    }
+
+    /// Mints an exact number of shares from the fee vault for a specific reserve, supplying
+    /// the required underlying tokens from the user
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to deposit
+    /// * `user` - The address of the user making the deposit
+    /// * `shares` - The number of shares to mint
+    ///
+    /// ### Returns
+    /// * `VaultAction` - The shares minted, bTokens minted and underlying tokens supplied
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `DepositsBlocked`, `WithdrawOnly` or `VaultPaused` - If deposits are paused
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `NotAllowlisted` - If the allowlist is enabled and the user is not on it
+    /// * `ReserveCapExceeded` or `UserCapExceeded` - If the deposit exceeds a deposit cap
+    /// * `InvalidAmount` - If the number of shares is less than or equal to 0
+    /// * `InvalidBTokensMinted` - If the amount of bTokens minted is less than or equal to 0
+    pub fn mint(e: Env, reserve: Address, user: Address, shares: i128) -> VaultAction {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_deposits_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        require_allowlisted(&e, &user);
+        require_positive(&e, shares, FeeVaultError::InvalidAmount);
+
+        let (b_tokens_minted, amount) = reserve_vault::mint(&e, &reserve, &user, shares);
+        pool::supply(&e, &reserve, &user, amount);
+
+        FeeVaultEvents::vault_deposit(&e, &reserve, &user, &user, amount, shares, b_tokens_minted);
+        VaultAction {
+            shares,
+            b_tokens: b_tokens_minted,
+            underlying: amount,
+        }
+    }
+
+    /// Redeems an exact number of shares from the fee vault for a specific reserve, withdrawing
+    /// the underlying tokens to the user
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to withdraw
+    /// * `user` - The address of the user making the withdrawal
+    /// * `shares` - The number of shares to burn
+    ///
+    /// ### Returns
+    /// * `VaultAction` - The shares burnt, bTokens burnt and underlying tokens withdrawn
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `InvalidAmount` - If the number of shares is less than or equal to 0
+    /// * `BalanceError` - If the user does not have enough shares
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn redeem(e: Env, reserve: Address, user: Address, shares: i128) -> VaultAction {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        require_positive(&e, shares, FeeVaultError::InvalidAmount);
+
+        Self::execute_redeem(&e, &reserve, &user, &user, shares)
+    }
+
+    /// Redeems all of a user's shares from the fee vault for a specific reserve, withdrawing
+    /// the underlying tokens to the user
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to withdraw
+    /// * `user` - The address of the user making the withdrawal
+    ///
+    /// ### Returns
+    /// * `VaultAction` - The shares burnt, bTokens burnt and underlying tokens withdrawn
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `UserFrozen` - If the user's position is frozen
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `BalanceError` - If the user does not have any shares
+    /// * `InvalidBTokensBurnt` - If the amount of bTokens burnt is less than or equal to 0
+    /// * `InsufficientReserves` - If the pool doesn't have enough reserves to complete the withdrawal
+    pub fn withdraw_all(e: Env, reserve: Address, user: Address) -> VaultAction {
+        storage::extend_instance(&e);
+        user.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &user);
+        let shares = storage::get_reserve_vault_shares(&e, &reserve, &user);
+        require_positive(&e, shares, FeeVaultError::BalanceError);
+
+        Self::execute_redeem(&e, &reserve, &user, &user, shares)
+    }
+
+    /// Claims a user's accrued BLND emissions for a reserve vault
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to claim emissions for
+    /// * `user` - The address of the user claiming
+    /// * `to` - The address to send the emissions to
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of BLND claimed
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault, and the user has no emissions
+    ///   left from a removed reserve vault
+    /// * `UserFrozen` - If the user's position is frozen
+    pub fn claim_user_emissions(e: Env, reserve: Address, user: Address, to: Address) -> i128 {
+        storage::extend_instance(&e);
+        user.require_auth();
+        if !storage::has_user_emissions(&e, &reserve, &user) {
+            require_has_reserve(&e, &reserve);
+        }
+        require_not_frozen(&e, &reserve, &user);
+
+        let claimed = emissions::claim_user(&e, &reserve, &user);
+        if claimed > 0 {
+            TokenClient::new(&e, &storage::get_blnd(&e)).transfer(
+                &e.current_contract_address(),
+                &to,
+                &claimed,
+            );
+        }
+
+        FeeVaultEvents::user_emissions_claim(&e, &reserve, &user, &to, claimed);
+        claimed
+    }
+
+    /// KEEPER ONLY
+    /// Compounds the emissions held for a reserve vault. The BLND is swapped for the reserve asset
+    /// through the swap router and supplied to the pool, raising the share price for all depositors.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The address of the reserve to compound
+    /// * `min_amount_out` - The minimum amount of the reserve asset to receive from the swap
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of the reserve asset supplied to the pool
+    ///
+    /// ### Panics
+    /// * `CompoundConfigNotSet` - If no swap router and keeper are set
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `DepositsBlocked`, `WithdrawOnly` or `VaultPaused` - If deposits are paused
+    /// * `ReserveDeprecated` - If the reserve vault is deprecated
+    /// * `MinAmountOutNotMet` - If the swap returns less than `min_amount_out`
+    pub fn compound_emissions(e: Env, reserve: Address, min_amount_out: i128) -> i128 {
+        storage::extend_instance(&e);
+        let config = storage::get_compound_config(&e)
+            .unwrap_or_else(|| panic_with_error!(&e, FeeVaultError::CompoundConfigNotSet));
+        config.keeper.require_auth();
+        require_has_reserve(&e, &reserve);
+        require_deposits_allowed(&e, &reserve);
+
+        let blnd_amount = emissions::take_compound(&e, &reserve);
+        if blnd_amount <= 0 {
+            return 0;
+        }
+        let amount = router::swap_blnd(&e, &config.router, &reserve, blnd_amount, min_amount_out);
+        pool::supply_from_vault(&e, &reserve, amount);
+        let b_tokens = reserve_vault::compound(&e, &reserve, amount);
+
+        FeeVaultEvents::vault_compound(&e, &reserve, &config.keeper, blnd_amount, amount, b_tokens);
+        amount
+    }
+
+    //********** Share Token ***********//
+
+    /// SHARE TOKEN ONLY
+    /// Transfer reserve vault shares between users. Called by the reserve's share token, which
+    /// authorizes `from` or spends the spender's allowance.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    /// * `from` - The address of the user sending the shares
+    /// * `to` - The address of the user receiving the shares
+    /// * `amount` - The number of shares to transfer
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `NotAllowlisted` - If the allowlist is enabled and `to` is not on it
+    /// * `UserFrozen` - If `from`'s or `to`'s position is frozen
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `BalanceError` - If `from` does not have enough shares
+    /// * `UserCapExceeded` - If `to`'s position would be worth more than the user cap
+    pub fn share_transfer(e: Env, reserve: Address, from: Address, to: Address, amount: i128) {
+        storage::extend_instance(&e);
+        require_has_reserve(&e, &reserve);
+        require_share_token(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_allowlisted(&e, &to);
+        require_not_frozen(&e, &reserve, &from);
+        require_not_frozen(&e, &reserve, &to);
+
+        share_token::transfer(&e, &reserve, &from, &to, amount);
+    }
+
+    /// SHARE TOKEN ONLY
+    /// Burn a user's reserve vault shares without withdrawing. The bTokens backing the shares are
+    /// left to the remaining depositors. Called by the reserve's share token, which authorizes
+    /// `from` or spends the spender's allowance.
+    ///
+    /// ### Arguments
+    /// * `reserve` - The asset address of the reserve
+    /// * `from` - The address of the user burning the shares
+    /// * `amount` - The number of shares to burn
+    ///
+    /// ### Panics
+    /// * `ReserveNotFound` - If the reserve does not have a vault
+    /// * `VaultPaused` - If the reserve vault is paused
+    /// * `UserFrozen` - If `from`'s position is frozen
+    /// * `NegativeAmountError` - If the amount is negative
+    /// * `BalanceError` - If `from` does not have enough shares
+    pub fn share_burn(e: Env, reserve: Address, from: Address, amount: i128) {
+        storage::extend_instance(&e);
+        require_has_reserve(&e, &reserve);
+        require_share_token(&e, &reserve);
+        require_withdrawals_allowed(&e, &reserve);
+        require_not_frozen(&e, &reserve, &from);
+
+        reserve_vault::burn(&e, &reserve, &from, amount);
+    }
+}
+
+impl FeeVault {
+    /// Applies a config change immediately if it is allowed to skip the timelock
+    fn execute_config_change(e: &Env, change: ConfigChange) {
+        storage::extend_instance(e);
+        config::require_auth(e, &config::config_key(&change));
+        config::require_valid_change(e, &change);
+        if !config::can_apply_immediately(e, &change) {
+            panic_with_error!(e, FeeVaultError::TimelockRequired);
+        }
+
+        config::apply_change(e, &change);
+    }
+
+    /// Sets the pause state of a reserve vault, or of the whole vault if no reserve is given
+    fn execute_set_pause_state(
+        e: &Env,
+        caller: &Address,
+        reserve: Option<Address>,
+        state: PauseState,
+    ) {
+        match &reserve {
+            Some(reserve) => storage::set_reserve_pause_state(e, reserve, state),
+            None => storage::set_pause_state(e, state),
+        }
+
+        FeeVaultEvents::pause_state_updated(e, caller, reserve, state);
+    }
+
+    /// Adds or removes addresses from the deposit allowlist
+    fn execute_update_allowlist(e: &Env, users: Vec<Address>, allowed: bool) {
+        storage::extend_instance(e);
+        let admin = storage::get_admin(e);
+        admin.require_auth();
+
+        for user in users.iter() {
+            storage::set_allowlisted(e, &user, allowed);
+        }
+
+        FeeVaultEvents::allowlist_updated(e, allowed, users);
+    }
+
+    /// Claims the admin's share of emissions for the given reserves and sends them to `to`
+    fn execute_claim_emissions(
+        e: &Env,
+        treasurer: &Address,
+        reserves: Vec<Address>,
+        reserve_token_ids: Vec<u32>,
+        to: &Address,
+    ) -> i128 {
+        let mut claimed = 0;
+        let mut amounts: Vec<(Address, i128)> = Vec::new(e);
+        for reserve in reserves {
+            let amount = emissions::claim_admin(e, &reserve);
+            claimed += amount;
+            amounts.push_back((reserve, amount));
+        }
+        if claimed > 0 {
+            TokenClient::new(e, &storage::get_blnd(e)).transfer(
+                &e.current_contract_address(),
+                to,
+                &claimed,
+            );
+        }
+
+        FeeVaultEvents::vault_emissions_claim(e, treasurer, reserve_token_ids, amounts, claimed);
+        claimed
+    }
+
+    /// Supplies `amount` tokens from `from` to the pool and mints the shares to `to`
+    fn execute_deposit(
+        e: &Env,
+        reserve: &Address,
+        from: &Address,
+        to: &Address,
+        amount: i128,
+    ) -> i128 {
+        pool::supply(e, reserve, from, amount);
+        let (b_tokens_minted, new_shares) = reserve_vault::deposit(e, reserve, to, amount);
+
+        FeeVaultEvents::vault_deposit(e, reserve, from, to, amount, new_shares, b_tokens_minted);
+        new_shares
+    }
+
+    /// Burns the shares for `amount` tokens from `from` and withdraws the tokens to `to`
+    fn execute_withdraw(
+        e: &Env,
+        reserve: &Address,
+        from: &Address,
+        to: &Address,
+        amount: i128,
+    ) -> i128 {
+        pool::withdraw(e, reserve, to, amount);
+        let (b_tokens_burnt, burnt_shares) = reserve_vault::withdraw(e, reserve, from, amount);
+
+        FeeVaultEvents::vault_withdraw(e, reserve, from, to, amount, burnt_shares, b_tokens_burnt);
+        burnt_shares
+    }
+
+    /// Burns `shares` from the user's position and withdraws the underlying tokens to the user
+    fn execute_redeem(
+        e: &Env,
+        reserve: &Address,
+        from: &Address,
+        to: &Address,
+        shares: i128,
+    ) -> VaultAction {
+        let (b_tokens_burnt, amount) = reserve_vault::redeem(e, reserve, from, shares);
+        pool::withdraw(e, reserve, to, amount);
+
+        FeeVaultEvents::vault_withdraw(e, reserve, from, to, amount, shares, b_tokens_burnt);
+        VaultAction {
+            shares,
+            b_tokens: b_tokens_burnt,
+            underlying: amount,
+        }
     }
 }
@@ -0,0 +1,195 @@
+use crate::{
+    constants::{SCALAR_12, SCALAR_7},
+    pool,
+    storage::{self, ReserveEmissionData},
+};
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env};
+
+/// Claims the BLND the vault has earned for a reserve from the pool and adds it to the reserve's
+/// emission index, after setting aside the admin's take. If the reserve vault compounds its
+/// emissions, the depositors' portion is held for compounding instead. Must be called before the reserve's
+/// total shares change.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `total_shares` - The reserve vault's total shares the emissions were earned by
+///
+/// ### Returns
+/// * `ReserveEmissionData` - The updated reserve emission data
+pub fn update_reserve_emissions(
+    e: &Env,
+    reserve: &Address,
+    total_shares: i128,
+) -> ReserveEmissionData {
+    let mut data = storage::get_reserve_emissions(e, reserve);
+    let claimed = pool::claim(
+        e,
+        &vec![e, pool::reserve_b_token_id(e, reserve)],
+        &e.current_contract_address(),
+    );
+    if claimed <= 0 {
+        return data;
+    }
+
+    if total_shares > 0 {
+        let admin_take = claimed
+            .fixed_mul_ceil(storage::get_emissions_take_rate(e), SCALAR_7)
+            .unwrap_optimized();
+        if storage::get_reserve_is_compounding(e, reserve) {
+            // the depositors' portion is held until a keeper compounds it into the reserve
+            data.admin_accrued += admin_take;
+            data.compound_accrued += claimed - admin_take;
+            storage::set_reserve_emissions(e, reserve, &data);
+            return data;
+        }
+        let index_delta = (claimed - admin_take)
+            .fixed_div_floor(total_shares, SCALAR_12)
+            .unwrap_optimized();
+        // any rounding dust from the index is given to the admin
+        let distributed = index_delta
+            .fixed_mul_floor(total_shares, SCALAR_12)
+            .unwrap_optimized();
+        data.index += index_delta;
+        data.admin_accrued += claimed - distributed;
+    } else {
+        // no depositors to distribute to
+        data.admin_accrued += claimed;
+    }
+    storage::set_reserve_emissions(e, reserve, &data);
+    data
+}
+
+/// Checkpoints a user's emissions for a reserve. Must be called before the user's shares or the
+/// reserve's total shares change.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `total_shares` - The reserve vault's total shares
+/// * `user` - The user address
+/// * `user_shares` - The user's shares
+pub fn update_user_emissions(
+    e: &Env,
+    reserve: &Address,
+    total_shares: i128,
+    user: &Address,
+    user_shares: i128,
+) {
+    let reserve_data = update_reserve_emissions(e, reserve, total_shares);
+    checkpoint_user(e, reserve, &reserve_data, user, user_shares);
+}
+
+/// Claims a user's accrued emissions for a reserve. Does not transfer the BLND.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user address
+///
+/// ### Returns
+/// * `i128` - The amount of BLND the user claimed
+pub fn claim_user(e: &Env, reserve: &Address, user: &Address) -> i128 {
+    if !storage::has_reserve_vault(e, reserve) {
+        // the reserve vault was removed with no shares left, so only the accrued emissions remain
+        let amount = storage::get_user_emissions(e, reserve, user).accrued;
+        storage::del_user_emissions(e, reserve, user);
+        return amount;
+    }
+    let total_shares = storage::get_reserve_vault(e, reserve).total_shares;
+    let user_shares = storage::get_reserve_vault_shares(e, reserve, user);
+    update_user_emissions(e, reserve, total_shares, user, user_shares);
+
+    let mut user_data = storage::get_user_emissions(e, reserve, user);
+    let amount = user_data.accrued;
+    if amount > 0 {
+        user_data.accrued = 0;
+        storage::set_user_emissions(e, reserve, user, &user_data);
+    }
+    amount
+}
+
+/// Claims the admin's accrued emissions for a reserve. Does not transfer the BLND.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `i128` - The amount of BLND the admin claimed
+pub fn claim_admin(e: &Env, reserve: &Address) -> i128 {
+    let total_shares = storage::get_reserve_vault(e, reserve).total_shares;
+    let mut reserve_data = update_reserve_emissions(e, reserve, total_shares);
+    let amount = reserve_data.admin_accrued;
+    if amount > 0 {
+        reserve_data.admin_accrued = 0;
+        storage::set_reserve_emissions(e, reserve, &reserve_data);
+    }
+    amount
+}
+
+/// Takes the BLND held for compounding for a reserve. Does not swap or supply the BLND.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `i128` - The amount of BLND to compound
+pub fn take_compound(e: &Env, reserve: &Address) -> i128 {
+    let total_shares = storage::get_reserve_vault(e, reserve).total_shares;
+    let mut reserve_data = update_reserve_emissions(e, reserve, total_shares);
+    let amount = reserve_data.compound_accrued;
+    if amount > 0 {
+        reserve_data.compound_accrued = 0;
+        storage::set_reserve_emissions(e, reserve, &reserve_data);
+    }
+    amount
+}
+
+/// Distributes the emissions earned by all reserves
+pub fn update_emissions_for_all_reserves(e: &Env) {
+    for reserve in storage::get_reserves(e) {
+        let total_shares = storage::get_reserve_vault(e, &reserve).total_shares;
+        update_reserve_emissions(e, &reserve, total_shares);
+    }
+}
+
+/// Fetch a user's accrued emissions for a reserve as of the reserve's last emissions update
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user address
+pub fn get_user_accrued(e: &Env, reserve: &Address, user: &Address) -> i128 {
+    let reserve_data = storage::get_reserve_emissions(e, reserve);
+    let user_data = storage::get_user_emissions(e, reserve, user);
+    let user_shares = storage::get_reserve_vault_shares(e, reserve, user);
+    user_data.accrued
+        + user_shares
+            .fixed_mul_floor(reserve_data.index - user_data.index, SCALAR_12)
+            .unwrap_optimized()
+}
+
+/// Checkpoints a user's emissions against an already updated reserve emission index. Must be
+/// called before the user's shares change.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `reserve_data` - The updated reserve emission data
+/// * `user` - The user address
+/// * `user_shares` - The user's shares
+pub fn checkpoint_user(
+    e: &Env,
+    reserve: &Address,
+    reserve_data: &ReserveEmissionData,
+    user: &Address,
+    user_shares: i128,
+) {
+    let mut user_data = storage::get_user_emissions(e, reserve, user);
+    if user_data.index == reserve_data.index {
+        return;
+    }
+    if user_shares > 0 {
+        user_data.accrued += user_shares
+            .fixed_mul_floor(reserve_data.index - user_data.index, SCALAR_12)
+            .unwrap_optimized();
+    }
+    user_data.index = reserve_data.index;
+    storage::set_user_emissions(e, reserve, user, &user_data);
+}
@@ -6,6 +6,8 @@ use soroban_sdk::contracterror;
 #[repr(u32)]
 pub enum FeeVaultError {
     // Default errors to align with built-in contract
+    NegativeAmountError = 8,
+    AllowanceError = 9,
     BalanceError = 10,
 
     ReserveNotFound = 100,
@@ -17,4 +19,32 @@ pub enum FeeVaultError {
     InvalidBTokensMinted = 106,
     InvalidBTokensBurnt = 107,
     InvalidSharesMinted = 108,
+    InvalidExpirationLedger = 109,
+    MinSharesOutNotMet = 110,
+    MaxSharesInExceeded = 111,
+    DeadlineExpired = 112,
+    DuplicateReserve = 113,
+    InvalidEmissionsTakeRate = 114,
+    CompoundConfigNotSet = 115,
+    TimelockRequired = 116,
+    ConfigChangeNotFound = 117,
+    ConfigChangeLocked = 118,
+    RoleNotGranted = 119,
+    NoPendingAdmin = 120,
+    AdminProposalExpired = 121,
+    InvalidFeeRecipients = 122,
+    FeeRecipientsNotSet = 123,
+    VaultPaused = 124,
+    DepositsBlocked = 125,
+    WithdrawOnly = 126,
+    InvalidPauseState = 127,
+    ReserveCapExceeded = 128,
+    UserCapExceeded = 129,
+    NotAllowlisted = 130,
+    UserFrozen = 131,
+    UserNotFrozen = 132,
+    ReserveDeprecated = 133,
+    ReserveNotDeprecated = 134,
+    ReserveNotEmpty = 135,
+    MinAmountOutNotMet = 136,
 }
@@ -1,25 +1,60 @@
 use soroban_sdk::{Address, Env, Symbol, Vec};
 
+use crate::storage::{
+    ConfigChange, ConfigKey, DepositCaps, FeeMode, FeeRecipients, PauseState, Role,
+};
+
 pub struct FeeVaultEvents {}
 
 impl FeeVaultEvents {
     /// Emitted when a new reserve vault is created
     ///
     /// - topics - `["new_reserve_vault", reserve: Address]`
-    /// - data - Void
-    pub fn new_reserve_vault(e: &Env, reserve: &Address) {
+    /// - data - `is_collateral: bool`
+    pub fn new_reserve_vault(e: &Env, reserve: &Address, is_collateral: bool) {
         let topics = (Symbol::new(&e, "new_reserve_vault"), reserve.clone());
+        e.events().publish(topics, is_collateral);
+    }
+
+    /// Emitted when a reserve vault is deprecated
+    ///
+    /// - topics - `["reserve_vault_deprecated", reserve: Address]`
+    /// - data - `()`
+    pub fn reserve_vault_deprecated(e: &Env, reserve: &Address) {
+        let topics = (Symbol::new(e, "reserve_vault_deprecated"), reserve.clone());
         e.events().publish(topics, ());
     }
 
-    /// Emitted when a deposit is performed against a reserve vault
+    /// Emitted when a reserve vault is removed. `amount` is the underlying rounding dust and
+    /// `emissions` the BLND swept to the treasurer.
     ///
-    /// - topics - `["vault_deposit", reserve: Address, from: Address]`
+    /// - topics - `["reserve_vault_removed", reserve: Address, treasurer: Address]`
+    /// - data - `[amount: i128, emissions: i128]`
+    pub fn reserve_vault_removed(
+        e: &Env,
+        reserve: &Address,
+        treasurer: &Address,
+        amount: i128,
+        emissions: i128,
+    ) {
+        let topics = (
+            Symbol::new(e, "reserve_vault_removed"),
+            reserve.clone(),
+            treasurer.clone(),
+        );
+        e.events().publish(topics, (amount, emissions));
+    }
+
+    /// Emitted when a deposit is performed against a reserve vault. `from` supplies the tokens
+    /// and `to` receives the shares.
+    ///
+    /// - topics - `["vault_deposit", reserve: Address, from: Address, to: Address]`
     /// - data - `[amount: i128, shares: i128, b_tokens: i128]`
     pub fn vault_deposit(
         e: &Env,
         reserve: &Address,
         from: &Address,
+        to: &Address,
         amount: i128,
         shares: i128,
         b_tokens: i128,
@@ -28,18 +63,21 @@ impl FeeVaultEvents {
             Symbol::new(&e, "vault_deposit"),
             reserve.clone(),
             from.clone(),
+            to.clone(),
         );
         e.events().publish(topics, (amount, shares, b_tokens));
     }
 
-    /// Emitted when a withdraw is performed against a reserve vault
+    /// Emitted when a withdraw is performed against a reserve vault. `from` burns the shares
+    /// and `to` receives the tokens.
     ///
-    /// - topics - `["vault_withdraw", reserve: Address, from: Address]`
+    /// - topics - `["vault_withdraw", reserve: Address, from: Address, to: Address]`
     /// - data - `[amount: i128, shares: i128, b_tokens: i128]`
     pub fn vault_withdraw(
         e: &Env,
         reserve: &Address,
         from: &Address,
+        to: &Address,
         amount: i128,
         shares: i128,
         b_tokens: i128,
@@ -48,50 +86,352 @@ impl FeeVaultEvents {
             Symbol::new(&e, "vault_withdraw"),
             reserve.clone(),
             from.clone(),
+            to.clone(),
         );
         e.events().publish(topics, (amount, shares, b_tokens));
     }
 
     /// Emitted when fees are claimed from a reserve vault
     ///
-    /// - topics - `["vault_fee_claim", reserve: Address, admin: Address]`
+    /// - topics - `["vault_fee_claim", reserve: Address, treasurer: Address, to: Address]`
     /// - data - `[amount: i128, b_tokens: i128]`
     pub fn vault_fee_claim(
         e: &Env,
         reserve: &Address,
-        admin: &Address,
+        treasurer: &Address,
+        to: &Address,
         amount: i128,
         b_tokens: i128,
     ) {
         let topics = (
             Symbol::new(&e, "vault_fee_claim"),
             reserve.clone(),
-            admin.clone(),
+            treasurer.clone(),
+            to.clone(),
         );
         e.events().publish(topics, (amount, b_tokens));
     }
 
-    /// Emitted when emissions are claimed
+    /// Emitted when fees are converted into shares of a reserve vault
+    ///
+    /// - topics - `["vault_fee_conversion", reserve: Address, treasurer: Address, to: Address]`
+    /// - data - `[b_tokens: i128, shares: i128]`
+    pub fn vault_fee_conversion(
+        e: &Env,
+        reserve: &Address,
+        treasurer: &Address,
+        to: &Address,
+        b_tokens: i128,
+        shares: i128,
+    ) {
+        let topics = (
+            Symbol::new(e, "vault_fee_conversion"),
+            reserve.clone(),
+            treasurer.clone(),
+            to.clone(),
+        );
+        e.events().publish(topics, (b_tokens, shares));
+    }
+
+    /// Emitted when fees are distributed from a reserve vault to the fee recipients
     ///
-    /// - topics - `["vault_emissions_claim", admin: Address]`
-    /// - data - `[reserve_token_ids: Vec<u32>, amount: i128]`
+    /// - topics - `["vault_fee_distribution", reserve: Address]`
+    /// - data - `[amounts: Vec<(Address, i128)>, b_tokens: i128]`
+    pub fn vault_fee_distribution(
+        e: &Env,
+        reserve: &Address,
+        amounts: Vec<(Address, i128)>,
+        b_tokens: i128,
+    ) {
+        let topics = (Symbol::new(e, "vault_fee_distribution"), reserve.clone());
+        e.events().publish(topics, (amounts, b_tokens));
+    }
+
+    /// Emitted when the admin's share of emissions is claimed. Only the reserve token ids that
+    /// belong to a reserve vault are included.
+    ///
+    /// - topics - `["vault_emissions_claim", treasurer: Address]`
+    /// - data - `[reserve_token_ids: Vec<u32>, amounts: Vec<(Address, i128)>, amount: i128]`
     pub fn vault_emissions_claim(
         e: &Env,
-        admin: &Address,
+        treasurer: &Address,
         reserve_token_ids: Vec<u32>,
+        amounts: Vec<(Address, i128)>,
         amount: i128,
     ) {
-        let topics = (Symbol::new(&e, "vault_emissions_claim"), admin.clone());
-        e.events().publish(topics, (reserve_token_ids, amount));
+        let topics = (Symbol::new(&e, "vault_emissions_claim"), treasurer.clone());
+        e.events()
+            .publish(topics, (reserve_token_ids, amounts, amount));
+    }
+
+    /// Emitted when a reserve vault's emissions are compounded into the reserve
+    ///
+    /// - topics - `["vault_compound", reserve: Address, keeper: Address]`
+    /// - data - `[blnd_amount: i128, amount: i128, b_tokens: i128]`
+    pub fn vault_compound(
+        e: &Env,
+        reserve: &Address,
+        keeper: &Address,
+        blnd_amount: i128,
+        amount: i128,
+        b_tokens: i128,
+    ) {
+        let topics = (
+            Symbol::new(e, "vault_compound"),
+            reserve.clone(),
+            keeper.clone(),
+        );
+        e.events().publish(topics, (blnd_amount, amount, b_tokens));
+    }
+
+    /// Emitted when a user claims their emissions from a reserve vault
+    ///
+    /// - topics - `["user_emissions_claim", reserve: Address, user: Address, to: Address]`
+    /// - data - `amount: i128`
+    pub fn user_emissions_claim(
+        e: &Env,
+        reserve: &Address,
+        user: &Address,
+        to: &Address,
+        amount: i128,
+    ) {
+        let topics = (
+            Symbol::new(e, "user_emissions_claim"),
+            reserve.clone(),
+            user.clone(),
+            to.clone(),
+        );
+        e.events().publish(topics, amount);
+    }
+
+    /// Emitted when a withdraw allowance is set for reserve vault shares
+    ///
+    /// - topics - `["approve_withdraw", reserve: Address, owner: Address, spender: Address]`
+    /// - data - `[shares: i128, expiration_ledger: u32]`
+    pub fn approve_withdraw(
+        e: &Env,
+        reserve: &Address,
+        owner: &Address,
+        spender: &Address,
+        shares: i128,
+        expiration_ledger: u32,
+    ) {
+        let topics = (
+            Symbol::new(e, "approve_withdraw"),
+            reserve.clone(),
+            owner.clone(),
+            spender.clone(),
+        );
+        e.events().publish(topics, (shares, expiration_ledger));
     }
 
     /// Emitted when the fee mode is updated for a fee vault
     ///
     /// - topics - `["fee_mode_update"]`
-    /// - data - `[is_apr_capped: bool, value: i128]`
-    pub fn fee_mode_updated(e: &Env, is_apr_capped: bool, value: i128) {
+    /// - data - `fee_mode: FeeMode`
+    pub fn fee_mode_updated(e: &Env, fee_mode: FeeMode) {
         let topics = (Symbol::new(&e, "fee_mode_update"),);
 
-        e.events().publish(topics, (is_apr_capped, value));
+        e.events().publish(topics, fee_mode);
+    }
+
+    /// Emitted when a reserve vault's own fee mode is updated. A `None` fee mode means the reserve
+    /// vault uses the global fee mode.
+    ///
+    /// - topics - `["reserve_fee_mode_update", reserve: Address]`
+    /// - data - `fee_mode: Option<FeeMode>`
+    pub fn reserve_fee_mode_updated(e: &Env, reserve: &Address, fee_mode: Option<FeeMode>) {
+        let topics = (Symbol::new(e, "reserve_fee_mode_update"), reserve.clone());
+        e.events().publish(topics, fee_mode);
+    }
+
+    /// Emitted when the admin's take rate on emissions is updated
+    ///
+    /// - topics - `["emissions_take_rate_update"]`
+    /// - data - `take_rate: i128`
+    pub fn emissions_take_rate_updated(e: &Env, take_rate: i128) {
+        let topics = (Symbol::new(e, "emissions_take_rate_update"),);
+        e.events().publish(topics, take_rate);
+    }
+
+    /// Emitted when the emissions compounding config is updated
+    ///
+    /// - topics - `["compound_config_update"]`
+    /// - data - `[router: Address, keeper: Address]`
+    pub fn compound_config_updated(e: &Env, router: &Address, keeper: &Address) {
+        let topics = (Symbol::new(e, "compound_config_update"),);
+        e.events().publish(topics, (router.clone(), keeper.clone()));
+    }
+
+    /// Emitted when a reserve vault starts or stops compounding its emissions
+    ///
+    /// - topics - `["compounding_update", reserve: Address]`
+    /// - data - `is_compounding: bool`
+    pub fn compounding_updated(e: &Env, reserve: &Address, is_compounding: bool) {
+        let topics = (Symbol::new(e, "compounding_update"), reserve.clone());
+        e.events().publish(topics, is_compounding);
+    }
+
+    /// Emitted when a config change is queued
+    ///
+    /// - topics - `["config_change_queued"]`
+    /// - data - `[change: ConfigChange, unlock_time: u64]`
+    pub fn config_change_queued(e: &Env, change: ConfigChange, unlock_time: u64) {
+        let topics = (Symbol::new(e, "config_change_queued"),);
+        e.events().publish(topics, (change, unlock_time));
+    }
+
+    /// Emitted when a queued config change is cancelled
+    ///
+    /// - topics - `["config_change_cancelled"]`
+    /// - data - `key: ConfigKey`
+    pub fn config_change_cancelled(e: &Env, key: ConfigKey) {
+        let topics = (Symbol::new(e, "config_change_cancelled"),);
+        e.events().publish(topics, key);
+    }
+
+    /// Emitted when the delay for queued config changes is updated
+    ///
+    /// - topics - `["config_delay_update"]`
+    /// - data - `delay: u64`
+    pub fn config_delay_updated(e: &Env, delay: u64) {
+        let topics = (Symbol::new(e, "config_delay_update"),);
+        e.events().publish(topics, delay);
+    }
+
+    /// Emitted when a role is granted to an address
+    ///
+    /// - topics - `["role_granted", role: Role]`
+    /// - data - `account: Address`
+    pub fn role_granted(e: &Env, role: Role, account: &Address) {
+        let topics = (Symbol::new(e, "role_granted"), role);
+        e.events().publish(topics, account.clone());
+    }
+
+    /// Emitted when a role grant is revoked and the role returns to the admin
+    ///
+    /// - topics - `["role_revoked", role: Role]`
+    /// - data - `()`
+    pub fn role_revoked(e: &Env, role: Role) {
+        let topics = (Symbol::new(e, "role_revoked"), role);
+        e.events().publish(topics, ());
+    }
+
+    /// Emitted when the admin proposes a new admin
+    ///
+    /// - topics - `["admin_proposed", admin: Address]`
+    /// - data - `[new_admin: Address, expiration_ledger: u32]`
+    pub fn admin_proposed(e: &Env, admin: &Address, new_admin: &Address, expiration_ledger: u32) {
+        let topics = (Symbol::new(e, "admin_proposed"), admin.clone());
+        e.events()
+            .publish(topics, (new_admin.clone(), expiration_ledger));
+    }
+
+    /// Emitted when the admin cancels a pending admin proposal
+    ///
+    /// - topics - `["admin_proposal_cancelled", admin: Address]`
+    /// - data - `new_admin: Address`
+    pub fn admin_proposal_cancelled(e: &Env, admin: &Address, new_admin: &Address) {
+        let topics = (Symbol::new(e, "admin_proposal_cancelled"), admin.clone());
+        e.events().publish(topics, new_admin.clone());
+    }
+
+    /// Emitted when the admin changes
+    ///
+    /// - topics - `["admin_update", old_admin: Address]`
+    /// - data - `new_admin: Address`
+    pub fn admin_updated(e: &Env, old_admin: &Address, new_admin: &Address) {
+        let topics = (Symbol::new(e, "admin_update"), old_admin.clone());
+        e.events().publish(topics, new_admin.clone());
+    }
+
+    /// Emitted when the fee recipients are updated
+    ///
+    /// - topics - `["fee_recipients_update"]`
+    /// - data - `fee_recipients: FeeRecipients`
+    pub fn fee_recipients_updated(e: &Env, fee_recipients: FeeRecipients) {
+        let topics = (Symbol::new(e, "fee_recipients_update"),);
+        e.events().publish(topics, fee_recipients);
+    }
+
+    /// Emitted when the pause state of a reserve vault, or of the whole vault, changes
+    ///
+    /// - topics - `["pause_state_update", caller: Address]`
+    /// - data - `[reserve: Option<Address>, state: PauseState]`
+    pub fn pause_state_updated(
+        e: &Env,
+        caller: &Address,
+        reserve: Option<Address>,
+        state: PauseState,
+    ) {
+        let topics = (Symbol::new(e, "pause_state_update"), caller.clone());
+        e.events().publish(topics, (reserve, state));
+    }
+
+    /// Emitted when a reserve vault's deposit caps are updated
+    ///
+    /// - topics - `["deposit_caps_update", reserve: Address]`
+    /// - data - `caps: DepositCaps`
+    pub fn deposit_caps_updated(e: &Env, reserve: &Address, caps: DepositCaps) {
+        let topics = (Symbol::new(e, "deposit_caps_update"), reserve.clone());
+        e.events().publish(topics, caps);
+    }
+
+    /// Emitted when the deposit allowlist is enabled or disabled
+    ///
+    /// - topics - `["allowlist_enabled_update"]`
+    /// - data - `enabled: bool`
+    pub fn allowlist_enabled_updated(e: &Env, enabled: bool) {
+        let topics = (Symbol::new(e, "allowlist_enabled_update"),);
+        e.events().publish(topics, enabled);
+    }
+
+    /// Emitted when addresses are added to or removed from the deposit allowlist
+    ///
+    /// - topics - `["allowlist_update", allowed: bool]`
+    /// - data - `users: Vec<Address>`
+    pub fn allowlist_updated(e: &Env, allowed: bool, users: Vec<Address>) {
+        let topics = (Symbol::new(e, "allowlist_update"), allowed);
+        e.events().publish(topics, users);
+    }
+
+    /// Emitted when a user's position in a reserve vault is frozen
+    ///
+    /// - topics - `["user_frozen", reserve: Address, user: Address]`
+    /// - data - `reason: u32`
+    pub fn user_frozen(e: &Env, reserve: &Address, user: &Address, reason: u32) {
+        let topics = (Symbol::new(e, "user_frozen"), reserve.clone(), user.clone());
+        e.events().publish(topics, reason);
+    }
+
+    /// Emitted when a user's position in a reserve vault is unfrozen
+    ///
+    /// - topics - `["user_unfrozen", reserve: Address, user: Address]`
+    /// - data - `reason: u32`
+    pub fn user_unfrozen(e: &Env, reserve: &Address, user: &Address, reason: u32) {
+        let topics = (
+            Symbol::new(e, "user_unfrozen"),
+            reserve.clone(),
+            user.clone(),
+        );
+        e.events().publish(topics, reason);
+    }
+
+    /// Emitted when a frozen position is forced out of a reserve vault
+    ///
+    /// - topics - `["forced_exit", reserve: Address, user: Address]`
+    /// - data - `[to: Address, reason: u32, underlying: i128, shares: i128]`
+    pub fn forced_exit(
+        e: &Env,
+        reserve: &Address,
+        user: &Address,
+        to: &Address,
+        reason: u32,
+        underlying: i128,
+        shares: i128,
+    ) {
+        let topics = (Symbol::new(e, "forced_exit"), reserve.clone(), user.clone());
+        e.events()
+            .publish(topics, (to.clone(), reason, underlying, shares));
     }
 }
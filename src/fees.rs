@@ -0,0 +1,101 @@
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{unwrap::UnwrapOptimized, Address, Env, Vec};
+
+use crate::{constants::MAX_BPS, storage::FeeRecipients};
+
+/// Split an amount of fees between the fee recipients by weight. Rounding dust is paid to the
+/// dust recipient, which is added to the split if it is not already a recipient.
+///
+/// ### Arguments
+/// * `fee_recipients` - The fee recipients
+/// * `amount` - The amount of fees to split
+///
+/// ### Returns
+/// * `Vec<(Address, i128)>` - The amount paid to each address
+pub fn split_fees(e: &Env, fee_recipients: &FeeRecipients, amount: i128) -> Vec<(Address, i128)> {
+    let mut amounts: Vec<(Address, i128)> = Vec::new(e);
+    let mut remaining = amount;
+    for recipient in fee_recipients.recipients.iter() {
+        let share = amount
+            .fixed_mul_floor(i128::from(recipient.weight), i128::from(MAX_BPS))
+            .unwrap_optimized();
+        remaining -= share;
+        amounts.push_back((recipient.address, share));
+    }
+
+    let dust_recipient = &fee_recipients.dust_recipient;
+    match amounts
+        .iter()
+        .position(|(address, _)| address == *dust_recipient)
+    {
+        Some(index) => {
+            let (address, share) = amounts.get_unchecked(index as u32);
+            amounts.set(index as u32, (address, share + remaining));
+        }
+        None => amounts.push_back((dust_recipient.clone(), remaining)),
+    }
+    amounts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::FeeRecipient;
+    use soroban_sdk::{testutils::Address as _, vec};
+
+    #[test]
+    fn test_split_fees() {
+        let e = Env::default();
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let merry = Address::generate(&e);
+
+        let fee_recipients = FeeRecipients {
+            recipients: vec![
+                &e,
+                FeeRecipient {
+                    address: samwise.clone(),
+                    weight: 5000,
+                },
+                FeeRecipient {
+                    address: frodo.clone(),
+                    weight: 3333,
+                },
+                FeeRecipient {
+                    address: merry.clone(),
+                    weight: 1667,
+                },
+            ],
+            dust_recipient: samwise.clone(),
+        };
+
+        let amounts = split_fees(&e, &fee_recipients, 1_000_0001);
+        assert_eq!(
+            amounts,
+            vec![
+                &e,
+                (samwise.clone(), 500_0001),
+                (frodo.clone(), 333_3000),
+                (merry.clone(), 166_7000),
+            ]
+        );
+
+        // dust is paid to a dust recipient that is not a recipient
+        let pippin = Address::generate(&e);
+        let fee_recipients = FeeRecipients {
+            dust_recipient: pippin.clone(),
+            ..fee_recipients
+        };
+        let amounts = split_fees(&e, &fee_recipients, 10);
+        assert_eq!(
+            amounts,
+            vec![
+                &e,
+                (samwise.clone(), 5),
+                (frodo.clone(), 3),
+                (merry.clone(), 1),
+                (pippin.clone(), 1),
+            ]
+        );
+    }
+}
@@ -5,12 +5,17 @@ extern crate std;
 #[cfg(any(test, feature = "testutils"))]
 pub mod testutils;
 
+pub mod config;
 pub mod constants;
 pub mod contract;
+pub mod emissions;
 pub mod errors;
 pub mod events;
+pub mod fees;
 pub mod pool;
 pub mod reserve_vault;
+pub mod router;
+pub mod share_token;
 pub mod storage;
 pub mod validator;
 
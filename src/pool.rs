@@ -1,50 +1,104 @@
-use crate::storage;
+use crate::{
+    constants::{SCALAR_12, SCALAR_7},
+    storage,
+};
 use blend_contract_sdk::pool::{Client as PoolClient, Request};
-use soroban_sdk::{vec, Address, Env, Vec};
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::{
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    unwrap::UnwrapOptimized,
+    vec, Address, Env, IntoVal, Symbol, Vec,
+};
 
-/// Executes a supply of a specific reserve into the underlying pool on behalf of the fee vault
+/// Executes a supply of a specific reserve into the underlying pool on behalf of the fee vault.
+/// Collateral reserve vaults supply through `SupplyCollateral`, all others through `Supply`.
 ///
 /// ### Arguments
 /// * `reserve` - The reserve address
 /// * `from` - The address of the user
 /// * `amount` - The amount of tokens to deposit
 pub fn supply(e: &Env, reserve: &Address, from: &Address, amount: i128) {
+    supply_many(e, from, &vec![e, (reserve.clone(), amount)]);
+}
+
+/// Executes a supply of several reserves into the underlying pool on behalf of the fee vault
+/// with a single submit
+///
+/// ### Arguments
+/// * `from` - The address of the user
+/// * `deposits` - The (reserve, amount) pairs to deposit
+pub fn supply_many(e: &Env, from: &Address, deposits: &Vec<(Address, i128)>) {
+    let mut requests: Vec<Request> = vec![e];
+    for (reserve, amount) in deposits.iter() {
+        requests.push_back(Request {
+            request_type: supply_request_type(e, &reserve),
+            address: reserve,
+            amount,
+        });
+    }
     // Execute the deposit - the tokens are transferred from the user to the pool
-    get_pool_client(&e).submit(
-        &e.current_contract_address(),
-        &from,
-        &from,
-        &vec![
-            &e,
-            Request {
-                address: reserve.clone(),
-                amount,
-                request_type: 0,
+    get_pool_client(e).submit(&e.current_contract_address(), from, from, &requests);
+}
+
+/// Executes a supply of tokens held by the fee vault into the underlying pool
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `amount` - The amount of tokens to supply
+pub fn supply_from_vault(e: &Env, reserve: &Address, amount: i128) {
+    let vault = e.current_contract_address();
+    // the pool transfers the tokens from the vault, which is not a direct call by the vault
+    e.authorize_as_current_contract(vec![
+        e,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: reserve.clone(),
+                fn_name: Symbol::new(e, "transfer"),
+                args: vec![
+                    e,
+                    vault.into_val(e),
+                    storage::get_pool(e).into_val(e),
+                    amount.into_val(e),
+                ],
             },
-        ],
-    );
+            sub_invocations: vec![e],
+        }),
+    ]);
+    supply(e, reserve, &vault, amount);
 }
 
-/// Executes a user withdrawal of a specific reserve from the underlying pool on behalf of the fee vault
+/// Executes a user withdrawal of a specific reserve from the underlying pool on behalf of the fee vault.
+/// Collateral reserve vaults withdraw through `WithdrawCollateral`, all others through `Withdraw`.
 ///
 /// ### Arguments
 /// * `reserve` - The reserve address
 /// * `to` - The destination of the withdrawal
 /// * `amount` - The amount of tokens to withdraw
 pub fn withdraw(e: &Env, reserve: &Address, to: &Address, amount: i128) {
+    withdraw_many(e, to, &vec![e, (reserve.clone(), amount)]);
+}
+
+/// Executes a user withdrawal of several reserves from the underlying pool on behalf of the fee
+/// vault with a single submit
+///
+/// ### Arguments
+/// * `to` - The destination of the withdrawal
+/// * `withdrawals` - The (reserve, amount) pairs to withdraw
+pub fn withdraw_many(e: &Env, to: &Address, withdrawals: &Vec<(Address, i128)>) {
+    let mut requests: Vec<Request> = vec![e];
+    for (reserve, amount) in withdrawals.iter() {
+        requests.push_back(Request {
+            request_type: withdraw_request_type(e, &reserve),
+            address: reserve,
+            amount,
+        });
+    }
     // Execute the withdrawal - the tokens are transferred from the pool to the user
-    get_pool_client(&e).submit(
+    get_pool_client(e).submit(
         &e.current_contract_address(),
         &e.current_contract_address(),
-        &to,
-        &vec![
-            &e,
-            Request {
-                address: reserve.clone(),
-                amount,
-                request_type: 1,
-            },
-        ],
+        to,
+        &requests,
     );
 }
 
@@ -61,6 +115,18 @@ pub fn claim(e: &Env, reserve_token_ids: &Vec<u32>, to: &Address) -> i128 {
     get_pool_client(&e).claim(&e.current_contract_address(), reserve_token_ids, to)
 }
 
+/// Fetches the id the pool tracks the reserve's bToken emissions under. Collateral and
+/// non-collateral supply share the same id.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `u32` - The reserve's bToken emissions id, `index * 2 + 1`
+pub fn reserve_b_token_id(e: &Env, reserve: &Address) -> u32 {
+    get_pool_client(e).get_reserve(reserve).config.index * 2 + 1
+}
+
 /// Fetches the reserve's b_rate from the pool
 ///
 /// ### Arguments
@@ -72,6 +138,92 @@ pub fn reserve_b_rate(e: &Env, reserve: &Address) -> i128 {
     get_pool_client(&e).get_reserve(reserve).data.b_rate
 }
 
+/// Fetches the amount of underlying tokens that can be withdrawn from the reserve before its
+/// utilization exceeds the reserve's `max_util`
+///
+/// ### Arguments
+/// * `reserve` - The reserve address to fetch the available liquidity for
+///
+/// ### Returns
+/// * `i128` - The amount of underlying tokens available to withdraw
+pub fn reserve_available_liquidity(e: &Env, reserve: &Address) -> i128 {
+    let pool_reserve = get_pool_client(e).get_reserve(reserve);
+    let max_util = pool_reserve.config.max_util as i128;
+    if max_util == 0 {
+        return 0;
+    }
+    let total_liabilities = pool_reserve
+        .data
+        .d_supply
+        .fixed_mul_ceil(pool_reserve.data.d_rate, SCALAR_12)
+        .unwrap_optimized();
+    // the bTokens required to keep the reserve's utilization at or below `max_util`. Computed in
+    // bTokens as withdrawals burn bTokens rounding up.
+    let min_b_supply = total_liabilities
+        .fixed_div_ceil(max_util, SCALAR_7)
+        .unwrap_optimized()
+        .fixed_div_ceil(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    let available_b_tokens = pool_reserve.data.b_supply - min_b_supply;
+    if available_b_tokens <= 0 {
+        return 0;
+    }
+    available_b_tokens
+        .fixed_mul_floor(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized()
+}
+
+/// Fetches the amount of underlying tokens that can be supplied to the reserve before reaching its
+/// supply cap
+///
+/// ### Arguments
+/// * `reserve` - The reserve address to fetch the available supply for
+///
+/// ### Returns
+/// * `i128` - The amount of underlying tokens that can be supplied, or 0 if the reserve is disabled
+pub fn reserve_available_supply(e: &Env, reserve: &Address) -> i128 {
+    let pool_reserve = get_pool_client(e).get_reserve(reserve);
+    if !pool_reserve.config.enabled {
+        return 0;
+    }
+    let total_supply = pool_reserve
+        .data
+        .b_supply
+        .fixed_mul_ceil(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    (pool_reserve.config.supply_cap - total_supply).max(0)
+}
+
+/// Fetches the pool request type used to supply the reserve
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `u32` - 2 (`SupplyCollateral`) if the reserve vault is a collateral vault, 0 (`Supply`) otherwise
+pub fn supply_request_type(e: &Env, reserve: &Address) -> u32 {
+    if storage::get_reserve_is_collateral(e, reserve) {
+        2
+    } else {
+        0
+    }
+}
+
+/// Fetches the pool request type used to withdraw the reserve
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `u32` - 3 (`WithdrawCollateral`) if the reserve vault is a collateral vault, 1 (`Withdraw`) otherwise
+pub fn withdraw_request_type(e: &Env, reserve: &Address) -> u32 {
+    if storage::get_reserve_is_collateral(e, reserve) {
+        3
+    } else {
+        1
+    }
+}
+
 fn get_pool_client(e: &Env) -> PoolClient {
     PoolClient::new(&e, &storage::get_pool(&e))
 }
@@ -1,8 +1,10 @@
 use crate::{
     constants::{SCALAR_12, SCALAR_7, SECONDS_PER_YEAR},
+    emissions,
     errors::FeeVaultError,
-    pool, storage,
-    validator::require_positive,
+    pool,
+    storage::{self, FeeMode},
+    validator::{require_nonnegative, require_positive},
 };
 use soroban_fixed_point_math::{i128, FixedPoint};
 use soroban_sdk::{contracttype, panic_with_error, unwrap::UnwrapOptimized, Address, Env};
@@ -13,6 +15,8 @@ pub struct ReserveVault {
     pub address: Address,
     /// The reserve's last bRate
     pub b_rate: i128,
+    /// The highest bRate the reserve has reached. Fees are only accrued on growth above it.
+    pub high_water_b_rate: i128,
     /// The timestamp of the last update
     pub last_update_timestamp: u64,
     /// The total shares issued by the reserve vault
@@ -23,6 +27,18 @@ pub struct ReserveVault {
     pub accrued_fees: i128,
 }
 
+/// The amounts moved by a share priced vault action
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct VaultAction {
+    /// The number of shares minted or burnt
+    pub shares: i128,
+    /// The number of bTokens minted or burnt
+    pub b_tokens: i128,
+    /// The amount of underlying tokens supplied or withdrawn
+    pub underlying: i128,
+}
+
 impl ReserveVault {
     /// Converts a b_token amount to shares rounding down
     pub fn b_tokens_to_shares_down(&self, amount: i128) -> i128 {
@@ -46,11 +62,24 @@ impl ReserveVault {
 
     /// Coverts a share amount to a b_token amount rounding down
     pub fn shares_to_b_tokens_down(&self, amount: i128) -> i128 {
+        if self.total_shares == 0 {
+            return amount;
+        }
         amount
             .fixed_div_floor(self.total_shares, self.total_b_tokens)
             .unwrap_optimized()
     }
 
+    /// Coverts a share amount to a b_token amount rounding up
+    pub fn shares_to_b_tokens_up(&self, amount: i128) -> i128 {
+        if self.total_shares == 0 || self.total_b_tokens == 0 {
+            return amount;
+        }
+        amount
+            .fixed_div_ceil(self.total_shares, self.total_b_tokens)
+            .unwrap_optimized()
+    }
+
     /// Coverts a b_token amount to an underlying token amount rounding down
     pub fn b_tokens_to_underlying_down(&self, amount: i128) -> i128 {
         amount
@@ -58,6 +87,13 @@ impl ReserveVault {
             .unwrap_optimized()
     }
 
+    /// Coverts a b_token amount to an underlying token amount rounding up
+    pub fn b_tokens_to_underlying_up(&self, amount: i128) -> i128 {
+        amount
+            .fixed_mul_ceil(self.b_rate, SCALAR_12)
+            .unwrap_optimized()
+    }
+
     /// Coverts an underlying amount to a b_token amount rounding down
     pub fn underlying_to_b_tokens_down(&self, amount: i128) -> i128 {
         amount
@@ -72,57 +108,112 @@ impl ReserveVault {
             .unwrap_optimized()
     }
 
+    /// Coverts a share amount to an underlying token amount rounding down
+    pub fn shares_to_underlying_down(&self, amount: i128) -> i128 {
+        if amount == 0 {
+            return 0;
+        }
+        self.b_tokens_to_underlying_down(self.shares_to_b_tokens_down(amount))
+    }
+
+    /// Calculates the bRate the reserve would have if it earned exactly the target APR since the last
+    /// update, starting from `base_rate`
+    fn target_b_rate(&self, base_rate: i128, now: u64, target_apr: i128) -> i128 {
+        let time_elapsed = now - self.last_update_timestamp;
+
+        // Target growth rate for target APR over the time elapsed scaled to 12 decimals
+        // -> target_apr is 7 decimals, so we multiply by 100_000 to get 12 decimals (seconds per year and
+        //    time elapsed have no decimals)
+        let target_growth_rate =
+            (100_000 * target_apr * (time_elapsed as i128)) / SECONDS_PER_YEAR + SCALAR_12;
+
+        base_rate
+            .fixed_mul_ceil(target_growth_rate, SCALAR_12)
+            .unwrap_optimized()
+    }
+
+    /// Calculates the bTokens owed to the admin for all interest earned above the target bRate
+    fn excess_interest(&self, new_rate: i128, target_b_rate: i128) -> i128 {
+        // If the target APR wasn't reached, no fees are accrued
+        if target_b_rate >= new_rate {
+            0
+        } else {
+            self.total_b_tokens
+                .fixed_mul_floor(new_rate - target_b_rate, new_rate)
+                .unwrap_optimized()
+        }
+    }
+
+    /// Calculates the bTokens owed to the admin for their take rate of the interest earned
+    /// between `base_rate` and `to_rate`
+    fn interest_take(
+        &self,
+        base_rate: i128,
+        to_rate: i128,
+        new_rate: i128,
+        take_rate: i128,
+    ) -> i128 {
+        if to_rate <= base_rate {
+            return 0;
+        }
+        self.total_b_tokens
+            .fixed_mul_floor(to_rate - base_rate, SCALAR_12)
+            .unwrap_optimized()
+            .fixed_mul_floor(take_rate, SCALAR_7)
+            .unwrap_optimized()
+            .fixed_div_floor(new_rate, SCALAR_12)
+            .unwrap_optimized()
+    }
+
+    /// Fetch the reserve's drawdown from its high water mark, with 7 decimals
+    pub fn drawdown(&self) -> i128 {
+        if self.b_rate >= self.high_water_b_rate {
+            return 0;
+        }
+        (self.high_water_b_rate - self.b_rate)
+            .fixed_div_ceil(self.high_water_b_rate, SCALAR_7)
+            .unwrap_optimized()
+    }
+
     /// Updates the reserve's bRate and accrues fees to the admin in accordance with the portion of interest they earned
     fn update_rate(&mut self, e: &Env) {
         let now = e.ledger().timestamp();
         let new_rate = pool::reserve_b_rate(e, &self.address);
-        // if the rate didn't increase, admin won't take any fees, so short circuit the math
-        // and just apply the b_rate update here
-        if new_rate <= self.b_rate {
+        // if the rate didn't increase past the high water mark, admin won't take any fees, so short
+        // circuit the math and just apply the b_rate update here. This ensures fees are not charged
+        // on a recovery from a loss.
+        if new_rate <= self.high_water_b_rate {
             self.last_update_timestamp = now;
             self.b_rate = new_rate;
             return;
         }
+        // fees are only accrued on the growth above the high water mark. The bRate never exceeds
+        // the high water mark, so it is always the base rate.
+        let base_rate = self.high_water_b_rate;
 
-        let fee_mode = storage::get_fee_mode(e);
         // this can round to zero if new_rate ~= target_b_rate
         // admin_take_b_tokens calc should round down, to prevent any rounding spam exploits
-        let admin_take_b_tokens = if fee_mode.is_apr_capped {
-            let target_apr = fee_mode.value;
-            let time_elapsed = now - self.last_update_timestamp;
-
-            // Target growth rate for target APR over the time elapsed scaled to 12 decimals
-            // -> target_apr is 7 decimals, so we multiply by 100_000 to get 12 decimals (seconds per year and
-            //    time elapsed have no decimals)
-            let target_growth_rate =
-                (100_000 * target_apr * (time_elapsed as i128)) / SECONDS_PER_YEAR + SCALAR_12;
-
-            let target_b_rate = self
-                .b_rate
-                .fixed_mul_ceil(target_growth_rate, SCALAR_12)
-                .unwrap_optimized();
-
-            // If the target APR wasn't reached, no fees are accrued
-            if target_b_rate >= new_rate {
-                0
-            } else {
-                self.total_b_tokens
-                    .fixed_mul_floor(new_rate - target_b_rate, new_rate)
-                    .unwrap_optimized()
+        let admin_take_b_tokens = match get_fee_mode(e, &self.address) {
+            FeeMode::TakeRate(take_rate) => {
+                self.interest_take(base_rate, new_rate, new_rate, take_rate)
+            }
+            FeeMode::AprCap(apr_cap) => {
+                let target_b_rate = self.target_b_rate(base_rate, now, apr_cap);
+                self.excess_interest(new_rate, target_b_rate)
+            }
+            FeeMode::Hybrid(take_rate, apr_cap) => {
+                // The APR cap takes precedence - all interest above the target goes to the admin,
+                // and the take rate only applies to the interest up to the target
+                let target_b_rate = self.target_b_rate(base_rate, now, apr_cap);
+                let capped_rate = target_b_rate.min(new_rate);
+                self.excess_interest(new_rate, target_b_rate)
+                    + self.interest_take(base_rate, capped_rate, new_rate, take_rate)
             }
-        } else {
-            let admin_take_rate = fee_mode.value;
-            self.total_b_tokens
-                .fixed_mul_floor(new_rate - self.b_rate, SCALAR_12)
-                .unwrap_optimized()
-                .fixed_mul_floor(admin_take_rate, SCALAR_7)
-                .unwrap_optimized()
-                .fixed_div_floor(new_rate, SCALAR_12)
-                .unwrap_optimized()
         };
 
         self.last_update_timestamp = now;
         self.b_rate = new_rate;
+        self.high_water_b_rate = new_rate;
 
         // if no interest was accrued we do not accrue fees
         if admin_take_b_tokens <= 0 {
@@ -134,6 +225,14 @@ impl ReserveVault {
     }
 }
 
+/// Get the fee mode of a reserve vault, falling back to the global fee mode
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+pub fn get_fee_mode(e: &Env, reserve: &Address) -> FeeMode {
+    storage::get_reserve_fee_mode(e, reserve).unwrap_or_else(|| storage::get_fee_mode(e))
+}
+
 /// Get the reserve vault from storage and update the bRate
 ///
 /// ### Arguments
@@ -171,10 +270,12 @@ pub fn deposit(e: &Env, reserve: &Address, user: &Address, amount: i128) -> (i12
     let mut user_shares = storage::get_reserve_vault_shares(e, &vault.address, user);
     let share_amount = vault.b_tokens_to_shares_down(b_tokens_amount);
     require_positive(e, share_amount, FeeVaultError::InvalidSharesMinted);
+    emissions::update_user_emissions(e, &vault.address, vault.total_shares, user, user_shares);
 
     vault.total_shares += share_amount;
     vault.total_b_tokens += b_tokens_amount;
     user_shares += share_amount;
+    require_within_deposit_caps(e, &vault, user_shares);
     storage::set_reserve_vault(e, &vault.address, &vault);
     storage::set_reserve_vault_shares(e, &vault.address, user, user_shares);
     (b_tokens_amount, share_amount)
@@ -208,6 +309,7 @@ pub fn withdraw(e: &Env, reserve: &Address, user: &Address, amount: i128) -> (i1
     if share_amount > user_shares {
         panic_with_error!(e, FeeVaultError::BalanceError);
     }
+    emissions::update_user_emissions(e, &vault.address, vault.total_shares, user, user_shares);
     vault.total_shares -= share_amount;
     vault.total_b_tokens -= b_tokens_amount;
 
@@ -217,6 +319,165 @@ pub fn withdraw(e: &Env, reserve: &Address, user: &Address, amount: i128) -> (i1
     (b_tokens_amount, share_amount)
 }
 
+/// Mint an exact number of shares from the reserve vault. Does not perform the call to the pool to deposit the tokens.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user receiving the shares
+/// * `shares` - The number of shares to mint
+///
+/// ### Returns
+/// * `(i128, i128)` - (The amount of b_tokens minted to the vault, the amount of underlying the user must supply)
+///
+/// ### Panics
+/// * If the amount of b_tokens minted is less than or equal to 0
+pub fn mint(e: &Env, reserve: &Address, user: &Address, shares: i128) -> (i128, i128) {
+    let mut vault = get_reserve_vault_updated(e, reserve);
+
+    // round against the user, as they are receiving an exact number of shares
+    let b_tokens_amount = vault.shares_to_b_tokens_up(shares);
+    require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensMinted);
+    let underlying_amount = vault.b_tokens_to_underlying_up(b_tokens_amount);
+
+    let mut user_shares = storage::get_reserve_vault_shares(e, &vault.address, user);
+    emissions::update_user_emissions(e, &vault.address, vault.total_shares, user, user_shares);
+    vault.total_shares += shares;
+    vault.total_b_tokens += b_tokens_amount;
+    user_shares += shares;
+    require_within_deposit_caps(e, &vault, user_shares);
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    storage::set_reserve_vault_shares(e, &vault.address, user, user_shares);
+    (b_tokens_amount, underlying_amount)
+}
+
+/// Redeem an exact number of shares from the reserve vault. Does not perform the call to the pool to withdraw the tokens.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user redeeming shares
+/// * `shares` - The number of shares to burn
+///
+/// ### Returns
+/// * `(i128, i128)` - (The amount of b_tokens burned from the vault, the amount of underlying owed to the user)
+///
+/// ### Panics
+/// * If the amount of b_tokens burnt is less than or equal to 0
+/// * If the user does not have enough shares
+pub fn redeem(e: &Env, reserve: &Address, user: &Address, shares: i128) -> (i128, i128) {
+    let mut vault = get_reserve_vault_updated(e, reserve);
+
+    let mut user_shares = storage::get_reserve_vault_shares(e, &vault.address, user);
+    if shares > user_shares {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+
+    // round against the user, as they are burning an exact number of shares
+    let b_tokens_amount = vault.shares_to_b_tokens_down(shares);
+    require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensBurnt);
+    let underlying_amount = vault.b_tokens_to_underlying_down(b_tokens_amount);
+
+    if vault.total_shares < shares || vault.total_b_tokens < b_tokens_amount {
+        panic_with_error!(e, FeeVaultError::InsufficientReserves);
+    }
+
+    emissions::update_user_emissions(e, &vault.address, vault.total_shares, user, user_shares);
+    vault.total_shares -= shares;
+    vault.total_b_tokens -= b_tokens_amount;
+    user_shares -= shares;
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    storage::set_reserve_vault_shares(e, &vault.address, user, user_shares);
+    (b_tokens_amount, underlying_amount)
+}
+
+/// Burns a user's shares without withdrawing. The bTokens backing the shares are left to the
+/// remaining depositors, or added to the fees if no shares remain.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user burning shares
+/// * `shares` - The number of shares to burn
+///
+/// ### Panics
+/// * If the number of shares is negative
+/// * If the user does not have enough shares
+pub fn burn(e: &Env, reserve: &Address, user: &Address, shares: i128) {
+    require_nonnegative(e, shares);
+    let mut vault = get_reserve_vault_updated(e, reserve);
+
+    let user_shares = storage::get_reserve_vault_shares(e, &vault.address, user);
+    if shares > user_shares {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+
+    emissions::update_user_emissions(e, &vault.address, vault.total_shares, user, user_shares);
+    vault.total_shares -= shares;
+    if vault.total_shares == 0 {
+        vault.accrued_fees += vault.total_b_tokens;
+        vault.total_b_tokens = 0;
+    }
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    storage::set_reserve_vault_shares(e, &vault.address, user, user_shares - shares);
+}
+
+/// Fetch the amount of underlying tokens a user can deposit before the reserve vault or the user
+/// reaches a deposit cap
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user depositing
+///
+/// ### Returns
+/// * `i128` - The capacity left, or i128::MAX if the reserve vault has no deposit caps
+pub fn deposit_capacity(e: &Env, reserve: &Address, user: &Address) -> i128 {
+    let vault = get_reserve_vault_updated(e, reserve);
+    let caps = storage::get_deposit_caps(e, reserve);
+    let mut capacity = i128::MAX;
+    if let Some(reserve_cap) = caps.reserve_cap {
+        let total_underlying = vault.b_tokens_to_underlying_down(vault.total_b_tokens);
+        capacity = capacity.min(reserve_cap - total_underlying);
+    }
+    if let Some(user_cap) = caps.user_cap {
+        let user_shares = storage::get_reserve_vault_shares(e, reserve, user);
+        capacity = capacity.min(user_cap - vault.shares_to_underlying_down(user_shares));
+    }
+    capacity.max(0)
+}
+
+/// Require that a reserve vault and a user's position are within the reserve vault's deposit caps
+///
+/// ### Arguments
+/// * `vault` - The reserve vault after the deposit
+/// * `user_shares` - The user's shares after the deposit
+///
+/// ### Panics
+/// * `ReserveCapExceeded` - If the reserve vault holds more than its cap
+/// * `UserCapExceeded` - If the user's position is worth more than the user cap
+fn require_within_deposit_caps(e: &Env, vault: &ReserveVault, user_shares: i128) {
+    let caps = storage::get_deposit_caps(e, &vault.address);
+    if let Some(reserve_cap) = caps.reserve_cap {
+        if vault.b_tokens_to_underlying_down(vault.total_b_tokens) > reserve_cap {
+            panic_with_error!(e, FeeVaultError::ReserveCapExceeded);
+        }
+    }
+    require_within_user_cap(e, vault, user_shares);
+}
+
+/// Require that a user's position is within the reserve vault's user deposit cap
+///
+/// ### Arguments
+/// * `vault` - The reserve vault
+/// * `user_shares` - The user's shares after the shares are received
+///
+/// ### Panics
+/// * `UserCapExceeded` - If the user's position is worth more than the user cap
+pub fn require_within_user_cap(e: &Env, vault: &ReserveVault, user_shares: i128) {
+    if let Some(user_cap) = storage::get_deposit_caps(e, &vault.address).user_cap {
+        if vault.shares_to_underlying_down(user_shares) > user_cap {
+            panic_with_error!(e, FeeVaultError::UserCapExceeded);
+        }
+    }
+}
+
 /// Claim fees from the reserve vault. Does not perform the call to the pool to claim the fees.
 ///
 /// ### Arguments
@@ -236,6 +497,124 @@ pub fn claim_fees(e: &Env, reserve: &Address) -> (i128, i128) {
     (b_tokens_amount, underlying_amount)
 }
 
+/// Claim the fees from the reserve vault that the pool has the liquidity to withdraw. Does not
+/// perform the call to the pool to claim the fees.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `(i128, i128)` - (The amount of b_tokens burnt from the accrued fees, the amount of underlying
+///                    tokens claimed), or (0, 0) if there are no fees or no liquidity to claim
+pub fn claim_available_fees(e: &Env, reserve: &Address) -> (i128, i128) {
+    let mut vault = get_reserve_vault_updated(e, reserve);
+    let accrued_underlying = vault.b_tokens_to_underlying_down(vault.accrued_fees);
+    if accrued_underlying <= 0 {
+        return (0, 0);
+    }
+    let available = pool::reserve_available_liquidity(e, reserve);
+    if available <= 0 {
+        return (0, 0);
+    }
+
+    let (b_tokens_amount, underlying_amount) = if accrued_underlying <= available {
+        (vault.accrued_fees, accrued_underlying)
+    } else {
+        (vault.underlying_to_b_tokens_up(available), available)
+    };
+    vault.accrued_fees -= b_tokens_amount;
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    (b_tokens_amount, underlying_amount)
+}
+
+/// Convert the fees accrued by the reserve vault into shares. The accrued bTokens stay in the vault
+/// and back the new shares, so no call to the pool is needed.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `user` - The user receiving the shares
+///
+/// ### Returns
+/// * `(i128, i128)` - (The amount of b_tokens converted, the amount of shares minted to the user)
+///
+/// ### Panics
+/// * If the accrued bToken amount is less than or equal to 0
+/// * If the amount of shares minted is less than or equal to 0
+/// * If the conversion exceeds a deposit cap
+pub fn convert_fees(e: &Env, reserve: &Address, user: &Address) -> (i128, i128) {
+    let mut vault = get_reserve_vault_updated(e, reserve);
+    let b_tokens_amount = vault.accrued_fees;
+    require_positive(e, b_tokens_amount, FeeVaultError::InsufficientAccruedFees);
+
+    let mut user_shares = storage::get_reserve_vault_shares(e, &vault.address, user);
+    let share_amount = vault.b_tokens_to_shares_down(b_tokens_amount);
+    require_positive(e, share_amount, FeeVaultError::InvalidSharesMinted);
+    emissions::update_user_emissions(e, &vault.address, vault.total_shares, user, user_shares);
+
+    vault.accrued_fees = 0;
+    vault.total_shares += share_amount;
+    vault.total_b_tokens += b_tokens_amount;
+    user_shares += share_amount;
+    require_within_deposit_caps(e, &vault, user_shares);
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    storage::set_reserve_vault_shares(e, &vault.address, user, user_shares);
+    (b_tokens_amount, share_amount)
+}
+
+/// Claim part of the fees from the reserve vault. Does not perform the call to the pool to claim the
+/// fees.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `amount` - The amount of underlying tokens to claim
+///
+/// ### Returns
+/// * `i128` - The amount of b_tokens burnt from the accrued fees
+///
+/// ### Panics
+/// * If the amount is less than or equal to 0
+/// * If the accrued bToken amount is less than the bTokens needed for the amount
+pub fn claim_fees_amount(e: &Env, reserve: &Address, amount: i128) -> i128 {
+    require_positive(e, amount, FeeVaultError::InvalidAmount);
+    let mut vault = get_reserve_vault_updated(e, reserve);
+    let b_tokens_amount = vault.underlying_to_b_tokens_up(amount);
+    if b_tokens_amount > vault.accrued_fees {
+        panic_with_error!(e, FeeVaultError::InsufficientAccruedFees);
+    }
+
+    vault.accrued_fees -= b_tokens_amount;
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    b_tokens_amount
+}
+
+/// Adds compounded tokens to the reserve vault without minting shares, raising the share price for
+/// all depositors. If the reserve vault has no shares, the tokens are added to the accrued fees
+/// instead, so the next depositor does not receive them. Does not perform the call to the pool to
+/// supply the tokens.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `amount` - The amount of underlying supplied to the pool
+///
+/// ### Returns
+/// * `i128` - The amount of b_tokens added to the vault
+///
+/// ### Panics
+/// * If the b_token amount is less than or equal to 0
+pub fn compound(e: &Env, reserve: &Address, amount: i128) -> i128 {
+    let mut vault = get_reserve_vault_updated(e, reserve);
+    let b_tokens_amount = vault.underlying_to_b_tokens_down(amount);
+    require_positive(e, b_tokens_amount, FeeVaultError::InvalidBTokensMinted);
+
+    if vault.total_shares == 0 {
+        vault.accrued_fees += b_tokens_amount;
+    } else {
+        vault.total_b_tokens += b_tokens_amount;
+    }
+    storage::set_reserve_vault(e, &vault.address, &vault);
+    b_tokens_amount
+}
+
 /// Accrues interest and updates the b_rate for all reserves
 pub fn accrue_interest_for_all_reserves(e: &Env) {
     let reserves = storage::get_reserves(e);
@@ -258,6 +637,7 @@ mod generic_tests {
         let mut vault = ReserveVault {
             address: Address::generate(&e),
             b_rate: 1_000_000_000_000,
+            high_water_b_rate: 1_000_000_000_000,
             last_update_timestamp: 0,
             total_shares: 0,
             total_b_tokens: 0,
@@ -289,6 +669,7 @@ mod generic_tests {
         let mut vault = ReserveVault {
             address: Address::generate(&e),
             b_rate: 1_000_000_000_000,
+            high_water_b_rate: 1_000_000_000_000,
             last_update_timestamp: 0,
             total_shares: 0,
             total_b_tokens: 0,
@@ -320,6 +701,7 @@ mod generic_tests {
         let mut vault = ReserveVault {
             address: Address::generate(&e),
             b_rate: 1_000_000_000_000,
+            high_water_b_rate: 1_000_000_000_000,
             last_update_timestamp: 0,
             total_shares: 0,
             total_b_tokens: 0,
@@ -337,6 +719,63 @@ mod generic_tests {
         vault.total_b_tokens = 0;
         let b_tokens = vault.shares_to_b_tokens_down(2_0000000);
         assert_eq!(b_tokens, 0);
+
+        // returns amount if total_shares is 0
+        vault.total_shares = 0;
+        vault.total_b_tokens = 0;
+        let b_tokens = vault.shares_to_b_tokens_down(2_0000000);
+        assert_eq!(b_tokens, 2_0000000);
+    }
+
+    #[test]
+    fn test_shares_to_b_tokens_up() {
+        let e = Env::default();
+        let mut vault = ReserveVault {
+            address: Address::generate(&e),
+            b_rate: 1_000_000_000_000,
+            high_water_b_rate: 1_000_000_000_000,
+            last_update_timestamp: 0,
+            total_shares: 0,
+            total_b_tokens: 0,
+            accrued_fees: 0,
+        };
+
+        // rounds up
+        vault.total_shares = 200_0000001;
+        vault.total_b_tokens = 100_0000000;
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000000);
+        assert_eq!(b_tokens, 1_0000000);
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000001);
+        assert_eq!(b_tokens, 1_0000001);
+
+        // returns amount if total_shares is 0
+        vault.total_shares = 0;
+        vault.total_b_tokens = 100_0000000;
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000000);
+        assert_eq!(b_tokens, 2_0000000);
+
+        // returns amount if total_b_tokens is 0
+        vault.total_shares = 200_0000000;
+        vault.total_b_tokens = 0;
+        let b_tokens = vault.shares_to_b_tokens_up(2_0000000);
+        assert_eq!(b_tokens, 2_0000000);
+    }
+
+    #[test]
+    fn test_b_tokens_to_underlying_up() {
+        let e = Env::default();
+        let vault = ReserveVault {
+            address: Address::generate(&e),
+            b_rate: 1_100_000_000_001,
+            high_water_b_rate: 1_100_000_000_001,
+            last_update_timestamp: 0,
+            total_shares: 0,
+            total_b_tokens: 0,
+            accrued_fees: 0,
+        };
+
+        assert_eq!(vault.b_tokens_to_underlying_up(1_0000000), 1_1000001);
+        assert_eq!(vault.b_tokens_to_underlying_down(1_0000000), 1_1000000);
     }
 
     #[test]
@@ -352,8 +791,7 @@ mod generic_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
         let samwise = Address::generate(&e);
@@ -365,6 +803,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -413,8 +852,7 @@ mod generic_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
         let samwise = Address::generate(&e);
@@ -426,6 +864,7 @@ mod generic_tests {
                 total_b_tokens: 0,
                 total_shares: 0,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -473,6 +912,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -498,6 +938,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -524,6 +965,7 @@ mod generic_tests {
                 total_b_tokens: 10000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -544,8 +986,7 @@ mod generic_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
         let samwise = Address::generate(&e);
@@ -557,6 +998,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -618,6 +1060,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -650,6 +1093,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -675,6 +1119,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -702,6 +1147,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -737,6 +1183,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -750,6 +1197,214 @@ mod generic_tests {
         });
     }
 
+    #[test]
+    fn test_mint() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_100_000_000_000;
+
+        let mock_client = &mockpool::register_mock_pool_with_b_rate(&e, init_b_rate);
+        let vault_address = register_fee_vault(
+            &e,
+            Some((
+                Address::generate(&e),
+                mock_client.address.clone(),
+                FeeMode::TakeRate(0_1000000),
+            )),
+        );
+        let samwise = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let reserve_vault = ReserveVault {
+                address: reserve.clone(),
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
+                last_update_timestamp: e.ledger().timestamp(),
+                accrued_fees: 0,
+            };
+            storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 10_0000000);
+
+            let shares = 100_0000001;
+            let expected_b_tokens = 83_3333335;
+            let expected_underlying = 91_6666669;
+            let (b_tokens_minted, underlying) = mint(&e, &reserve, &samwise, shares);
+            assert_eq!(b_tokens_minted, expected_b_tokens);
+            assert_eq!(underlying, expected_underlying);
+            // the underlying supplied to the pool must mint at least the bTokens credited to the vault
+            assert!(reserve_vault.underlying_to_b_tokens_down(underlying) >= b_tokens_minted);
+
+            let new_vault = storage::get_reserve_vault(&e, &reserve);
+            assert_eq!(new_vault.total_shares, 1200_0000000 + shares);
+            assert_eq!(new_vault.total_b_tokens, 1000_0000000 + expected_b_tokens);
+            assert_eq!(
+                storage::get_reserve_vault_shares(&e, &reserve, &samwise),
+                10_0000000 + shares
+            );
+        });
+    }
+
+    #[test]
+    fn test_initial_mint() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let reserve_vault = ReserveVault {
+                address: reserve.clone(),
+                total_b_tokens: 0,
+                total_shares: 0,
+                b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                accrued_fees: 0,
+            };
+            storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+
+            let (b_tokens_minted, underlying) = mint(&e, &reserve, &samwise, 100_0000000);
+            assert_eq!(b_tokens_minted, 100_0000000);
+            assert_eq!(underlying, 110_0000000);
+
+            let new_vault = storage::get_reserve_vault(&e, &reserve);
+            assert_eq!(new_vault.total_shares, 100_0000000);
+            assert_eq!(new_vault.total_b_tokens, 100_0000000);
+        });
+    }
+
+    #[test]
+    fn test_redeem() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let reserve_vault = ReserveVault {
+                address: reserve.clone(),
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                accrued_fees: 0,
+            };
+            storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 120_0000000);
+
+            let shares = 100_0000001;
+            let (b_tokens_burnt, underlying) = redeem(&e, &reserve, &samwise, shares);
+            assert_eq!(b_tokens_burnt, 83_3333334);
+            assert_eq!(underlying, 91_6666667);
+            // the underlying withdrawn from the pool must burn at most the bTokens removed from the vault
+            assert!(reserve_vault.underlying_to_b_tokens_up(underlying) <= b_tokens_burnt);
+
+            let new_vault = storage::get_reserve_vault(&e, &reserve);
+            assert_eq!(new_vault.total_shares, 1200_0000000 - shares);
+            assert_eq!(new_vault.total_b_tokens, 1000_0000000 - b_tokens_burnt);
+            assert_eq!(
+                storage::get_reserve_vault_shares(&e, &reserve, &samwise),
+                120_0000000 - shares
+            );
+        });
+    }
+
+    #[test]
+    fn test_redeem_max() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let reserve_vault = ReserveVault {
+                address: reserve.clone(),
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                accrued_fees: 0,
+            };
+            storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 1200_0000000);
+
+            let (b_tokens_burnt, underlying) = redeem(&e, &reserve, &samwise, 1200_0000000);
+            assert_eq!(b_tokens_burnt, 1000_0000000);
+            assert_eq!(underlying, 1100_0000000);
+
+            let new_vault = storage::get_reserve_vault(&e, &reserve);
+            assert_eq!(new_vault.total_shares, 0);
+            assert_eq!(new_vault.total_b_tokens, 0);
+            assert_eq!(storage::get_reserve_vault_shares(&e, &reserve, &samwise), 0);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_redeem_over_balance() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let reserve_vault = ReserveVault {
+                address: reserve.clone(),
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                accrued_fees: 0,
+            };
+            storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 100_0000000);
+
+            redeem(&e, &reserve, &samwise, 100_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #107)")]
+    fn test_redeem_zero_b_tokens() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let reserve_vault = ReserveVault {
+                address: reserve.clone(),
+                total_b_tokens: 1000_0000000,
+                total_shares: 1200_0000000,
+                b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
+                last_update_timestamp: e.ledger().timestamp(),
+                accrued_fees: 0,
+            };
+            storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 100_0000000);
+
+            redeem(&e, &reserve, &samwise, 1);
+        });
+    }
+
     #[test]
     fn test_claim_fees() {
         let e = Env::default();
@@ -762,8 +1417,7 @@ mod generic_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
         let reserve = Address::generate(&e);
@@ -775,6 +1429,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: starting_fees,
             };
@@ -837,6 +1492,7 @@ mod generic_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -858,8 +1514,7 @@ mod generic_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
         let reserve = Address::generate(&e);
@@ -872,6 +1527,7 @@ mod generic_tests {
                 total_b_tokens: 0,
                 total_shares: 0,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees,
             };
@@ -915,8 +1571,7 @@ mod take_rate_tests {
             Some((
                 bombadil.clone(),
                 mock_client.address.clone(),
-                false,
-                200_0000,
+                FeeMode::TakeRate(200_0000),
             )),
         );
 
@@ -927,6 +1582,7 @@ mod take_rate_tests {
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -973,8 +1629,7 @@ mod take_rate_tests {
             Some((
                 bombadil.clone(),
                 mock_client.address.clone(),
-                false,
-                200_0000,
+                FeeMode::TakeRate(200_0000),
             )),
         );
 
@@ -984,6 +1639,7 @@ mod take_rate_tests {
                 total_b_tokens: 500_000_0000000,
                 total_shares: 500_000_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             };
@@ -1024,6 +1680,7 @@ mod take_rate_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: now,
                 accrued_fees: 12_0000000,
             };
@@ -1052,6 +1709,7 @@ mod take_rate_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: now,
                 accrued_fees: 12_0000000,
             };
@@ -1084,8 +1742,7 @@ mod take_rate_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
 
@@ -1096,6 +1753,7 @@ mod take_rate_tests {
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 100_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1138,8 +1796,7 @@ mod take_rate_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                false,
-                0_1000000,
+                FeeMode::TakeRate(0_1000000),
             )),
         );
 
@@ -1151,6 +1808,7 @@ mod take_rate_tests {
                 last_update_timestamp: init_timestamp,
                 total_shares: 100_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1170,6 +1828,7 @@ mod take_rate_tests {
             // reset vault
             reserve_vault.total_b_tokens = 1000_0000000;
             reserve_vault.b_rate = init_b_rate;
+            reserve_vault.high_water_b_rate = init_b_rate;
             reserve_vault.accrued_fees = 0;
             reserve_vault.last_update_timestamp = init_timestamp;
 
@@ -1181,6 +1840,71 @@ mod take_rate_tests {
             assert_eq!(reserve_vault.last_update_timestamp, init_timestamp + 5);
         });
     }
+
+    #[test]
+    fn test_update_rate_after_loss() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let init_b_rate = 1_000_000_000_000;
+        let bombadil = Address::generate(&e);
+
+        let mock_client = &mockpool::register_mock_pool_with_b_rate(&e, init_b_rate);
+        let vault_address = register_fee_vault(
+            &e,
+            Some((
+                bombadil.clone(),
+                mock_client.address.clone(),
+                FeeMode::TakeRate(200_0000),
+            )),
+        );
+
+        e.as_contract(&vault_address, || {
+            let mut reserve_vault = ReserveVault {
+                address: Address::generate(&e),
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1000_0000000,
+                b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
+                accrued_fees: 0,
+            };
+
+            // 10% growth accrues fees and raises the high water mark
+            mock_client.set_b_rate(&1_100_000_000_000);
+            e.jump(1);
+            reserve_vault.update_rate(&e);
+            let expected_fees = 181818181;
+            assert_eq!(reserve_vault.accrued_fees, expected_fees);
+            assert_eq!(reserve_vault.high_water_b_rate, 1_100_000_000_000);
+            assert_eq!(reserve_vault.drawdown(), 0);
+
+            // A loss lowers the b_rate but not the high water mark
+            mock_client.set_b_rate(&900_000_000_000);
+            e.jump(1);
+            reserve_vault.update_rate(&e);
+            assert_eq!(reserve_vault.accrued_fees, expected_fees);
+            assert_eq!(reserve_vault.b_rate, 900_000_000_000);
+            assert_eq!(reserve_vault.high_water_b_rate, 1_100_000_000_000);
+            assert_eq!(reserve_vault.drawdown(), 0_1818182);
+
+            // Recovering to the high water mark does not accrue fees
+            mock_client.set_b_rate(&1_100_000_000_000);
+            e.jump(1);
+            reserve_vault.update_rate(&e);
+            assert_eq!(reserve_vault.accrued_fees, expected_fees);
+            assert_eq!(reserve_vault.b_rate, 1_100_000_000_000);
+            assert_eq!(reserve_vault.drawdown(), 0);
+            assert_eq!(reserve_vault.total_b_tokens, 1000_0000000 - expected_fees);
+
+            // Only the growth above the high water mark accrues fees
+            mock_client.set_b_rate(&1_200_000_000_000);
+            e.jump(1);
+            reserve_vault.update_rate(&e);
+            assert_eq!(reserve_vault.accrued_fees, expected_fees + 163636363);
+            assert_eq!(reserve_vault.high_water_b_rate, 1_200_000_000_000);
+        });
+    }
 }
 
 #[cfg(test)]
@@ -1217,8 +1941,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                true,
-                0_0500000,
+                FeeMode::AprCap(0_0500000),
             )),
         );
 
@@ -1229,6 +1952,7 @@ mod apr_capped_tests {
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1331,8 +2055,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                true,
-                0_0600000,
+                FeeMode::AprCap(0_0600000),
             )),
         );
 
@@ -1343,6 +2066,7 @@ mod apr_capped_tests {
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1422,8 +2146,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                true,
-                0_0100000,
+                FeeMode::AprCap(0_0100000),
             )),
         );
 
@@ -1435,6 +2158,7 @@ mod apr_capped_tests {
                 last_update_timestamp: init_timestamp,
                 total_shares: 10_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1453,6 +2177,7 @@ mod apr_capped_tests {
             // reset vault
             reserve_vault.total_b_tokens = 100_0000000;
             reserve_vault.b_rate = init_b_rate;
+            reserve_vault.high_water_b_rate = init_b_rate;
             reserve_vault.accrued_fees = 0;
             reserve_vault.last_update_timestamp = init_timestamp;
 
@@ -1480,8 +2205,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                true,
-                0_1000000,
+                FeeMode::AprCap(0_1000000),
             )),
         );
 
@@ -1492,6 +2216,7 @@ mod apr_capped_tests {
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1514,13 +2239,7 @@ mod apr_capped_tests {
             assert_eq!(reserve_vault.last_update_timestamp, e.ledger().timestamp());
 
             // The admin decides to update the apr_cap to 5%, as 10% didn't yield any interest to the admin
-            storage::set_fee_mode(
-                &e,
-                FeeMode {
-                    is_apr_capped: true,
-                    value: 0_0500000,
-                },
-            );
+            storage::set_fee_mode(&e, FeeMode::AprCap(0_0500000));
 
             // Assume 4% APR increase over the the next 6 months, 8% yearly
             let new_b_rate = 1_092_000_000_000;
@@ -1567,8 +2286,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mock_client.address.clone(),
-                true,
-                0_0800000,
+                FeeMode::AprCap(0_0800000),
             )),
         );
 
@@ -1579,6 +2297,7 @@ mod apr_capped_tests {
                 last_update_timestamp: e.ledger().timestamp(),
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 accrued_fees: 0,
             };
 
@@ -1607,13 +2326,7 @@ mod apr_capped_tests {
             assert_eq!(reserve_vault.last_update_timestamp, e.ledger().timestamp());
 
             // Update the fee mode to take_rate with 20% take rate
-            storage::set_fee_mode(
-                &e,
-                FeeMode {
-                    is_apr_capped: false,
-                    value: 200_0000,
-                },
-            );
+            storage::set_fee_mode(&e, FeeMode::TakeRate(200_0000));
 
             let new_b_rate = 1_200_000_000_000;
 
@@ -1644,8 +2357,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mockpool::register_mock_pool_with_b_rate(&e, 1_100_000_000_000).address,
-                true,
-                0_0500000,
+                FeeMode::AprCap(0_0500000),
             )),
         );
 
@@ -1656,6 +2368,7 @@ mod apr_capped_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: now,
                 accrued_fees: 12_0000000,
             };
@@ -1680,8 +2393,7 @@ mod apr_capped_tests {
             Some((
                 Address::generate(&e),
                 mockpool::register_mock_pool_with_b_rate(&e, 1_100_000_000_000).address,
-                true,
-                0_0500000,
+                FeeMode::AprCap(0_0500000),
             )),
         );
 
@@ -1692,6 +2404,7 @@ mod apr_capped_tests {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: 1_100_000_000_000,
+                high_water_b_rate: 1_100_000_000_000,
                 last_update_timestamp: now,
                 accrued_fees: 12_0000000,
             };
@@ -1709,3 +2422,89 @@ mod apr_capped_tests {
         });
     }
 }
+
+#[cfg(test)]
+mod hybrid_tests {
+    use super::*;
+    use crate::testutils::{assert_approx_eq_rel, mockpool, register_fee_vault, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, Address};
+
+    #[test]
+    fn test_update_rate() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let init_b_rate = 1_000_000_000_000;
+
+        let mock_client = &mockpool::register_mock_pool_with_b_rate(&e, init_b_rate);
+        // Fee vault with a 10% take rate and an 8% apr cap
+        let vault_address = register_fee_vault(
+            &e,
+            Some((
+                Address::generate(&e),
+                mock_client.address.clone(),
+                FeeMode::Hybrid(0_1000000, 0_0800000),
+            )),
+        );
+
+        e.as_contract(&vault_address, || {
+            let mut reserve_vault = ReserveVault {
+                address: Address::generate(&e),
+                total_b_tokens: 1000_0000000,
+                last_update_timestamp: e.ledger().timestamp(),
+                total_shares: 1000_0000000,
+                b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
+                accrued_fees: 0,
+            };
+            let underlying_value_before =
+                reserve_vault.b_tokens_to_underlying_down(reserve_vault.total_b_tokens);
+
+            // 12% APR over a year
+            mock_client.set_b_rate(&1_120_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            reserve_vault.update_rate(&e);
+
+            // The admin takes the 4% above the cap, and 10% of the 8% up to it
+            let underlying_value_after =
+                reserve_vault.b_tokens_to_underlying_down(reserve_vault.total_b_tokens);
+            assert_approx_eq_rel(
+                underlying_value_after,
+                underlying_value_before * 10_720 / 10_000,
+                0_0000001,
+            );
+            let accrued_fees_value =
+                reserve_vault.b_tokens_to_underlying_down(reserve_vault.accrued_fees);
+            assert_approx_eq_rel(
+                accrued_fees_value,
+                underlying_value_before * 480 / 10_000,
+                0_0000001,
+            );
+            assert_eq!(
+                reserve_vault.total_b_tokens + reserve_vault.accrued_fees,
+                1000_0000000
+            );
+
+            // 5% APR over a year, below the cap
+            let accrued_fees = reserve_vault.accrued_fees;
+            mock_client.set_b_rate(&1_176_000_000_000);
+            e.jump_time(SECONDS_PER_YEAR as u64);
+            reserve_vault.update_rate(&e);
+
+            // Only the take rate applies
+            assert_approx_eq_rel(
+                reserve_vault.b_tokens_to_underlying_down(reserve_vault.total_b_tokens),
+                underlying_value_after * 10_450 / 10_000,
+                0_0000001,
+            );
+            let new_fees_value = reserve_vault
+                .b_tokens_to_underlying_down(reserve_vault.accrued_fees - accrued_fees);
+            assert_approx_eq_rel(
+                new_fees_value,
+                underlying_value_after * 50 / 10_000,
+                0_0000001,
+            );
+        });
+    }
+}
@@ -0,0 +1,71 @@
+use soroban_sdk::{contractclient, panic_with_error, token::TokenClient, Address, Env};
+
+use crate::{errors::FeeVaultError, storage};
+
+/// The swap router interface used to compound emissions. DEX specific routers are expected to be
+/// wrapped by an adapter contract that implements this interface.
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    /// Swap an exact amount of `token_in` for `token_out`. The `token_in` amount is transferred
+    /// to the router before the call.
+    ///
+    /// ### Arguments
+    /// * `token_in` - The token being sold
+    /// * `token_out` - The token being bought
+    /// * `amount_in` - The amount of `token_in` to sell
+    /// * `min_amount_out` - The minimum amount of `token_out` to receive
+    /// * `to` - The address to send the `token_out` to
+    ///
+    /// ### Returns
+    /// * `i128` - The amount of `token_out` sent to `to`
+    fn swap_exact_in(
+        e: Env,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_amount_out: i128,
+        to: Address,
+    ) -> i128;
+}
+
+/// Swaps BLND held by the vault for a reserve asset through the swap router. The amount received is
+/// measured from the vault's balance rather than trusted from the router.
+///
+/// ### Arguments
+/// * `router` - The swap router address
+/// * `reserve` - The reserve asset to buy
+/// * `amount_in` - The amount of BLND to sell
+/// * `min_amount_out` - The minimum amount of the reserve asset to receive
+///
+/// ### Returns
+/// * `i128` - The amount of the reserve asset the vault received
+///
+/// ### Panics
+/// * `MinAmountOutNotMet` - If the vault received less than `min_amount_out`
+pub fn swap_blnd(
+    e: &Env,
+    router: &Address,
+    reserve: &Address,
+    amount_in: i128,
+    min_amount_out: i128,
+) -> i128 {
+    let blnd = storage::get_blnd(e);
+    let vault = e.current_contract_address();
+    let reserve_client = TokenClient::new(e, reserve);
+    let balance_before = reserve_client.balance(&vault);
+
+    TokenClient::new(e, &blnd).transfer(&vault, router, &amount_in);
+    SwapRouterClient::new(e, router).swap_exact_in(
+        &blnd,
+        reserve,
+        &amount_in,
+        &min_amount_out,
+        &vault,
+    );
+
+    let amount_out = reserve_client.balance(&vault) - balance_before;
+    if amount_out < min_amount_out {
+        panic_with_error!(e, FeeVaultError::MinAmountOutNotMet);
+    }
+    amount_out
+}
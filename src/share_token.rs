@@ -0,0 +1,336 @@
+use crate::{
+    emissions,
+    errors::FeeVaultError,
+    reserve_vault,
+    storage::{self, ShareAllowance},
+    validator::require_nonnegative,
+};
+use soroban_sdk::{panic_with_error, xdr::ToXdr, Address, Env};
+
+/// Deploy the SEP-41 share token for a reserve vault. The share token is deployed once per
+/// reserve, so a reserve that is added back reuses its share token.
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+///
+/// ### Returns
+/// * `Address` - The address of the share token
+pub fn deploy(e: &Env, reserve: &Address) -> Address {
+    if let Some(share_token) = storage::get_share_token(e, reserve) {
+        return share_token;
+    }
+    let salt = e.crypto().sha256(&reserve.clone().to_xdr(e));
+    let share_token = e
+        .deployer()
+        .with_current_contract(salt.to_bytes())
+        .deploy_v2(
+            storage::get_share_token_wasm_hash(e),
+            (e.current_contract_address(), reserve.clone()),
+        );
+    storage::set_share_token(e, reserve, &share_token);
+    share_token
+}
+
+/// Transfer shares of a reserve vault between users
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `from` - The user sending the shares
+/// * `to` - The user receiving the shares
+/// * `amount` - The number of shares to transfer
+///
+/// ### Panics
+/// * If the amount is negative
+/// * If `from` does not have enough shares
+/// * If `to`'s position would be worth more than the user deposit cap
+pub fn transfer(e: &Env, reserve: &Address, from: &Address, to: &Address, amount: i128) {
+    require_nonnegative(e, amount);
+    let from_shares = storage::get_reserve_vault_shares(e, reserve, from);
+    if amount > from_shares {
+        panic_with_error!(e, FeeVaultError::BalanceError);
+    }
+    if from == to {
+        return;
+    }
+    let to_shares = storage::get_reserve_vault_shares(e, reserve, to);
+
+    let vault = reserve_vault::get_reserve_vault_updated(e, reserve);
+    reserve_vault::require_within_user_cap(e, &vault, to_shares + amount);
+
+    let reserve_emissions = emissions::update_reserve_emissions(e, reserve, vault.total_shares);
+    emissions::checkpoint_user(e, reserve, &reserve_emissions, from, from_shares);
+    emissions::checkpoint_user(e, reserve, &reserve_emissions, to, to_shares);
+
+    storage::set_reserve_vault_shares(e, reserve, from, from_shares - amount);
+    storage::set_reserve_vault_shares(e, reserve, to, to_shares + amount);
+}
+
+/// Set the number of shares a spender can withdraw on behalf of a user
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `owner` - The user who owns the shares
+/// * `spender` - The user allowed to withdraw the shares
+/// * `shares` - The number of shares the spender can withdraw
+/// * `expiration_ledger` - The ledger sequence the allowance expires at
+///
+/// ### Panics
+/// * If the number of shares is negative
+/// * If the expiration ledger is in the past or past the max TTL and the shares are not 0
+pub fn approve_withdraw(
+    e: &Env,
+    reserve: &Address,
+    owner: &Address,
+    spender: &Address,
+    shares: i128,
+    expiration_ledger: u32,
+) {
+    require_valid_allowance(e, shares, expiration_ledger);
+    storage::set_withdraw_allowance(
+        e,
+        reserve,
+        owner,
+        spender,
+        &ShareAllowance {
+            amount: shares,
+            expiration_ledger,
+        },
+    );
+}
+
+/// Spend shares from a spender's withdraw allowance
+///
+/// ### Arguments
+/// * `reserve` - The reserve address
+/// * `owner` - The user who owns the shares
+/// * `spender` - The user withdrawing the shares
+/// * `shares` - The number of shares to spend
+///
+/// ### Panics
+/// * If the spender's withdraw allowance is less than the number of shares
+pub fn spend_withdraw_allowance(
+    e: &Env,
+    reserve: &Address,
+    owner: &Address,
+    spender: &Address,
+    shares: i128,
+) {
+    let mut allowance = storage::get_withdraw_allowance(e, reserve, owner, spender);
+    if allowance.amount < shares {
+        panic_with_error!(e, FeeVaultError::AllowanceError);
+    }
+    allowance.amount -= shares;
+    storage::set_withdraw_allowance(e, reserve, owner, spender, &allowance);
+}
+
+/// Require an allowance is non-negative, and that a positive allowance expires between the
+/// current ledger and the max TTL of a storage entry
+fn require_valid_allowance(e: &Env, amount: i128, expiration_ledger: u32) {
+    require_nonnegative(e, amount);
+    if amount > 0
+        && (expiration_ledger < e.ledger().sequence()
+            || expiration_ledger > e.ledger().max_live_until_ledger())
+    {
+        panic_with_error!(e, FeeVaultError::InvalidExpirationLedger);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reserve_vault::ReserveVault;
+    use crate::testutils::{register_fee_vault, EnvTestUtils};
+    use soroban_sdk::{testutils::Address as _, Address};
+
+    #[test]
+    fn test_transfer() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            storage::set_reserve_vault(
+                &e,
+                &reserve,
+                &ReserveVault {
+                    address: reserve.clone(),
+                    b_rate: 1_000_000_000_000,
+                    high_water_b_rate: 1_000_000_000_000,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    total_shares: 100_0000000,
+                    total_b_tokens: 100_0000000,
+                    accrued_fees: 0,
+                },
+            );
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 100_0000000);
+
+            transfer(&e, &reserve, &samwise, &frodo, 40_0000000);
+            assert_eq!(
+                storage::get_reserve_vault_shares(&e, &reserve, &samwise),
+                60_0000000
+            );
+            assert_eq!(
+                storage::get_reserve_vault_shares(&e, &reserve, &frodo),
+                40_0000000
+            );
+
+            // transfers to self do not change the balance
+            transfer(&e, &reserve, &frodo, &frodo, 40_0000000);
+            assert_eq!(
+                storage::get_reserve_vault_shares(&e, &reserve, &frodo),
+                40_0000000
+            );
+
+            // transfers of 0 are allowed
+            transfer(&e, &reserve, &frodo, &samwise, 0);
+            assert_eq!(
+                storage::get_reserve_vault_shares(&e, &reserve, &samwise),
+                60_0000000
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #10)")]
+    fn test_transfer_over_balance() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            storage::set_reserve_vault_shares(&e, &reserve, &samwise, 100_0000000);
+
+            transfer(&e, &reserve, &samwise, &frodo, 100_0000001);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #8)")]
+    fn test_transfer_negative() {
+        let e = Env::default();
+        e.mock_all_auths();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            storage::set_reserve_vault_shares(&e, &reserve, &frodo, 100_0000000);
+
+            transfer(&e, &reserve, &samwise, &frodo, -1);
+        });
+    }
+
+    #[test]
+    fn test_approve_and_spend_withdraw_allowance() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve_withdraw(
+                &e,
+                &reserve,
+                &samwise,
+                &frodo,
+                50_0000000,
+                expiration_ledger,
+            );
+            let allowance = storage::get_withdraw_allowance(&e, &reserve, &samwise, &frodo);
+            assert_eq!(allowance.amount, 50_0000000);
+            assert_eq!(allowance.expiration_ledger, expiration_ledger);
+
+            spend_withdraw_allowance(&e, &reserve, &samwise, &frodo, 20_0000000);
+            let allowance = storage::get_withdraw_allowance(&e, &reserve, &samwise, &frodo);
+            assert_eq!(allowance.amount, 30_0000000);
+            assert_eq!(allowance.expiration_ledger, expiration_ledger);
+
+            // the full allowance can be spent
+            spend_withdraw_allowance(&e, &reserve, &samwise, &frodo, 30_0000000);
+            assert_eq!(
+                storage::get_withdraw_allowance(&e, &reserve, &samwise, &frodo).amount,
+                0
+            );
+        });
+
+        e.as_contract(&vault_address, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve_withdraw(
+                &e,
+                &reserve,
+                &samwise,
+                &frodo,
+                50_0000000,
+                expiration_ledger,
+            );
+        });
+
+        // -> verify the withdraw allowance expires
+        e.jump(101);
+        e.as_contract(&vault_address, || {
+            assert_eq!(
+                storage::get_withdraw_allowance(&e, &reserve, &samwise, &frodo).amount,
+                0
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #109)")]
+    fn test_approve_withdraw_past_max_ttl() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let expiration_ledger = e.ledger().max_live_until_ledger() + 1;
+            approve_withdraw(&e, &reserve, &samwise, &frodo, 1, expiration_ledger);
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Error(Contract, #9)")]
+    fn test_spend_withdraw_allowance_over_allowance() {
+        let e = Env::default();
+        e.mock_all_auths();
+        e.set_default_info();
+
+        let vault_address = register_fee_vault(&e, None);
+        let samwise = Address::generate(&e);
+        let frodo = Address::generate(&e);
+        let reserve = Address::generate(&e);
+
+        e.as_contract(&vault_address, || {
+            let expiration_ledger = e.ledger().sequence() + 100;
+            approve_withdraw(
+                &e,
+                &reserve,
+                &samwise,
+                &frodo,
+                50_0000000,
+                expiration_ledger,
+            );
+            spend_withdraw_allowance(&e, &reserve, &samwise, &frodo, 50_0000001);
+        });
+    }
+}
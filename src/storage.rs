@@ -1,5 +1,5 @@
 use soroban_sdk::{
-    contracttype, panic_with_error, unwrap::UnwrapOptimized, vec, Address, Env, Symbol, Vec,
+    contracttype, panic_with_error, unwrap::UnwrapOptimized, vec, Address, BytesN, Env, Symbol, Vec,
 };
 
 use crate::{errors::FeeVaultError, reserve_vault::ReserveVault};
@@ -10,6 +10,15 @@ const POOL_KEY: &str = "Pool";
 const ADMIN_KEY: &str = "Admin";
 const FEE_MODE_KEY: &str = "FeeModeKey";
 const RESERVES_KEY: &str = "Reserves";
+const BLND_KEY: &str = "BLND";
+const EMISSIONS_TAKE_KEY: &str = "EmisTake";
+const COMPOUND_KEY: &str = "Compound";
+const CONFIG_DELAY_KEY: &str = "CfgDelay";
+const PENDING_ADMIN_KEY: &str = "PendAdmin";
+const FEE_RECIPIENTS_KEY: &str = "FeeRecips";
+const PAUSE_KEY: &str = "Pause";
+const ALLOWLIST_KEY: &str = "Allowlist";
+const SHARE_TOKEN_WASM_KEY: &str = "ShareWasm";
 
 #[derive(Clone)]
 #[contracttype]
@@ -18,23 +27,183 @@ pub struct DepositKey {
     user: Address,    // the user who owns the deposit
 }
 
+#[derive(Clone)]
+#[contracttype]
+pub struct WithdrawAllowanceKey {
+    reserve: Address, // the reserve asset address
+    owner: Address,   // the user who owns the deposit
+    spender: Address, // the user allowed to withdraw the deposit
+}
+
 #[derive(Clone)]
 #[contracttype]
 pub enum FeeVaultDataKey {
     Deposit(DepositKey),
+    UserEmis(DepositKey),
+    WithdrawAllowance(WithdrawAllowanceKey),
     ResVault(Address),
+    ResEmis(Address),
+    Collateral(Address),
+    Compounding(Address),
+    ResFeeMode(Address),
+    QueuedConfig(ConfigKey),
+    Role(Role),
+    Pause(Address),
+    DepositCaps(Address),
+    Allowed(Address),
+    Frozen(DepositKey),
+    Deprecated(Address),
+    ShareToken(Address),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct ShareAllowance {
+    pub amount: i128,           // the number of shares the spender can spend
+    pub expiration_ledger: u32, // the ledger sequence the allowance expires at
+}
+
+#[derive(Clone, Default)]
+#[contracttype]
+pub struct ReserveEmissionData {
+    pub index: i128,            // the BLND emitted per share, with 12 decimals
+    pub admin_accrued: i128,    // the BLND the admin is due
+    pub compound_accrued: i128, // the BLND waiting to be compounded into the reserve
+}
+
+#[derive(Clone, Default)]
+#[contracttype]
+pub struct UserEmissionData {
+    pub index: i128,   // the reserve's emission index at the user's last checkpoint
+    pub accrued: i128, // the BLND the user is due
 }
 
 #[derive(Clone)]
 #[contracttype]
-pub struct FeeMode {
-    pub is_apr_capped: bool, // whether the vault APR is capped
-    pub value: i128,         // the apr_cap value if is_apr_capped, otherwise the admin's take_rate
+pub struct CompoundConfig {
+    pub router: Address, // the swap router used to swap BLND for reserve assets
+    pub keeper: Address, // the address allowed to compound emissions
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct PendingAdmin {
+    pub admin: Address,         // the proposed admin
+    pub expiration_ledger: u32, // the ledger sequence the proposal expires at
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeRecipient {
+    pub address: Address, // the address paid a share of distributed fees
+    pub weight: u32,      // the recipient's share of distributed fees, in basis points
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct FeeRecipients {
+    pub recipients: Vec<FeeRecipient>, // the recipients of distributed fees
+    pub dust_recipient: Address,       // the address paid any rounding dust
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct DepositCaps {
+    pub reserve_cap: Option<i128>, // the most underlying the reserve vault's depositors can hold
+    pub user_cap: Option<i128>,    // the most underlying a single depositor can hold
+}
+
+/// How far a reserve vault, or the whole vault, is paused. Each state blocks everything the states
+/// before it block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[contracttype]
+#[repr(u32)]
+pub enum PauseState {
+    /// Nothing is paused
+    Active = 0,
+    /// Deposits are blocked
+    DepositsBlocked = 1,
+    /// Only withdrawals are allowed, so fee claims are blocked as well
+    WithdrawOnly = 2,
+    /// Deposits, withdrawals and fee claims are blocked
+    Paused = 3,
+}
+
+/// A role that can perform a set of privileged actions. The admin holds every role that has not
+/// been granted to another address.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Role {
+    /// Sets the fee modes and the admin's take rate on emissions
+    FeeManager,
+    /// Adds, deprecates and removes reserve vaults, sets deposit caps and manages emissions
+    /// compounding
+    ReserveManager,
+    /// Claims fees and emissions, converts fees to shares and sets the fee recipients
+    Treasurer,
+    /// Pauses the vault in an emergency
+    Guardian,
+    /// Freezes positions and forces frozen positions out of the vault
+    Compliance,
+}
+
+/// A change to a sensitive vault config
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ConfigChange {
+    /// Set the global fee mode: `FeeMode(fee_mode)`
+    FeeMode(FeeMode),
+    /// Set a reserve vault's own fee mode: `ReserveFeeMode(reserve, fee_mode)`
+    ReserveFeeMode(Address, FeeMode),
+    /// Clear a reserve vault's own fee mode so it uses the global fee mode: `ClearReserveFeeMode(reserve)`
+    ClearReserveFeeMode(Address),
+    /// Set the admin's take rate on emissions: `EmissionsTakeRate(take_rate)`
+    EmissionsTakeRate(i128),
+    /// Set the swap router and keeper used to compound emissions: `CompoundConfig(router, keeper)`
+    CompoundConfig(Address, Address),
+    /// Set the delay queued config changes wait before they can be applied: `ConfigDelay(delay)`
+    ConfigDelay(u64),
+    /// Set the recipients fees are distributed to: `FeeRecipients(fee_recipients)`
+    FeeRecipients(FeeRecipients),
+}
+
+/// Identifies the config a change applies to. Only one change can be queued per config.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum ConfigKey {
+    FeeMode,
+    ReserveFeeMode(Address),
+    EmissionsTakeRate,
+    CompoundConfig,
+    ConfigDelay,
+    FeeRecipients,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct QueuedConfigChange {
+    pub change: ConfigChange, // the queued config change
+    pub unlock_time: u64,     // the timestamp the change can be applied at
+}
+
+/// How the admin takes fees from the interest earned by a reserve vault. All values are expressed
+/// with 7 decimals.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum FeeMode {
+    /// The admin takes a percentage of all interest: `TakeRate(take_rate)`
+    TakeRate(i128),
+    /// The admin takes all interest above a target APR: `AprCap(apr_cap)`
+    AprCap(i128),
+    /// The admin takes all interest above a target APR, and a percentage of the interest up to
+    /// it: `Hybrid(take_rate, apr_cap)`
+    Hybrid(i128, i128),
 }
 
 //********** Storage Utils **********//
 
 pub const ONE_DAY_LEDGERS: u32 = 17280; // assumes 5 seconds per ledger on average
+pub const ADMIN_PROPOSAL_LEDGERS: u32 = 7 * ONE_DAY_LEDGERS; // how long a proposed admin has to accept
 
 const LEDGER_BUMP_SHARED: u32 = 31 * ONE_DAY_LEDGERS;
 const LEDGER_THRESHOLD_SHARED: u32 = LEDGER_BUMP_SHARED - ONE_DAY_LEDGERS;
@@ -81,7 +250,28 @@ pub fn set_admin(e: &Env, admin: Address) {
         .set::<Symbol, Address>(&Symbol::new(e, ADMIN_KEY), &admin);
 }
 
-/// Get the fee mode for the fee vault
+/// Get the pending admin proposal, if one exists
+pub fn get_pending_admin(e: &Env) -> Option<PendingAdmin> {
+    e.storage()
+        .instance()
+        .get::<Symbol, PendingAdmin>(&Symbol::new(e, PENDING_ADMIN_KEY))
+}
+
+/// Set the pending admin proposal
+pub fn set_pending_admin(e: &Env, pending: &PendingAdmin) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PendingAdmin>(&Symbol::new(e, PENDING_ADMIN_KEY), pending);
+}
+
+/// Remove the pending admin proposal
+pub fn del_pending_admin(e: &Env) {
+    e.storage()
+        .instance()
+        .remove(&Symbol::new(e, PENDING_ADMIN_KEY));
+}
+
+/// Get the global fee mode for the fee vault
 pub fn get_fee_mode(e: &Env) -> FeeMode {
     e.storage()
         .instance()
@@ -89,13 +279,162 @@ pub fn get_fee_mode(e: &Env) -> FeeMode {
         .unwrap_optimized()
 }
 
-/// Set the fee mode for the fee vault
+/// Set the global fee mode for the fee vault
 pub fn set_fee_mode(e: &Env, mode: FeeMode) {
     e.storage()
         .instance()
         .set::<Symbol, FeeMode>(&Symbol::new(e, FEE_MODE_KEY), &mode);
 }
 
+/// Get the BLND token address
+pub fn get_blnd(e: &Env) -> Address {
+    e.storage()
+        .instance()
+        .get::<Symbol, Address>(&Symbol::new(e, BLND_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the BLND token address
+pub fn set_blnd(e: &Env, blnd: Address) {
+    e.storage()
+        .instance()
+        .set::<Symbol, Address>(&Symbol::new(e, BLND_KEY), &blnd);
+}
+
+/// Get the wasm hash of the share token deployed for each reserve vault
+pub fn get_share_token_wasm_hash(e: &Env) -> BytesN<32> {
+    e.storage()
+        .instance()
+        .get::<Symbol, BytesN<32>>(&Symbol::new(e, SHARE_TOKEN_WASM_KEY))
+        .unwrap_optimized()
+}
+
+/// Set the wasm hash of the share token deployed for each reserve vault
+pub fn set_share_token_wasm_hash(e: &Env, wasm_hash: BytesN<32>) {
+    e.storage()
+        .instance()
+        .set::<Symbol, BytesN<32>>(&Symbol::new(e, SHARE_TOKEN_WASM_KEY), &wasm_hash);
+}
+
+/// Get the admin's take rate on emissions. Defaults to 0.
+pub fn get_emissions_take_rate(e: &Env) -> i128 {
+    e.storage()
+        .instance()
+        .get::<Symbol, i128>(&Symbol::new(e, EMISSIONS_TAKE_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the admin's take rate on emissions
+pub fn set_emissions_take_rate(e: &Env, take_rate: i128) {
+    e.storage()
+        .instance()
+        .set::<Symbol, i128>(&Symbol::new(e, EMISSIONS_TAKE_KEY), &take_rate);
+}
+
+/// Get the emissions compounding config, if one is set
+pub fn get_compound_config(e: &Env) -> Option<CompoundConfig> {
+    e.storage()
+        .instance()
+        .get::<Symbol, CompoundConfig>(&Symbol::new(e, COMPOUND_KEY))
+}
+
+/// Set the emissions compounding config
+pub fn set_compound_config(e: &Env, config: &CompoundConfig) {
+    e.storage()
+        .instance()
+        .set::<Symbol, CompoundConfig>(&Symbol::new(e, COMPOUND_KEY), config);
+}
+
+/// Get the delay, in seconds, queued config changes wait before they can be applied. Defaults to 0.
+pub fn get_config_delay(e: &Env) -> u64 {
+    e.storage()
+        .instance()
+        .get::<Symbol, u64>(&Symbol::new(e, CONFIG_DELAY_KEY))
+        .unwrap_or(0)
+}
+
+/// Set the delay, in seconds, queued config changes wait before they can be applied
+pub fn set_config_delay(e: &Env, delay: u64) {
+    e.storage()
+        .instance()
+        .set::<Symbol, u64>(&Symbol::new(e, CONFIG_DELAY_KEY), &delay);
+}
+
+/// Get the recipients fees are distributed to, if set
+pub fn get_fee_recipients(e: &Env) -> Option<FeeRecipients> {
+    e.storage()
+        .instance()
+        .get::<Symbol, FeeRecipients>(&Symbol::new(e, FEE_RECIPIENTS_KEY))
+}
+
+/// Set the recipients fees are distributed to
+pub fn set_fee_recipients(e: &Env, fee_recipients: &FeeRecipients) {
+    e.storage()
+        .instance()
+        .set::<Symbol, FeeRecipients>(&Symbol::new(e, FEE_RECIPIENTS_KEY), fee_recipients);
+}
+
+/// Get the pause state of the whole vault. Defaults to `Active`.
+pub fn get_pause_state(e: &Env) -> PauseState {
+    e.storage()
+        .instance()
+        .get::<Symbol, PauseState>(&Symbol::new(e, PAUSE_KEY))
+        .unwrap_or(PauseState::Active)
+}
+
+/// Set the pause state of the whole vault
+pub fn set_pause_state(e: &Env, state: PauseState) {
+    e.storage()
+        .instance()
+        .set::<Symbol, PauseState>(&Symbol::new(e, PAUSE_KEY), &state);
+}
+
+/// Get whether deposits are restricted to allowlisted addresses
+pub fn get_allowlist_enabled(e: &Env) -> bool {
+    e.storage()
+        .instance()
+        .get::<Symbol, bool>(&Symbol::new(e, ALLOWLIST_KEY))
+        .unwrap_or(false)
+}
+
+/// Set whether deposits are restricted to allowlisted addresses
+pub fn set_allowlist_enabled(e: &Env, enabled: bool) {
+    e.storage()
+        .instance()
+        .set::<Symbol, bool>(&Symbol::new(e, ALLOWLIST_KEY), &enabled);
+}
+
+/// Get the address a role has been granted to, if any
+///
+/// ### Arguments
+/// * `role` - The role
+pub fn get_role(e: &Env, role: &Role) -> Option<Address> {
+    e.storage()
+        .instance()
+        .get::<FeeVaultDataKey, Address>(&FeeVaultDataKey::Role(role.clone()))
+}
+
+/// Grant a role to an address
+///
+/// ### Arguments
+/// * `role` - The role
+/// * `account` - The address to grant the role to
+pub fn set_role(e: &Env, role: &Role, account: &Address) {
+    e.storage()
+        .instance()
+        .set::<FeeVaultDataKey, Address>(&FeeVaultDataKey::Role(role.clone()), account);
+}
+
+/// Remove a role grant, returning the role to the admin
+///
+/// ### Arguments
+/// * `role` - The role
+pub fn del_role(e: &Env, role: &Role) {
+    e.storage()
+        .instance()
+        .remove(&FeeVaultDataKey::Role(role.clone()));
+}
+
 /********** Persistent **********/
 
 /// Set a reserve's vault data
@@ -134,6 +473,26 @@ pub fn get_reserve_vault(e: &Env, reserve: &Address) -> ReserveVault {
     }
 }
 
+/// Remove a reserve's vault data, config and emission data. The reserve's share token is kept, so
+/// it is reused if the reserve is added back.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn del_reserve_vault(e: &Env, reserve: &Address) {
+    for key in [
+        FeeVaultDataKey::ResVault(reserve.clone()),
+        FeeVaultDataKey::Collateral(reserve.clone()),
+        FeeVaultDataKey::Compounding(reserve.clone()),
+        FeeVaultDataKey::ResFeeMode(reserve.clone()),
+        FeeVaultDataKey::Pause(reserve.clone()),
+        FeeVaultDataKey::DepositCaps(reserve.clone()),
+        FeeVaultDataKey::Deprecated(reserve.clone()),
+        FeeVaultDataKey::ResEmis(reserve.clone()),
+    ] {
+        e.storage().persistent().remove(&key);
+    }
+}
+
 /// Check if a reserve has a vault
 ///
 /// ### Arguments
@@ -143,6 +502,376 @@ pub fn has_reserve_vault(e: &Env, reserve: &Address) -> bool {
     e.storage().persistent().has(&key)
 }
 
+/// Set whether a reserve vault supplies to the pool as collateral
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `is_collateral` - Whether the reserve vault supplies as collateral
+pub fn set_reserve_is_collateral(e: &Env, reserve: &Address, is_collateral: bool) {
+    let key = FeeVaultDataKey::Collateral(reserve.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, bool>(&key, &is_collateral);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Check if a reserve vault supplies to the pool as collateral. Defaults to false.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_reserve_is_collateral(e: &Env, reserve: &Address) -> bool {
+    let key = FeeVaultDataKey::Collateral(reserve.clone());
+    let result = e.storage().persistent().get::<FeeVaultDataKey, bool>(&key);
+    match result {
+        Some(is_collateral) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            is_collateral
+        }
+        None => false,
+    }
+}
+
+/// Get a queued config change, if one exists
+///
+/// ### Arguments
+/// * `key` - The config the change applies to
+pub fn get_queued_config(e: &Env, key: &ConfigKey) -> Option<QueuedConfigChange> {
+    let key = FeeVaultDataKey::QueuedConfig(key.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, QueuedConfigChange>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set a queued config change
+///
+/// ### Arguments
+/// * `key` - The config the change applies to
+/// * `queued` - The queued config change
+pub fn set_queued_config(e: &Env, key: &ConfigKey, queued: &QueuedConfigChange) {
+    let key = FeeVaultDataKey::QueuedConfig(key.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, QueuedConfigChange>(&key, queued);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Remove a queued config change
+///
+/// ### Arguments
+/// * `key` - The config the change applies to
+pub fn del_queued_config(e: &Env, key: &ConfigKey) {
+    let key = FeeVaultDataKey::QueuedConfig(key.clone());
+    e.storage().persistent().remove(&key);
+}
+
+/// Set a reserve vault's own fee mode. Removing it makes the reserve vault use the global fee mode.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `fee_mode` - The reserve vault's fee mode, or None to use the global fee mode
+pub fn set_reserve_fee_mode(e: &Env, reserve: &Address, fee_mode: &Option<FeeMode>) {
+    let key = FeeVaultDataKey::ResFeeMode(reserve.clone());
+    match fee_mode {
+        Some(fee_mode) => {
+            e.storage()
+                .persistent()
+                .set::<FeeVaultDataKey, FeeMode>(&key, fee_mode);
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+        }
+        None => e.storage().persistent().remove(&key),
+    }
+}
+
+/// Get a reserve vault's own fee mode, if it has one
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_reserve_fee_mode(e: &Env, reserve: &Address) -> Option<FeeMode> {
+    let key = FeeVaultDataKey::ResFeeMode(reserve.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, FeeMode>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set whether a reserve vault compounds its emissions into the reserve
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `is_compounding` - Whether the reserve vault compounds its emissions
+pub fn set_reserve_is_compounding(e: &Env, reserve: &Address, is_compounding: bool) {
+    let key = FeeVaultDataKey::Compounding(reserve.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, bool>(&key, &is_compounding);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Check if a reserve vault compounds its emissions into the reserve. Defaults to false.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_reserve_is_compounding(e: &Env, reserve: &Address) -> bool {
+    let key = FeeVaultDataKey::Compounding(reserve.clone());
+    let result = e.storage().persistent().get::<FeeVaultDataKey, bool>(&key);
+    match result {
+        Some(is_compounding) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            is_compounding
+        }
+        None => false,
+    }
+}
+
+/// Set whether a reserve vault is deprecated
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `is_deprecated` - Whether the reserve vault is deprecated
+pub fn set_reserve_is_deprecated(e: &Env, reserve: &Address, is_deprecated: bool) {
+    let key = FeeVaultDataKey::Deprecated(reserve.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, bool>(&key, &is_deprecated);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Get the address of a reserve's share token, if one has been deployed
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_share_token(e: &Env, reserve: &Address) -> Option<Address> {
+    let key = FeeVaultDataKey::ShareToken(reserve.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, Address>(&key);
+    if result.is_some() {
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+    result
+}
+
+/// Set the address of a reserve's share token
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `share_token` - The address of the share token
+pub fn set_share_token(e: &Env, reserve: &Address, share_token: &Address) {
+    let key = FeeVaultDataKey::ShareToken(reserve.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, Address>(&key, share_token);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Check if a reserve vault is deprecated. Defaults to false.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_reserve_is_deprecated(e: &Env, reserve: &Address) -> bool {
+    let key = FeeVaultDataKey::Deprecated(reserve.clone());
+    let result = e.storage().persistent().get::<FeeVaultDataKey, bool>(&key);
+    match result {
+        Some(is_deprecated) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            is_deprecated
+        }
+        None => false,
+    }
+}
+
+/// Set the pause state of a reserve vault. Setting it to `Active` removes it.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `state` - The reserve vault's pause state
+pub fn set_reserve_pause_state(e: &Env, reserve: &Address, state: PauseState) {
+    let key = FeeVaultDataKey::Pause(reserve.clone());
+    if state == PauseState::Active {
+        e.storage().persistent().remove(&key);
+    } else {
+        e.storage()
+            .persistent()
+            .set::<FeeVaultDataKey, PauseState>(&key, &state);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    }
+}
+
+/// Get the pause state of a reserve vault. Defaults to `Active`.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_reserve_pause_state(e: &Env, reserve: &Address) -> PauseState {
+    let key = FeeVaultDataKey::Pause(reserve.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, PauseState>(&key);
+    match result {
+        Some(state) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            state
+        }
+        None => PauseState::Active,
+    }
+}
+
+/// Set the deposit caps of a reserve vault
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `caps` - The reserve vault's deposit caps
+pub fn set_deposit_caps(e: &Env, reserve: &Address, caps: &DepositCaps) {
+    let key = FeeVaultDataKey::DepositCaps(reserve.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, DepositCaps>(&key, caps);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Get the deposit caps of a reserve vault. Defaults to no caps.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_deposit_caps(e: &Env, reserve: &Address) -> DepositCaps {
+    let key = FeeVaultDataKey::DepositCaps(reserve.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, DepositCaps>(&key);
+    match result {
+        Some(caps) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            caps
+        }
+        None => DepositCaps::default(),
+    }
+}
+
+/// Get whether a user is on the deposit allowlist
+///
+/// ### Arguments
+/// * `user` - The address of the user
+pub fn is_allowlisted(e: &Env, user: &Address) -> bool {
+    let key = FeeVaultDataKey::Allowed(user.clone());
+    let result = e.storage().persistent().get::<FeeVaultDataKey, bool>(&key);
+    match result {
+        Some(allowed) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            allowed
+        }
+        None => false,
+    }
+}
+
+/// Set whether a user is on the deposit allowlist. Removing a user deletes their entry.
+///
+/// ### Arguments
+/// * `user` - The address of the user
+/// * `allowed` - Whether the user is allowlisted
+pub fn set_allowlisted(e: &Env, user: &Address, allowed: bool) {
+    let key = FeeVaultDataKey::Allowed(user.clone());
+    if allowed {
+        e.storage()
+            .persistent()
+            .set::<FeeVaultDataKey, bool>(&key, &allowed);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
+/// Get whether a user's position in a reserve vault is frozen
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `user` - The address of the user
+pub fn is_frozen(e: &Env, reserve: &Address, user: &Address) -> bool {
+    let key = FeeVaultDataKey::Frozen(DepositKey {
+        reserve: reserve.clone(),
+        user: user.clone(),
+    });
+    let result = e.storage().persistent().get::<FeeVaultDataKey, bool>(&key);
+    match result {
+        Some(frozen) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            frozen
+        }
+        None => false,
+    }
+}
+
+/// Set whether a user's position in a reserve vault is frozen. Unfreezing deletes the entry.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `user` - The address of the user
+/// * `frozen` - Whether the position is frozen
+pub fn set_frozen(e: &Env, reserve: &Address, user: &Address, frozen: bool) {
+    let key = FeeVaultDataKey::Frozen(DepositKey {
+        reserve: reserve.clone(),
+        user: user.clone(),
+    });
+    if frozen {
+        e.storage()
+            .persistent()
+            .set::<FeeVaultDataKey, bool>(&key, &frozen);
+        e.storage()
+            .persistent()
+            .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+    } else {
+        e.storage().persistent().remove(&key);
+    }
+}
+
 /// Get the number of vault shares a user owns. Shares are stored with 7 decimal places of precision.
 ///
 /// ### Arguments
@@ -184,6 +913,112 @@ pub fn set_reserve_vault_shares(e: &Env, reserve: &Address, user: &Address, shar
         .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
+/// Get a reserve vault's emission data. Defaults to an empty index.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn get_reserve_emissions(e: &Env, reserve: &Address) -> ReserveEmissionData {
+    let key = FeeVaultDataKey::ResEmis(reserve.clone());
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, ReserveEmissionData>(&key);
+    match result {
+        Some(data) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            data
+        }
+        None => ReserveEmissionData::default(),
+    }
+}
+
+/// Set a reserve vault's emission data
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `data` - The reserve vault's emission data
+pub fn set_reserve_emissions(e: &Env, reserve: &Address, data: &ReserveEmissionData) {
+    let key = FeeVaultDataKey::ResEmis(reserve.clone());
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, ReserveEmissionData>(&key, data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Get a user's emission data for a reserve vault. Defaults to an empty checkpoint.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `user` - The address of the user
+pub fn get_user_emissions(e: &Env, reserve: &Address, user: &Address) -> UserEmissionData {
+    let key = FeeVaultDataKey::UserEmis(DepositKey {
+        reserve: reserve.clone(),
+        user: user.clone(),
+    });
+    let result = e
+        .storage()
+        .persistent()
+        .get::<FeeVaultDataKey, UserEmissionData>(&key);
+    match result {
+        Some(data) => {
+            e.storage()
+                .persistent()
+                .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+            data
+        }
+        None => UserEmissionData::default(),
+    }
+}
+
+/// Set a user's emission data for a reserve vault
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `user` - The address of the user
+/// * `data` - The user's emission data
+pub fn set_user_emissions(e: &Env, reserve: &Address, user: &Address, data: &UserEmissionData) {
+    let key = FeeVaultDataKey::UserEmis(DepositKey {
+        reserve: reserve.clone(),
+        user: user.clone(),
+    });
+    e.storage()
+        .persistent()
+        .set::<FeeVaultDataKey, UserEmissionData>(&key, data);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
+/// Check if a user has emission data for a reserve vault
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `user` - The address of the user
+pub fn has_user_emissions(e: &Env, reserve: &Address, user: &Address) -> bool {
+    let key = FeeVaultDataKey::UserEmis(DepositKey {
+        reserve: reserve.clone(),
+        user: user.clone(),
+    });
+    e.storage().persistent().has(&key)
+}
+
+/// Remove a user's emission data for a reserve vault
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `user` - The address of the user
+pub fn del_user_emissions(e: &Env, reserve: &Address, user: &Address) {
+    let key = FeeVaultDataKey::UserEmis(DepositKey {
+        reserve: reserve.clone(),
+        user: user.clone(),
+    });
+    e.storage().persistent().remove(&key);
+}
+
 /// Add a reserve to the list of supported reserves
 ///
 /// ### Arguments
@@ -202,6 +1037,26 @@ pub fn add_reserve_to_reserves(e: &Env, reserve: Address) {
         .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
 }
 
+/// Remove a reserve from the list of supported reserves
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+pub fn remove_reserve_from_reserves(e: &Env, reserve: &Address) {
+    let key = Symbol::new(e, RESERVES_KEY);
+
+    let mut reserves = get_reserves(e);
+    if let Some(index) = reserves.first_index_of(reserve) {
+        reserves.remove(index);
+    }
+
+    e.storage()
+        .persistent()
+        .set::<Symbol, Vec<Address>>(&key, &reserves);
+    e.storage()
+        .persistent()
+        .extend_ttl(&key, LEDGER_THRESHOLD_USER, LEDGER_BUMP_USER);
+}
+
 /// Get all the supported reserves
 ///
 /// Note: Since Blend-v2 supports up to 30 assets,
@@ -219,3 +1074,67 @@ pub fn get_reserves(e: &Env) -> Vec<Address> {
         None => vec![e],
     }
 }
+
+/********** Temporary **********/
+
+/// Get the number of shares a spender is allowed to withdraw on behalf of a user. Returns an
+/// allowance of 0 if none exists or if it has expired.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `owner` - The address of the user who owns the shares
+/// * `spender` - The address of the spender
+pub fn get_withdraw_allowance(
+    e: &Env,
+    reserve: &Address,
+    owner: &Address,
+    spender: &Address,
+) -> ShareAllowance {
+    let key = FeeVaultDataKey::WithdrawAllowance(WithdrawAllowanceKey {
+        reserve: reserve.clone(),
+        owner: owner.clone(),
+        spender: spender.clone(),
+    });
+    let result = e
+        .storage()
+        .temporary()
+        .get::<FeeVaultDataKey, ShareAllowance>(&key);
+    match result {
+        Some(allowance) if allowance.expiration_ledger >= e.ledger().sequence() => allowance,
+        _ => ShareAllowance {
+            amount: 0,
+            expiration_ledger: 0,
+        },
+    }
+}
+
+/// Set the number of shares a spender is allowed to withdraw on behalf of a user. The entry
+/// lives until the allowance expires.
+///
+/// ### Arguments
+/// * `reserve` - The address of the reserve asset
+/// * `owner` - The address of the user who owns the shares
+/// * `spender` - The address of the spender
+/// * `allowance` - The allowance
+pub fn set_withdraw_allowance(
+    e: &Env,
+    reserve: &Address,
+    owner: &Address,
+    spender: &Address,
+    allowance: &ShareAllowance,
+) {
+    let key = FeeVaultDataKey::WithdrawAllowance(WithdrawAllowanceKey {
+        reserve: reserve.clone(),
+        owner: owner.clone(),
+        spender: spender.clone(),
+    });
+    if allowance.amount > 0 {
+        e.storage()
+            .temporary()
+            .set::<FeeVaultDataKey, ShareAllowance>(&key, allowance);
+        let live_for = allowance.expiration_ledger - e.ledger().sequence();
+        e.storage().temporary().extend_ttl(&key, live_for, live_for);
+    } else {
+        e.storage().temporary().remove(&key);
+    }
+}
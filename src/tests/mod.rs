@@ -1,4 +1,21 @@
+mod test_allowlist;
+mod test_batch;
+mod test_claim_fees;
+mod test_collateral;
+mod test_compound;
 mod test_default;
+mod test_deposit_caps;
+mod test_emissions;
 mod test_entrypoints;
 mod test_fee_accrual;
+mod test_fee_split;
+mod test_freeze;
 mod test_happy_path;
+mod test_limits;
+mod test_mint_redeem;
+mod test_pause;
+mod test_previews;
+mod test_remove_reserve;
+mod test_roles;
+mod test_third_party;
+mod test_timelock;
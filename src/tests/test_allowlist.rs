@@ -0,0 +1,184 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::share_token_wasm::Client as ShareTokenClient;
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_allowlist() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    let share_token_client = ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&usdc));
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    usdc_client.mint(&frodo, &10_000_0000000);
+    usdc_client.mint(&samwise, &10_000_0000000);
+
+    // samwise deposits before the allowlist is enabled
+    assert!(!fee_vault_client.is_allowlist_enabled());
+    fee_vault_client.deposit(&usdc, &samwise, &1_000_0000000);
+
+    /*
+     * Enable the allowlist
+     */
+    fee_vault_client.set_allowlist_enabled(&true);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![&e, Symbol::new(&e, "allowlist_enabled_update").into_val(&e)]
+    );
+    let data: bool = event.2.into_val(&e);
+    assert!(data);
+    assert!(fee_vault_client.is_allowlist_enabled());
+
+    fee_vault_client.add_to_allowlist(&vec![&e, frodo.clone(), merry.clone()]);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "allowlist_update").into_val(&e),
+            true.into_val(&e)
+        ]
+    );
+    let data: Vec<Address> = event.2.into_val(&e);
+    assert_eq!(data, vec![&e, frodo.clone(), merry.clone()]);
+    assert!(fee_vault_client.is_allowlisted(&frodo));
+    assert!(!fee_vault_client.is_allowlisted(&samwise));
+
+    // -> verify only allowlisted addresses can receive shares
+    fee_vault_client.deposit(&usdc, &frodo, &1_000_0000000);
+    fee_vault_client.mint(&usdc, &frodo, &1_0000000);
+    fee_vault_client.deposit_for(&usdc, &samwise, &merry, &1_0000000);
+    share_token_client.transfer(&frodo, &merry, &1_0000000);
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &samwise, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+    assert_eq!(
+        fee_vault_client.try_mint(&usdc, &samwise, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_many(&samwise, &vec![&e, (usdc.clone(), 1_0000000)])
+            .err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_for(&usdc, &frodo, &samwise, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+    assert_eq!(
+        share_token_client
+            .try_transfer(&frodo, &samwise, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+
+    // -> verify fees can only be converted into shares for allowlisted addresses
+    e.jump(ONE_DAY_LEDGERS);
+    assert_eq!(
+        fee_vault_client
+            .try_convert_fees_to_shares(&usdc, &samwise)
+            .err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+    fee_vault_client.convert_fees_to_shares(&usdc, &merry);
+
+    // -> verify existing holders can still withdraw
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.withdraw(&usdc, &samwise, &100_0000000);
+    share_token_client.transfer(&samwise, &frodo, &1_0000000);
+
+    /*
+     * Remove from the allowlist
+     */
+    fee_vault_client.remove_from_allowlist(&vec![&e, frodo.clone()]);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "allowlist_update").into_val(&e),
+            false.into_val(&e)
+        ]
+    );
+    assert!(!fee_vault_client.is_allowlisted(&frodo));
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(130)))
+    );
+    fee_vault_client.withdraw_all(&usdc, &frodo);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &frodo), 0);
+
+    /*
+     * Disable the allowlist
+     */
+    fee_vault_client.set_allowlist_enabled(&false);
+    assert_eq!(e.auths()[0].0, gandalf);
+    fee_vault_client.deposit(&usdc, &frodo, &1_0000000);
+    fee_vault_client.deposit(&usdc, &samwise, &1_0000000);
+}
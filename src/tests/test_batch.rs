@@ -0,0 +1,217 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_deposit_and_withdraw_many() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens of each asset and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: xlm.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+        Request {
+            address: xlm.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &true, &None);
+
+    // -> verify batches are validated
+    assert_eq!(
+        fee_vault_client.try_deposit_many(&frodo, &vec![&e]).err(),
+        Some(Ok(Error::from_contract_error(102)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_many(
+                &frodo,
+                &vec![&e, (usdc.clone(), 1_0000000), (usdc.clone(), 1_0000000)]
+            )
+            .err(),
+        Some(Ok(Error::from_contract_error(113)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_many(
+                &frodo,
+                &vec![&e, (usdc.clone(), 1_0000000), (xlm.clone(), 0)]
+            )
+            .err(),
+        Some(Ok(Error::from_contract_error(102)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_many(&frodo, &vec![&e, (blnd.clone(), 1_0000000)])
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    /*
+     * Deposit into several reserves in one call
+     * -> frodo deposits 100 usdc and 200 xlm
+     */
+    let usdc_amount = 100_0000000;
+    let xlm_amount = 200_0000000;
+    usdc_client.mint(&frodo, &usdc_amount);
+    xlm_client.mint(&frodo, &xlm_amount);
+    let deposits = vec![&e, (usdc.clone(), usdc_amount), (xlm.clone(), xlm_amount)];
+    let shares = fee_vault_client.deposit_many(&frodo, &deposits);
+    // -> verify a single submit supplies both reserves, using each vault's request type
+    let deposit_requests = vec![
+        &e,
+        Request {
+            request_type: 0,
+            address: usdc.clone(),
+            amount: usdc_amount,
+        },
+        Request {
+            request_type: 2,
+            address: xlm.clone(),
+            amount: xlm_amount,
+        },
+    ];
+    assert_eq!(
+        e.auths()[0],
+        (
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "deposit_many"),
+                    vec![&e, frodo.to_val(), deposits.to_val()]
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        pool.clone(),
+                        Symbol::new(&e, "submit"),
+                        vec![
+                            &e,
+                            fee_vault.to_val(),
+                            frodo.to_val(),
+                            frodo.to_val(),
+                            deposit_requests.to_val(),
+                        ]
+                    )),
+                    sub_invocations: std::vec![
+                        // the pool transfers the tokens in its own reserve order
+                        AuthorizedInvocation {
+                            function: AuthorizedFunction::Contract((
+                                xlm.clone(),
+                                Symbol::new(&e, "transfer"),
+                                vec![&e, frodo.to_val(), pool.to_val(), xlm_amount.into_val(&e)]
+                            )),
+                            sub_invocations: std::vec![]
+                        },
+                        AuthorizedInvocation {
+                            function: AuthorizedFunction::Contract((
+                                usdc.clone(),
+                                Symbol::new(&e, "transfer"),
+                                vec![&e, frodo.to_val(), pool.to_val(), usdc_amount.into_val(&e)]
+                            )),
+                            sub_invocations: std::vec![]
+                        }
+                    ]
+                }]
+            }
+        )
+    );
+    assert_eq!(shares.len(), 2);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &frodo),
+        shares.get(0).unwrap_optimized()
+    );
+    assert_eq!(
+        fee_vault_client.get_shares(&xlm, &frodo),
+        shares.get(1).unwrap_optimized()
+    );
+    assert_eq!(usdc_client.balance(&frodo), 0);
+    assert_eq!(xlm_client.balance(&frodo), 0);
+
+    let vault_positions = pool_client.get_positions(&fee_vault);
+    assert_eq!(vault_positions.supply.len(), 1);
+    assert_eq!(vault_positions.collateral.len(), 1);
+
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    /*
+     * Withdraw from several reserves in one call
+     * -> frodo withdraws 50 usdc and their full xlm position
+     */
+    let usdc_withdraw = 50_0000000;
+    let xlm_withdraw = fee_vault_client.get_underlying_tokens(&xlm, &frodo);
+    let usdc_preview = fee_vault_client.preview_withdraw(&usdc, &usdc_withdraw);
+    let frodo_usdc_shares = fee_vault_client.get_shares(&usdc, &frodo);
+    let burnt = fee_vault_client.withdraw_many(
+        &frodo,
+        &vec![
+            &e,
+            (usdc.clone(), usdc_withdraw),
+            (xlm.clone(), xlm_withdraw),
+        ],
+    );
+    assert_eq!(burnt.get(0).unwrap_optimized(), usdc_preview);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &frodo),
+        frodo_usdc_shares - usdc_preview
+    );
+    assert_eq!(fee_vault_client.get_shares(&xlm, &frodo), 0);
+    assert_eq!(usdc_client.balance(&frodo), usdc_withdraw);
+    assert_eq!(xlm_client.balance(&frodo), xlm_withdraw);
+    assert!(xlm_withdraw > xlm_amount);
+}
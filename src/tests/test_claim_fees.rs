@@ -0,0 +1,471 @@
+#![cfg(test)]
+
+use crate::constants::{SCALAR_12, SCALAR_7};
+use crate::pool;
+use crate::storage::{FeeMode, PauseState, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, Error, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_claim_fees_amount_and_all() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let treasury = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens of each reserve for a 50% util rate
+    let mut requests = Vec::new(&e);
+    for reserve in [usdc.clone(), xlm.clone()] {
+        requests.push_back(Request {
+            address: reserve.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        });
+        requests.push_back(Request {
+            address: reserve,
+            amount: 100_000_0000000,
+            request_type: 4,
+        });
+    }
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // -> verify nothing is claimed before fees accrue
+    assert_eq!(
+        fee_vault_client.claim_all_fees(&treasury),
+        Vec::<(Address, i128)>::new(&e)
+    );
+
+    // frodo deposits 10k tokens into each vault and fees accrue for a week
+    usdc_client.mint(&frodo, &10_000_0000000);
+    xlm_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &10_000_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &10_000_0000000);
+    e.jump(7 * ONE_DAY_LEDGERS);
+
+    /*
+     * Claim part of the USDC fees
+     */
+    let usdc_fees = fee_vault_client.get_collected_fees(&usdc);
+    let xlm_fees = fee_vault_client.get_collected_fees(&xlm);
+    assert!(usdc_fees > 0 && xlm_fees > 0);
+
+    // -> verify the amount is validated
+    assert_eq!(
+        fee_vault_client
+            .try_claim_fees_amount(&usdc, &treasury, &0)
+            .err(),
+        Some(Ok(Error::from_contract_error(102)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_claim_fees_amount(&usdc, &treasury, &(usdc_fees + 1))
+            .err(),
+        Some(Ok(Error::from_contract_error(103)))
+    );
+
+    let partial_amount = usdc_fees / 2;
+    let accrued_b_tokens = fee_vault_client.get_reserve_vault(&usdc).accrued_fees;
+    let b_tokens_burnt = fee_vault_client.claim_fees_amount(&usdc, &treasury, &partial_amount);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "vault_fee_claim").into_val(&e),
+            usdc.into_val(&e),
+            gandalf.into_val(&e),
+            treasury.into_val(&e)
+        ]
+    );
+    let data: (i128, i128) = event.2.into_val(&e);
+    assert_eq!(data, (partial_amount, b_tokens_burnt));
+    assert_eq!(usdc_client.balance(&treasury), partial_amount);
+    assert_eq!(
+        fee_vault_client.get_reserve_vault(&usdc).accrued_fees,
+        accrued_b_tokens - b_tokens_burnt
+    );
+
+    /*
+     * Claim the rest of the fees for all reserves
+     */
+    let remaining_usdc_fees = fee_vault_client.get_collected_fees(&usdc);
+    // the rest of the USDC fees are within rounding of the unclaimed half
+    assert!((usdc_fees - partial_amount - remaining_usdc_fees).abs() <= 1);
+
+    let claims = fee_vault_client.claim_all_fees(&treasury);
+    assert_eq!(e.auths()[0].0, gandalf);
+    assert_eq!(
+        claims,
+        vec![
+            &e,
+            (usdc.clone(), remaining_usdc_fees),
+            (xlm.clone(), xlm_fees)
+        ]
+    );
+    assert_eq!(
+        usdc_client.balance(&treasury),
+        partial_amount + remaining_usdc_fees
+    );
+    assert_eq!(xlm_client.balance(&treasury), xlm_fees);
+    assert_eq!(fee_vault_client.get_reserve_vault(&usdc).accrued_fees, 0);
+    assert_eq!(fee_vault_client.get_reserve_vault(&xlm).accrued_fees, 0);
+
+    // -> verify empty reserve vaults are skipped
+    assert_eq!(
+        fee_vault_client.claim_all_fees(&treasury),
+        Vec::<(Address, i128)>::new(&e)
+    );
+}
+
+#[test]
+fn test_claim_all_fees_skips_paused_reserves() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let treasury = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens of each reserve for a 50% util rate
+    let mut requests = Vec::new(&e);
+    for reserve in [usdc.clone(), xlm.clone()] {
+        requests.push_back(Request {
+            address: reserve.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        });
+        requests.push_back(Request {
+            address: reserve,
+            amount: 100_000_0000000,
+            request_type: 4,
+        });
+    }
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo deposits 10k tokens into each vault and fees accrue for a week
+    usdc_client.mint(&frodo, &10_000_0000000);
+    xlm_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &10_000_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &10_000_0000000);
+    e.jump(7 * ONE_DAY_LEDGERS);
+
+    /*
+     * Pause fee claims for XLM
+     */
+    fee_vault_client.pause(&Some(xlm.clone()), &PauseState::WithdrawOnly);
+    let usdc_fees = fee_vault_client.get_collected_fees(&usdc);
+    let xlm_b_tokens = fee_vault_client.get_reserve_vault(&xlm).accrued_fees;
+    assert!(xlm_b_tokens > 0);
+
+    // -> verify the USDC fees are claimed and the XLM fees are left in the vault
+    let claims = fee_vault_client.claim_all_fees(&treasury);
+    assert_eq!(claims, vec![&e, (usdc.clone(), usdc_fees)]);
+    assert_eq!(usdc_client.balance(&treasury), usdc_fees);
+    assert_eq!(xlm_client.balance(&treasury), 0);
+    assert_eq!(
+        fee_vault_client.get_reserve_vault(&xlm).accrued_fees,
+        xlm_b_tokens
+    );
+
+    // -> verify the XLM fees are claimed once the reserve vault is unpaused
+    fee_vault_client.unpause(&Some(xlm.clone()), &PauseState::Active);
+    let xlm_fees = fee_vault_client.get_collected_fees(&xlm);
+    let claims = fee_vault_client.claim_all_fees(&treasury);
+    assert_eq!(claims, vec![&e, (xlm.clone(), xlm_fees)]);
+    assert_eq!(xlm_client.balance(&treasury), xlm_fees);
+}
+
+#[test]
+fn test_claim_all_fees_caps_illiquid_reserves() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let treasury = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens of each reserve for a 50% util rate
+    let mut requests = Vec::new(&e);
+    for reserve in [usdc.clone(), xlm.clone()] {
+        requests.push_back(Request {
+            address: reserve.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        });
+        requests.push_back(Request {
+            address: reserve,
+            amount: 100_000_0000000,
+            request_type: 4,
+        });
+    }
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo deposits 10k tokens into each vault and fees accrue for a week
+    usdc_client.mint(&frodo, &10_000_0000000);
+    xlm_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &10_000_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &10_000_0000000);
+    e.jump(7 * ONE_DAY_LEDGERS);
+
+    let usdc_fees = fee_vault_client.get_collected_fees(&usdc);
+    let xlm_fees = fee_vault_client.get_collected_fees(&xlm);
+    assert!(usdc_fees > 0 && xlm_fees > 0);
+
+    /*
+     * Drain the USDC liquidity
+     * -> merry borrows usdc against xlm collateral until about half of the USDC fees are left
+     *    under the 90% max_util
+     */
+    let merry_collateral = 10_000_000_0000000;
+    xlm_client.mint(&merry, &merry_collateral);
+    let pool_reserve = pool_client.get_reserve(&usdc);
+    let total_supply = pool_reserve
+        .data
+        .b_supply
+        .fixed_mul_floor(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    let total_liabilities = pool_reserve
+        .data
+        .d_supply
+        .fixed_mul_ceil(pool_reserve.data.d_rate, SCALAR_12)
+        .unwrap_optimized();
+    let borrow_amount = (total_supply - usdc_fees / 2)
+        .fixed_mul_floor(0_9000000, SCALAR_7)
+        .unwrap_optimized()
+        - total_liabilities;
+    pool_client.submit(
+        &merry,
+        &merry,
+        &merry,
+        &vec![
+            &e,
+            Request {
+                request_type: 2,
+                address: xlm.clone(),
+                amount: merry_collateral,
+            },
+            Request {
+                request_type: 4,
+                address: usdc.clone(),
+                amount: borrow_amount,
+            },
+        ],
+    );
+    let usdc_available = e.as_contract(&fee_vault, || pool::reserve_available_liquidity(&e, &usdc));
+    assert!(usdc_available > 0 && usdc_available < usdc_fees);
+
+    // -> verify the USDC claim is capped at the available liquidity and XLM is claimed in full
+    let claims = fee_vault_client.claim_all_fees(&treasury);
+    assert_eq!(
+        claims,
+        vec![&e, (usdc.clone(), usdc_available), (xlm.clone(), xlm_fees)]
+    );
+    assert_eq!(usdc_client.balance(&treasury), usdc_available);
+    assert_eq!(xlm_client.balance(&treasury), xlm_fees);
+    let remaining_usdc_fees = fee_vault_client.get_collected_fees(&usdc);
+    assert!(remaining_usdc_fees > 0);
+    assert!((usdc_fees - usdc_available - remaining_usdc_fees).abs() <= 1);
+    assert_eq!(fee_vault_client.get_reserve_vault(&xlm).accrued_fees, 0);
+}
+
+#[test]
+fn test_convert_fees_to_shares() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let treasury = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // -> verify there must be fees to convert
+    assert_eq!(
+        fee_vault_client
+            .try_convert_fees_to_shares(&usdc, &treasury)
+            .err(),
+        Some(Ok(Error::from_contract_error(103)))
+    );
+
+    // frodo deposits 10k tokens into the vault and fees accrue for a week
+    usdc_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &10_000_0000000);
+    e.jump(7 * ONE_DAY_LEDGERS);
+
+    /*
+     * Convert the fees into shares
+     */
+    let fees = fee_vault_client.get_collected_fees(&usdc);
+    let pre_vault = fee_vault_client.get_reserve_vault(&usdc);
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&usdc, &frodo);
+
+    let shares = fee_vault_client.convert_fees_to_shares(&usdc, &treasury);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "vault_fee_conversion").into_val(&e),
+            usdc.into_val(&e),
+            gandalf.into_val(&e),
+            treasury.into_val(&e)
+        ]
+    );
+    let data: (i128, i128) = event.2.into_val(&e);
+    assert_eq!(data, (pre_vault.accrued_fees, shares));
+
+    // -> verify the fees now back the treasury's shares without touching the pool
+    let post_vault = fee_vault_client.get_reserve_vault(&usdc);
+    assert_eq!(post_vault.accrued_fees, 0);
+    assert_eq!(
+        post_vault.total_b_tokens,
+        pre_vault.total_b_tokens + pre_vault.accrued_fees
+    );
+    assert_eq!(post_vault.total_shares, pre_vault.total_shares + shares);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &treasury), shares);
+    assert!((fee_vault_client.get_underlying_tokens(&usdc, &treasury) - fees).abs() <= 1);
+    assert_eq!(
+        fee_vault_client.get_underlying_tokens(&usdc, &frodo),
+        frodo_underlying
+    );
+    assert_eq!(usdc_client.balance(&treasury), 0);
+
+    // -> verify the shares earn interest alongside depositors and can be redeemed
+    e.jump(ONE_DAY_LEDGERS);
+    let treasury_underlying = fee_vault_client.get_underlying_tokens(&usdc, &treasury);
+    assert!(treasury_underlying > fees);
+    fee_vault_client.redeem(&usdc, &treasury, &shares);
+    assert!((usdc_client.balance(&treasury) - treasury_underlying).abs() <= 1);
+}
@@ -0,0 +1,213 @@
+#![cfg(test)]
+
+use crate::constants::SCALAR_7;
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation};
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, IntoVal, Symbol};
+
+#[test]
+fn test_collateral_reserve_vault() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &true, &None);
+    assert!(fee_vault_client.is_collateral_vault(&usdc));
+
+    /*
+     * Deposit into the collateral vault
+     * -> frodo deposits 100 into the fee vault
+     * -> merry deposits 100 directly into the pool
+     * -> bombadil borrows to return to 50% util rate
+     */
+    let starting_balance = 100_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+
+    // -> verify the deposit is submitted as `SupplyCollateral`
+    let deposit_request = vec![
+        &e,
+        Request {
+            request_type: 2,
+            address: usdc.clone(),
+            amount: starting_balance,
+        },
+    ];
+    assert_eq!(
+        e.auths()[0],
+        (
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "deposit"),
+                    vec![
+                        &e,
+                        usdc.to_val(),
+                        frodo.to_val(),
+                        starting_balance.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        pool.clone(),
+                        Symbol::new(&e, "submit"),
+                        vec![
+                            &e,
+                            fee_vault.to_val(),
+                            frodo.to_val(),
+                            frodo.to_val(),
+                            deposit_request.to_val(),
+                        ]
+                    )),
+                    sub_invocations: std::vec![AuthorizedInvocation {
+                        function: AuthorizedFunction::Contract((
+                            usdc.clone(),
+                            Symbol::new(&e, "transfer"),
+                            vec![
+                                &e,
+                                frodo.to_val(),
+                                pool.to_val(),
+                                starting_balance.into_val(&e)
+                            ]
+                        )),
+                        sub_invocations: std::vec![]
+                    }]
+                }]
+            }
+        )
+    );
+
+    // -> verify the vault's pool position is held as collateral
+    let vault_positions = pool_client.get_positions(&fee_vault);
+    assert!(vault_positions.supply.is_empty());
+    assert_eq!(
+        vault_positions.collateral.get(0).unwrap_optimized(),
+        starting_balance
+    );
+    assert_eq!(fee_vault_client.get_shares(&usdc, &frodo), starting_balance);
+
+    usdc_client.mint(&merry, &starting_balance);
+    pool_client.submit(
+        &merry,
+        &merry,
+        &merry,
+        &vec![
+            &e,
+            Request {
+                request_type: 2,
+                address: usdc.clone(),
+                amount: starting_balance,
+            },
+        ],
+    );
+    pool_client.submit(
+        &bombadil,
+        &bombadil,
+        &bombadil,
+        &vec![
+            &e,
+            Request {
+                request_type: 4,
+                address: usdc.clone(),
+                amount: starting_balance,
+            },
+        ],
+    );
+
+    /*
+     * Allow 1 week to pass
+     */
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    /*
+     * Withdraw from the collateral vault
+     * -> merry withdraws all funds from the pool
+     * -> frodo withdraws their full position from the vault
+     * -> admin claims fees, fully unwinding the vault's collateral position
+     */
+    pool_client.submit(
+        &merry,
+        &merry,
+        &merry,
+        &vec![
+            &e,
+            Request {
+                request_type: 3,
+                address: usdc.clone(),
+                amount: starting_balance * 2,
+            },
+        ],
+    );
+    let merry_profit = usdc_client.balance(&merry) - starting_balance;
+
+    // frodo earns the same interest as merry less the 10% vault fee
+    let expected_frodo_profit = merry_profit
+        .fixed_mul_ceil(0_9000000, SCALAR_7)
+        .unwrap_optimized();
+    let withdraw_amount = starting_balance + expected_frodo_profit;
+    fee_vault_client.withdraw(&usdc, &frodo, &withdraw_amount);
+    assert_eq!(usdc_client.balance(&frodo), withdraw_amount);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &frodo), 0);
+
+    let fees = fee_vault_client.get_collected_fees(&usdc);
+    assert!(fees > 0);
+    fee_vault_client.claim_fees(&usdc, &gandalf);
+    assert_eq!(usdc_client.balance(&gandalf), fees);
+
+    // -> verify vault position is empty and fully unwound
+    let vault_positions = pool_client.get_positions(&fee_vault);
+    assert!(vault_positions.collateral.is_empty());
+    assert!(vault_positions.supply.is_empty());
+}
@@ -0,0 +1,341 @@
+#![cfg(test)]
+
+use crate::constants::SCALAR_7;
+use crate::storage::{FeeMode, PauseState, ONE_DAY_LEDGERS};
+use crate::testutils::{
+    assert_approx_eq_abs, create_blend_pool, create_fee_vault, mockrouter::register_mock_router,
+    EnvTestUtils,
+};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::testutils::{Address as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, Error, IntoVal};
+
+#[test]
+fn test_compound_emissions() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let keeper = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let blnd_client = MockTokenClient::new(&e, &blnd);
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    // no fees are taken on interest, so compounding is the only other change to the vault's bTokens
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // the router pays 0.5 USDC per BLND
+    let swap_rate = 0_5000000;
+    let router_client = register_mock_router(&e, swap_rate);
+    usdc_client.mint(&router_client.address, &1_000_000_0000000);
+
+    // start emissions to the pool's reserves
+    blend_fixture.backstop.distribute();
+    e.jump(ONE_DAY_LEDGERS);
+    blend_fixture.emitter.distribute();
+    blend_fixture.backstop.distribute();
+    pool_client.gulp_emissions();
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    let take_rate = 0_1000000;
+    fee_vault_client.set_emissions_take_rate(&take_rate);
+
+    // -> verify compounding requires a config
+    assert_eq!(
+        fee_vault_client.try_compound_emissions(&usdc, &0).err(),
+        Some(Ok(Error::from_contract_error(115)))
+    );
+    fee_vault_client.set_compound_config(&router_client.address, &keeper);
+    assert_eq!(
+        fee_vault_client.try_set_compounding(&xlm, &true).err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+    fee_vault_client.set_compounding(&usdc, &true);
+    assert!(fee_vault_client.is_compounding_vault(&usdc));
+    assert!(!fee_vault_client.is_compounding_vault(&xlm));
+
+    /*
+     * Deposit into the vault and directly into the pool
+     * -> frodo and samwise deposit 1k tokens each into the vault
+     * -> merry deposits 2k tokens directly into the pool
+     */
+    let starting_balance = 1_000_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    usdc_client.mint(&samwise, &starting_balance);
+    usdc_client.mint(&merry, &(starting_balance * 2));
+    fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+    fee_vault_client.deposit(&usdc, &samwise, &starting_balance);
+    pool_client.submit(
+        &merry,
+        &merry,
+        &merry,
+        &vec![
+            &e,
+            Request {
+                request_type: 0,
+                address: usdc.clone(),
+                amount: starting_balance * 2,
+            },
+        ],
+    );
+
+    e.jump(ONE_DAY_LEDGERS);
+
+    /*
+     * Compound the vault's emissions
+     * -> the keeper compounds the depositors' BLND into usdc
+     * -> the depositors do not accrue BLND, but their underlying grows by the compounded amount
+     */
+    // -> verify the swap's minimum output is enforced
+    assert!(fee_vault_client
+        .try_compound_emissions(&usdc, &1_000_000_0000000)
+        .is_err());
+
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&usdc, &frodo);
+    let samwise_underlying = fee_vault_client.get_underlying_tokens(&usdc, &samwise);
+    let vault_b_tokens = fee_vault_client.get_reserve_vault(&usdc).total_b_tokens;
+    let shares = fee_vault_client.get_reserve_vault(&usdc).total_shares;
+
+    // only the keeper's auth is mocked, so the vault must authorize the pool's transfer itself
+    let compounded = fee_vault_client
+        .mock_auths(&[MockAuth {
+            address: &keeper,
+            invoke: &MockAuthInvoke {
+                contract: &fee_vault,
+                fn_name: "compound_emissions",
+                args: vec![&e, usdc.into_val(&e), 0i128.into_val(&e)],
+                sub_invokes: &[],
+            },
+        }])
+        .compound_emissions(&usdc, &0);
+    assert!(compounded > 0);
+
+    let vault = fee_vault_client.get_reserve_vault(&usdc);
+    assert_eq!(vault.total_shares, shares);
+    assert!(vault.total_b_tokens > vault_b_tokens);
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &frodo), 0);
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &samwise), 0);
+    assert_approx_eq_abs(
+        fee_vault_client.get_underlying_tokens(&usdc, &frodo),
+        frodo_underlying + compounded / 2,
+        10,
+    );
+    assert_approx_eq_abs(
+        fee_vault_client.get_underlying_tokens(&usdc, &samwise),
+        samwise_underlying + compounded / 2,
+        10,
+    );
+
+    // -> verify the vault's emissions were split between the admin and the compound
+    pool_client.claim(&merry, &vec![&e, 1], &merry);
+    let merry_emissions = blnd_client.balance(&merry);
+    let admin_claim = fee_vault_client.claim_emissions(&vec![&e, 1], &gandalf);
+    let expected_admin_take = merry_emissions
+        .fixed_mul_ceil(take_rate, SCALAR_7)
+        .unwrap_optimized();
+    assert_approx_eq_abs(admin_claim, expected_admin_take, 10);
+    assert_approx_eq_abs(
+        compounded,
+        (merry_emissions - admin_claim)
+            .fixed_mul_floor(swap_rate, SCALAR_7)
+            .unwrap_optimized(),
+        10,
+    );
+    assert!(blnd_client.balance(&fee_vault) < 10);
+
+    // -> verify compounding with nothing held is a no-op
+    assert_eq!(fee_vault_client.compound_emissions(&usdc, &0), 0);
+
+    /*
+     * Stop compounding
+     * -> the depositors accrue BLND again
+     */
+    fee_vault_client.set_compounding(&usdc, &false);
+    assert!(!fee_vault_client.is_compounding_vault(&usdc));
+    e.jump(ONE_DAY_LEDGERS);
+    assert_eq!(fee_vault_client.compound_emissions(&usdc, &0), 0);
+    let frodo_claim = fee_vault_client.claim_user_emissions(&usdc, &frodo, &frodo);
+    assert!(frodo_claim > 0);
+    assert_eq!(blnd_client.balance(&frodo), frodo_claim);
+}
+
+#[test]
+fn test_compound_emissions_guards() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let keeper = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // the router pays 0.5 USDC per BLND
+    let router_client = register_mock_router(&e, 0_5000000);
+    usdc_client.mint(&router_client.address, &1_000_000_0000000);
+
+    // start emissions to the pool's reserves
+    blend_fixture.backstop.distribute();
+    e.jump(ONE_DAY_LEDGERS);
+    blend_fixture.emitter.distribute();
+    blend_fixture.backstop.distribute();
+    pool_client.gulp_emissions();
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.set_compound_config(&router_client.address, &keeper);
+    fee_vault_client.set_compounding(&usdc, &true);
+
+    usdc_client.mint(&frodo, &1_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &1_000_0000000);
+    e.jump(ONE_DAY_LEDGERS);
+
+    /*
+     * Compounding follows the deposit pause states
+     */
+    fee_vault_client.pause(&Some(usdc.clone()), &PauseState::DepositsBlocked);
+    assert_eq!(
+        fee_vault_client.try_compound_emissions(&usdc, &0).err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    fee_vault_client.unpause(&Some(usdc.clone()), &PauseState::Active);
+
+    /*
+     * The router over-reports its output
+     */
+    let bonus = 1_000_000_0000000;
+    router_client.set_bonus(&bonus);
+
+    // -> verify the minimum output is checked against what the vault received
+    assert_eq!(
+        fee_vault_client.try_compound_emissions(&usdc, &bonus).err(),
+        Some(Ok(Error::from_contract_error(136)))
+    );
+
+    // -> verify only the amount received is credited to the vault
+    let router_balance = usdc_client.balance(&router_client.address);
+    let vault = fee_vault_client.get_reserve_vault(&usdc);
+    let compounded = fee_vault_client.compound_emissions(&usdc, &0);
+    assert!(compounded > 0 && compounded < bonus);
+    assert_eq!(
+        router_balance - usdc_client.balance(&router_client.address),
+        compounded
+    );
+    let compounded_b_tokens = compounded
+        .fixed_div_floor(vault.b_rate, 1_000_000_000_000)
+        .unwrap_optimized();
+    assert_approx_eq_abs(
+        fee_vault_client.get_reserve_vault(&usdc).total_b_tokens,
+        vault.total_b_tokens + compounded_b_tokens,
+        10,
+    );
+    router_client.set_bonus(&0);
+
+    /*
+     * Compound after every depositor has left
+     * -> the compounded tokens are added to the fees, not to the next depositor
+     */
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.withdraw_all(&usdc, &frodo);
+    let vault = fee_vault_client.get_reserve_vault(&usdc);
+    assert_eq!(vault.total_shares, 0);
+
+    let compounded = fee_vault_client.compound_emissions(&usdc, &0);
+    assert!(compounded > 0);
+    let post_vault = fee_vault_client.get_reserve_vault(&usdc);
+    assert_eq!(post_vault.total_b_tokens, vault.total_b_tokens);
+    assert!(post_vault.accrued_fees > vault.accrued_fees);
+
+    /*
+     * Compounding is blocked once the reserve vault is deprecated
+     */
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.deprecate_reserve_vault(&usdc);
+    assert_eq!(
+        fee_vault_client.try_compound_emissions(&usdc, &0).err(),
+        Some(Ok(Error::from_contract_error(133)))
+    );
+}
@@ -1,6 +1,7 @@
 #![cfg(test)]
 
 use crate::constants::{SCALAR_12, SCALAR_7};
+use crate::storage::FeeMode;
 use crate::testutils::{assert_approx_eq_abs, create_blend_pool, create_fee_vault, EnvTestUtils};
 use crate::FeeVaultClient;
 use blend_contract_sdk::pool::{Client as PoolClient, PoolDataKey, Request};
@@ -40,7 +41,7 @@ fn test_default() {
     // emits to each reserve token evently, and starts emissions
     let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
     let pool_client = PoolClient::new(&e, &pool);
-    let fee_vault = create_fee_vault(&e, &gandalf, &pool, false, 100_0000);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(100_0000));
     let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
 
     // Setup pool util rate
@@ -72,7 +73,7 @@ fn test_default() {
         .mock_all_auths()
         .submit(&bombadil, &bombadil, &bombadil, &requests);
 
-    fee_vault_client.add_reserve_vault(&usdc);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
 
     let pool_usdc_balance_start = usdc_client.balance(&pool);
 
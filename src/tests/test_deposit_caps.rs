@@ -0,0 +1,214 @@
+#![cfg(test)]
+
+use crate::storage::{DepositCaps, FeeMode, Role, ONE_DAY_LEDGERS};
+use crate::testutils::{
+    create_blend_pool, create_fee_vault, share_token_wasm::Client as ShareTokenClient, EnvTestUtils,
+};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_deposit_caps() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let elrond = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.grant_role(&Role::ReserveManager, &elrond);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    usdc_client.mint(&frodo, &10_000_0000000);
+    usdc_client.mint(&samwise, &10_000_0000000);
+
+    // -> verify the vault starts uncapped
+    assert_eq!(
+        fee_vault_client.get_deposit_caps(&usdc),
+        DepositCaps::default()
+    );
+    assert_eq!(
+        fee_vault_client.get_deposit_capacity(&usdc, &frodo),
+        i128::MAX
+    );
+    assert_eq!(fee_vault_client.get_deposit_capacity(&xlm, &frodo), 0);
+
+    /*
+     * Set the deposit caps
+     */
+    // -> verify caps can't be negative
+    assert_eq!(
+        fee_vault_client
+            .try_set_deposit_caps(&usdc, &Some(-1), &None)
+            .err(),
+        Some(Ok(Error::from_contract_error(8)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_set_deposit_caps(&xlm, &None, &None)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    fee_vault_client.set_deposit_caps(&usdc, &Some(1_500_0000000), &Some(1_000_0000000));
+    assert_eq!(e.auths()[0].0, elrond);
+    let caps = DepositCaps {
+        reserve_cap: Some(1_500_0000000),
+        user_cap: Some(1_000_0000000),
+    };
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "deposit_caps_update").into_val(&e),
+            usdc.into_val(&e)
+        ]
+    );
+    let data: DepositCaps = event.2.into_val(&e);
+    assert_eq!(data, caps);
+    assert_eq!(fee_vault_client.get_deposit_caps(&usdc), caps);
+
+    /*
+     * Deposit up to the user cap
+     */
+    assert_eq!(
+        fee_vault_client.get_deposit_capacity(&usdc, &frodo),
+        1_000_0000000
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_000_0000001)
+            .err(),
+        Some(Ok(Error::from_contract_error(129)))
+    );
+    fee_vault_client.deposit(&usdc, &frodo, &900_0000000);
+    let capacity = fee_vault_client.get_deposit_capacity(&usdc, &frodo);
+    // the deposit rounds down by at most a stroop
+    assert!((capacity - 100_0000000).abs() <= 1);
+
+    // -> verify accrued interest counts towards the user cap
+    e.jump(7 * ONE_DAY_LEDGERS);
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&usdc, &frodo);
+    assert!(frodo_underlying > 900_0000000);
+    let capacity = fee_vault_client.get_deposit_capacity(&usdc, &frodo);
+    assert_eq!(capacity, 1_000_0000000 - frodo_underlying);
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &(capacity + 1))
+            .err(),
+        Some(Ok(Error::from_contract_error(129)))
+    );
+    assert_eq!(
+        fee_vault_client.try_mint(&usdc, &frodo, &100_0000000).err(),
+        Some(Ok(Error::from_contract_error(129)))
+    );
+    fee_vault_client.deposit(&usdc, &frodo, &(capacity - 1));
+
+    /*
+     * Deposit up to the reserve cap
+     */
+    let total_underlying = fee_vault_client.get_underlying_tokens(&usdc, &frodo);
+    let capacity = fee_vault_client.get_deposit_capacity(&usdc, &samwise);
+    assert!((capacity - (1_500_0000000 - total_underlying)).abs() <= 1);
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &samwise, &(capacity + 1))
+            .err(),
+        Some(Ok(Error::from_contract_error(128)))
+    );
+    fee_vault_client.deposit(&usdc, &samwise, &(capacity - 1));
+    assert!(fee_vault_client.get_deposit_capacity(&usdc, &samwise) <= 1);
+
+    // -> verify share transfers can't take the recipient over the user cap
+    let share_token_client = ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&usdc));
+    assert_eq!(
+        share_token_client
+            .try_transfer(&samwise, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(129)))
+    );
+
+    // -> verify withdrawals free up capacity
+    fee_vault_client.withdraw(&usdc, &frodo, &100_0000000);
+    assert!(fee_vault_client.get_deposit_capacity(&usdc, &samwise) >= 100_0000000);
+    let frodo_shares = fee_vault_client.get_shares(&usdc, &frodo);
+    share_token_client.transfer(&samwise, &frodo, &10_0000000);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &frodo),
+        frodo_shares + 10_0000000
+    );
+
+    // -> verify converting fees into shares can't exceed the caps
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.set_deposit_caps(&usdc, &None, &Some(0));
+    assert_eq!(
+        fee_vault_client
+            .try_convert_fees_to_shares(&usdc, &gandalf)
+            .err(),
+        Some(Ok(Error::from_contract_error(129)))
+    );
+    fee_vault_client.set_deposit_caps(&usdc, &Some(0), &None);
+    assert_eq!(
+        fee_vault_client
+            .try_convert_fees_to_shares(&usdc, &gandalf)
+            .err(),
+        Some(Ok(Error::from_contract_error(128)))
+    );
+
+    /*
+     * Remove the caps
+     */
+    fee_vault_client.set_deposit_caps(&usdc, &None, &None);
+    assert_eq!(
+        fee_vault_client.get_deposit_capacity(&usdc, &samwise),
+        i128::MAX
+    );
+    fee_vault_client.deposit(&usdc, &samwise, &5_000_0000000);
+}
@@ -0,0 +1,287 @@
+#![cfg(test)]
+
+use crate::constants::SCALAR_7;
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::share_token_wasm::Client as ShareTokenClient;
+use crate::testutils::{assert_approx_eq_abs, create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::testutils::Events;
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, Error, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_user_emissions() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let pippin = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let blnd_client = MockTokenClient::new(&e, &blnd);
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // start emissions to the pool's reserves
+    blend_fixture.backstop.distribute();
+    e.jump(ONE_DAY_LEDGERS);
+    blend_fixture.emitter.distribute();
+    blend_fixture.backstop.distribute();
+    pool_client.gulp_emissions();
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    let share_token_client = ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&usdc));
+
+    // -> verify the emissions take rate is validated and set
+    assert_eq!(
+        fee_vault_client
+            .try_set_emissions_take_rate(&1_000_0001)
+            .err(),
+        Some(Ok(Error::from_contract_error(114)))
+    );
+    assert_eq!(
+        fee_vault_client.try_set_emissions_take_rate(&-1).err(),
+        Some(Ok(Error::from_contract_error(114)))
+    );
+    let take_rate = 0_1000000;
+    fee_vault_client.set_emissions_take_rate(&take_rate);
+    assert_eq!(fee_vault_client.get_emissions_take_rate(), take_rate);
+
+    /*
+     * Deposit into the vault and directly into the pool
+     * -> frodo and samwise deposit 1k tokens each into the vault
+     * -> merry deposits 2k tokens directly into the pool
+     */
+    let starting_balance = 1_000_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    usdc_client.mint(&samwise, &starting_balance);
+    usdc_client.mint(&merry, &(starting_balance * 2));
+    fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+    fee_vault_client.deposit(&usdc, &samwise, &starting_balance);
+    pool_client.submit(
+        &merry,
+        &merry,
+        &merry,
+        &vec![
+            &e,
+            Request {
+                request_type: 0,
+                address: usdc.clone(),
+                amount: starting_balance * 2,
+            },
+        ],
+    );
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &frodo), 0);
+
+    e.jump(ONE_DAY_LEDGERS);
+
+    /*
+     * Distribute emissions through a share transfer
+     * -> frodo transfers half of their shares to pippin
+     * -> pippin does not earn the emissions from before the transfer
+     */
+    let frodo_shares = fee_vault_client.get_shares(&usdc, &frodo);
+    share_token_client.transfer(&frodo, &pippin, &(frodo_shares / 2));
+    let frodo_emissions = fee_vault_client.get_user_emissions(&usdc, &frodo);
+    assert!(frodo_emissions > 0);
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &pippin), 0);
+    // samwise holds the same shares, so is owed the same emissions without a checkpoint
+    assert_eq!(
+        fee_vault_client.get_user_emissions(&usdc, &samwise),
+        frodo_emissions
+    );
+
+    e.jump(ONE_DAY_LEDGERS);
+
+    /*
+     * Claim emissions
+     * -> merry claims from the pool directly
+     * -> frodo, samwise and pippin claim from the vault, and the admin claims their take
+     * -> the vault's depositors and admin receive the same emissions as merry
+     */
+    pool_client.claim(&merry, &vec![&e, 1], &merry);
+    let merry_emissions = blnd_client.balance(&merry);
+    assert!(merry_emissions > 0);
+
+    let frodo_claim = fee_vault_client.claim_user_emissions(&usdc, &frodo, &frodo);
+    let samwise_claim = fee_vault_client.claim_user_emissions(&usdc, &samwise, &samwise);
+    let pippin_claim = fee_vault_client.claim_user_emissions(&usdc, &pippin, &merry);
+    let admin_claim = fee_vault_client.claim_emissions(&vec![&e, 1], &gandalf);
+    assert_eq!(blnd_client.balance(&frodo), frodo_claim);
+    assert_eq!(blnd_client.balance(&samwise), samwise_claim);
+    assert_eq!(blnd_client.balance(&merry), merry_emissions + pippin_claim);
+    assert_eq!(blnd_client.balance(&gandalf), admin_claim);
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &frodo), 0);
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &samwise), 0);
+    assert_eq!(fee_vault_client.get_user_emissions(&usdc, &pippin), 0);
+
+    // frodo and pippin split frodo's original position after the first day
+    assert!(pippin_claim > 0);
+    assert_approx_eq_abs(frodo_claim + pippin_claim, samwise_claim, 10);
+
+    let vault_emissions = frodo_claim + samwise_claim + pippin_claim + admin_claim;
+    assert_approx_eq_abs(vault_emissions, merry_emissions, 10);
+    let expected_admin_take = vault_emissions
+        .fixed_mul_ceil(take_rate, SCALAR_7)
+        .unwrap_optimized();
+    assert_approx_eq_abs(admin_claim, expected_admin_take, 10);
+    // the vault does not hold any unassigned emissions
+    assert!(blnd_client.balance(&fee_vault) < 10);
+
+    // -> verify claiming with nothing accrued is a no-op
+    assert_eq!(
+        fee_vault_client.claim_user_emissions(&usdc, &frodo, &frodo),
+        0
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_claim_user_emissions(&xlm, &frodo, &frodo)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+}
+
+#[test]
+fn test_claim_all_emissions() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let blnd_client = MockTokenClient::new(&e, &blnd);
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // start emissions to the pool's reserves
+    blend_fixture.backstop.distribute();
+    e.jump(ONE_DAY_LEDGERS);
+    blend_fixture.emitter.distribute();
+    blend_fixture.backstop.distribute();
+    pool_client.gulp_emissions();
+
+    // the vault's reserve order does not match the pool's reserve order
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.set_emissions_take_rate(&0_5000000);
+
+    let starting_balance = 1_000_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    xlm_client.mint(&frodo, &starting_balance);
+    fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+    fee_vault_client.deposit(&xlm, &frodo, &starting_balance);
+
+    e.jump(ONE_DAY_LEDGERS);
+
+    /*
+     * Claim the admin's emissions for all reserves
+     * -> the xlm bToken id is 3 and the usdc bToken id is 1
+     * -> the claim matches claiming each reserve's id directly
+     */
+    let claimed = fee_vault_client.claim_all_emissions(&gandalf);
+    assert!(claimed > 0);
+    assert_eq!(blnd_client.balance(&gandalf), claimed);
+
+    let xlm_claim = fee_vault_client.claim_emissions(&vec![&e, 3], &gandalf);
+    let usdc_claim = fee_vault_client.claim_emissions(&vec![&e, 1], &gandalf);
+    assert_eq!(xlm_claim, 0);
+    assert_eq!(usdc_claim, 0);
+
+    // -> verify the event includes the derived ids and the per reserve amounts
+    e.jump(ONE_DAY_LEDGERS);
+    let claimed = fee_vault_client.claim_all_emissions(&gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "vault_emissions_claim").into_val(&e),
+            gandalf.into_val(&e),
+        ]
+    );
+    let data: (Vec<u32>, Vec<(Address, i128)>, i128) = event.2.into_val(&e);
+    assert_eq!(data.0, vec![&e, 3, 1]);
+    assert_eq!(data.1.len(), 2);
+    let (xlm_reserve, xlm_amount) = data.1.get_unchecked(0);
+    let (usdc_reserve, usdc_amount) = data.1.get_unchecked(1);
+    assert_eq!(xlm_reserve, xlm);
+    assert_eq!(usdc_reserve, usdc);
+    assert!(xlm_amount > 0);
+    assert!(usdc_amount > 0);
+    assert_eq!(xlm_amount + usdc_amount, claimed);
+    assert_eq!(data.2, claimed);
+
+    // -> verify ids that don't belong to a reserve vault are left out of the event
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.claim_emissions(&vec![&e, 0, 3, 99], &gandalf);
+    let event = e.events().all().last().unwrap();
+    let data: (Vec<u32>, Vec<(Address, i128)>, i128) = event.2.into_val(&e);
+    assert_eq!(data.0, vec![&e, 3]);
+    assert_eq!(data.1.len(), 1);
+}
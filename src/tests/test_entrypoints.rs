@@ -3,9 +3,10 @@
 use crate::{
     constants::SCALAR_12,
     reserve_vault::ReserveVault,
-    storage,
+    storage::{self, FeeMode, PendingAdmin, ADMIN_PROPOSAL_LEDGERS},
     testutils::{
         assert_approx_eq_rel, create_blend_pool, create_fee_vault, mockpool, register_fee_vault,
+        share_token_wasm::{self, Client as ShareTokenClient},
         EnvTestUtils,
     },
     FeeVaultClient,
@@ -14,7 +15,7 @@ use blend_contract_sdk::testutils::BlendFixture;
 use sep_41_token::testutils::MockTokenClient;
 use soroban_fixed_point_math::FixedPoint;
 use soroban_sdk::{
-    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation},
+    testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events},
     unwrap::UnwrapOptimized,
     vec, Address, Env, Error, IntoVal, Symbol,
 };
@@ -25,18 +26,11 @@ fn test_constructor_ok() {
     e.mock_all_auths();
     let samwise = Address::generate(&e);
     let blend_pool = Address::generate(&e);
-    let take_rate = 1_000_0000;
-    let is_apr_capped = false;
+    let blnd = Address::generate(&e);
+    let fee_mode = FeeMode::TakeRate(1_000_0000);
 
-    let vault_address = register_fee_vault(
-        &e,
-        Some((
-            samwise.clone(),
-            blend_pool.clone(),
-            is_apr_capped,
-            take_rate,
-        )),
-    );
+    let vault_address = create_fee_vault(&e, &samwise, &blend_pool, &blnd, fee_mode.clone());
+    let share_token_wasm_hash = e.deployer().upload_contract_wasm(share_token_wasm::WASM);
 
     assert_eq!(
         e.auths()[0],
@@ -50,8 +44,9 @@ fn test_constructor_ok() {
                         &e,
                         samwise.into_val(&e),
                         blend_pool.into_val(&e),
-                        is_apr_capped.into_val(&e),
-                        take_rate.into_val(&e),
+                        blnd.into_val(&e),
+                        fee_mode.into_val(&e),
+                        share_token_wasm_hash.into_val(&e),
                     ]
                 )),
                 sub_invocations: std::vec![]
@@ -65,9 +60,13 @@ fn test_constructor_ok() {
     e.as_contract(&vault_address, || {
         assert_eq!(storage::get_admin(&e), samwise);
         assert_eq!(storage::get_pool(&e), blend_pool);
-        let fee_mode = storage::get_fee_mode(&e);
-        assert_eq!(fee_mode.is_apr_capped, is_apr_capped);
-        assert_eq!(fee_mode.value, take_rate);
+        assert_eq!(storage::get_blnd(&e), blnd);
+        assert_eq!(
+            storage::get_share_token_wasm_hash(&e),
+            share_token_wasm_hash
+        );
+        assert_eq!(storage::get_emissions_take_rate(&e), 0);
+        assert_eq!(storage::get_fee_mode(&e), fee_mode);
     });
 }
 
@@ -78,7 +77,10 @@ fn test_constructor_negative_take_rate() {
     e.mock_all_auths();
     let samwise = Address::generate(&e);
     // Note: This fails with `InvalidAction` during testing, rather than `InvalidTakeRate`
-    register_fee_vault(&e, Some((samwise.clone(), samwise.clone(), false, -1)));
+    register_fee_vault(
+        &e,
+        Some((samwise.clone(), samwise.clone(), FeeMode::TakeRate(-1))),
+    );
 }
 
 #[test]
@@ -88,7 +90,10 @@ fn test_constructor_negative_apr_cap() {
     e.mock_all_auths();
     let samwise = Address::generate(&e);
     // Note: This fails with `InvalidAction` during testing, rather than `InvalidTakeRate`
-    register_fee_vault(&e, Some((samwise.clone(), samwise.clone(), true, -1999)));
+    register_fee_vault(
+        &e,
+        Some((samwise.clone(), samwise.clone(), FeeMode::AprCap(-1999))),
+    );
 }
 
 #[test]
@@ -101,7 +106,11 @@ fn test_constructor_take_rate_over_max() {
     // Note: This fails with `InvalidAction` during testing, rather than `InvalidTakeRate`
     register_fee_vault(
         &e,
-        Some((samwise.clone(), samwise.clone(), false, 1_000_0001)),
+        Some((
+            samwise.clone(),
+            samwise.clone(),
+            FeeMode::TakeRate(1_000_0001),
+        )),
     );
 }
 
@@ -118,8 +127,7 @@ fn test_constructor_apr_cap_over_max() {
         Some((
             samwise.clone(),
             samwise.clone(),
-            true,
-            170_141_183_460_469_231_731_687_303_715_884_105_727i128,
+            FeeMode::AprCap(170_141_183_460_469_231_731_687_303_715_884_105_727i128),
         )),
     );
 }
@@ -141,8 +149,7 @@ fn test_get_b_tokens() {
         Some((
             samwise.clone(),
             mock_client.address.clone(),
-            false,
-            0_1000000,
+            FeeMode::TakeRate(0_1000000),
         )),
     );
 
@@ -154,6 +161,7 @@ fn test_get_b_tokens() {
             total_b_tokens: 1000_0000000,
             total_shares: 1200_0000000,
             b_rate: init_b_rate,
+            high_water_b_rate: init_b_rate,
             last_update_timestamp: e.ledger().timestamp(),
             accrued_fees: 0,
         };
@@ -227,8 +235,7 @@ fn test_underlying_wrappers() {
         Some((
             samwise.clone(),
             mock_client.address.clone(),
-            false,
-            0_1000000,
+            FeeMode::TakeRate(0_1000000),
         )),
     );
 
@@ -240,6 +247,7 @@ fn test_underlying_wrappers() {
             total_b_tokens: 1000_0000000,
             total_shares: 1200_0000000,
             b_rate: init_b_rate,
+            high_water_b_rate: init_b_rate,
             last_update_timestamp: e.ledger().timestamp(),
             accrued_fees: 0,
         };
@@ -313,6 +321,114 @@ fn test_underlying_wrappers() {
     assert_eq!(vault_client.get_collected_fees(&non_existent_reserve), 0);
 }
 
+#[test]
+fn test_preview_functions() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let reserve = Address::generate(&e);
+    let init_b_rate = 1_000_000_000_000;
+
+    let mock_client = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate);
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            mock_client.address.clone(),
+            FeeMode::TakeRate(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    e.as_contract(&vault_address, || {
+        let reserve_vault = ReserveVault {
+            address: reserve.clone(),
+            total_b_tokens: 1000_0000000,
+            total_shares: 1200_0000000,
+            b_rate: init_b_rate,
+            high_water_b_rate: init_b_rate,
+            last_update_timestamp: e.ledger().timestamp(),
+            accrued_fees: 0,
+        };
+        storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+    });
+
+    // b_rate is increased by 10%. `take_rate` is 10%
+    mock_client.set_b_rate(&1_100_000_000_000);
+    e.jump(5);
+
+    // previews use the updated vault, with 1000 - 9.0909090 bTokens backing 1200 shares
+    let updated_vault = vault_client.get_reserve_vault(&reserve);
+    assert_eq!(updated_vault.total_b_tokens, 1000_0000000 - 90909090);
+
+    let amount = 100_0000000;
+    let b_tokens = updated_vault.underlying_to_b_tokens_down(amount);
+    assert_eq!(
+        vault_client.preview_deposit(&reserve, &amount),
+        updated_vault.b_tokens_to_shares_down(b_tokens)
+    );
+    let b_tokens = updated_vault.underlying_to_b_tokens_up(amount);
+    assert_eq!(
+        vault_client.preview_withdraw(&reserve, &amount),
+        updated_vault.b_tokens_to_shares_up(b_tokens)
+    );
+
+    let shares = 100_0000000;
+    let b_tokens = updated_vault.shares_to_b_tokens_down(shares);
+    assert_eq!(
+        vault_client.preview_redeem(&reserve, &shares),
+        updated_vault.b_tokens_to_underlying_down(b_tokens)
+    );
+    let b_tokens = updated_vault.shares_to_b_tokens_up(shares);
+    assert_eq!(
+        vault_client.preview_mint(&reserve, &shares),
+        updated_vault.b_tokens_to_underlying_up(b_tokens)
+    );
+
+    // rounding always favors the vault
+    assert!(
+        vault_client.preview_redeem(&reserve, &shares)
+            < vault_client.preview_mint(&reserve, &shares)
+    );
+    assert!(
+        vault_client.preview_deposit(&reserve, &amount)
+            < vault_client.preview_withdraw(&reserve, &amount)
+    );
+
+    // The view functions shouldn't mutate the state
+    e.as_contract(&vault_address, || {
+        let reserve_vault = storage::get_reserve_vault(&e, &reserve);
+        assert_eq!(reserve_vault.accrued_fees, 0);
+        assert_eq!(reserve_vault.total_b_tokens, 1000_0000000);
+        assert_eq!(reserve_vault.b_rate, init_b_rate);
+    });
+
+    // previews fail for reserves without a vault
+    let non_existent_reserve = Address::generate(&e);
+    assert_eq!(
+        vault_client
+            .try_preview_deposit(&non_existent_reserve, &amount)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+    assert_eq!(
+        vault_client
+            .try_preview_redeem(&non_existent_reserve, &shares)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    // max functions return 0 if the vault doesn't exist or the user has no shares
+    assert_eq!(vault_client.max_deposit(&non_existent_reserve, &samwise), 0);
+    assert_eq!(
+        vault_client.max_withdraw(&non_existent_reserve, &samwise),
+        0
+    );
+    assert_eq!(vault_client.max_withdraw(&reserve, &samwise), 0);
+}
+
 #[test]
 fn test_set_fee_mode() {
     let e = Env::default();
@@ -322,27 +438,45 @@ fn test_set_fee_mode() {
 
     let vault_address = register_fee_vault(
         &e,
-        Some((samwise.clone(), Address::generate(&e), false, 0_1000000)),
+        Some((
+            samwise.clone(),
+            Address::generate(&e),
+            FeeMode::TakeRate(0_1000000),
+        )),
     );
     let vault_client = FeeVaultClient::new(&e, &vault_address);
 
     // value should be in range 0..1_000_0000
     assert_eq!(
-        vault_client.try_set_fee_mode(&false, &-1).err(),
+        vault_client.try_set_fee_mode(&FeeMode::TakeRate(-1)).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    assert_eq!(
+        vault_client.try_set_fee_mode(&FeeMode::AprCap(-2)).err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    assert_eq!(
+        vault_client
+            .try_set_fee_mode(&FeeMode::AprCap(1_000_0001))
+            .err(),
         Some(Ok(Error::from_contract_error(104)))
     );
     assert_eq!(
-        vault_client.try_set_fee_mode(&true, &-2).err(),
+        vault_client
+            .try_set_fee_mode(&FeeMode::Hybrid(1_000_0001, 0_0500000))
+            .err(),
         Some(Ok(Error::from_contract_error(104)))
     );
     assert_eq!(
-        vault_client.try_set_fee_mode(&true, &1_000_0001).err(),
+        vault_client
+            .try_set_fee_mode(&FeeMode::Hybrid(0_1000000, -1))
+            .err(),
         Some(Ok(Error::from_contract_error(104)))
     );
 
     // Set take rate to 0.5
-    let take_rate = 500_000;
-    vault_client.set_fee_mode(&false, &take_rate);
+    let fee_mode = FeeMode::TakeRate(500_000);
+    vault_client.set_fee_mode(&fee_mode);
     assert_eq!(
         e.auths()[0],
         (
@@ -351,30 +485,29 @@ fn test_set_fee_mode() {
                 function: AuthorizedFunction::Contract((
                     vault_address.clone(),
                     Symbol::new(&e, "set_fee_mode"),
-                    vec![&e, false.into_val(&e), take_rate.into_val(&e),]
+                    vec![&e, fee_mode.into_val(&e)]
                 )),
                 sub_invocations: std::vec![]
             }
         )
     );
     e.as_contract(&vault_address, || {
-        let fee_mode = storage::get_fee_mode(&e);
-        assert_eq!(fee_mode.is_apr_capped, false);
-        assert_eq!(fee_mode.value, 500_000);
+        assert_eq!(storage::get_fee_mode(&e), fee_mode);
     });
     // Setting the value to 0 or 100% should be possible
-    vault_client.set_fee_mode(&true, &0);
+    vault_client.set_fee_mode(&FeeMode::AprCap(0));
+    e.as_contract(&vault_address, || {
+        assert_eq!(storage::get_fee_mode(&e), FeeMode::AprCap(0));
+    });
+
+    vault_client.set_fee_mode(&FeeMode::TakeRate(1_000_0000));
     e.as_contract(&vault_address, || {
-        let fee_mode = storage::get_fee_mode(&e);
-        assert_eq!(fee_mode.is_apr_capped, true);
-        assert_eq!(fee_mode.value, 0);
+        assert_eq!(storage::get_fee_mode(&e), FeeMode::TakeRate(1_000_0000));
     });
 
-    vault_client.set_fee_mode(&false, &1_000_0000);
+    vault_client.set_fee_mode(&FeeMode::Hybrid(1_000_0000, 0));
     e.as_contract(&vault_address, || {
-        let fee_mode = storage::get_fee_mode(&e);
-        assert_eq!(fee_mode.is_apr_capped, false);
-        assert_eq!(fee_mode.value, 1_000_0000);
+        assert_eq!(storage::get_fee_mode(&e), FeeMode::Hybrid(1_000_0000, 0));
     });
 }
 
@@ -395,15 +528,14 @@ fn test_ensure_b_rate_gets_update_pre_fee_mode_update() {
         Some((
             samwise.clone(),
             mock_client.address.clone(),
-            false,
-            0_1000000,
+            FeeMode::TakeRate(0_1000000),
         )),
     );
     let vault_client = FeeVaultClient::new(&e, &vault_address);
 
     // Add 2 reserves
-    vault_client.add_reserve_vault(&usdc);
-    vault_client.add_reserve_vault(&xlm);
+    vault_client.add_reserve_vault(&usdc, &false, &None);
+    vault_client.add_reserve_vault(&xlm, &false, &None);
     e.as_contract(&vault_address, || {
         // Ensure both reserves where added and set the total_b_tokens manually
         // to mock blend-interaction
@@ -422,6 +554,7 @@ fn test_ensure_b_rate_gets_update_pre_fee_mode_update() {
                 total_b_tokens: 1000_0000000,
                 total_shares: 1200_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             },
@@ -435,6 +568,7 @@ fn test_ensure_b_rate_gets_update_pre_fee_mode_update() {
                 total_b_tokens: 100_0000000,
                 total_shares: 100_0000000,
                 b_rate: init_b_rate,
+                high_water_b_rate: init_b_rate,
                 last_update_timestamp: e.ledger().timestamp(),
                 accrued_fees: 0,
             },
@@ -467,7 +601,7 @@ fn test_ensure_b_rate_gets_update_pre_fee_mode_update() {
     });
 
     // Admin tries to take advantage of that by setting the take_rate to 100% to claim all the fees.
-    vault_client.set_fee_mode(&false, &1_000_0000);
+    vault_client.set_fee_mode(&FeeMode::TakeRate(1_000_0000));
 
     // The previous action shouldn't affect any already accrued rewards
     let usdc_underlying_balance_after = vault_client.get_underlying_tokens(&usdc, &samwise);
@@ -508,7 +642,11 @@ fn test_set_admin() {
 
     let vault_address = register_fee_vault(
         &e,
-        Some((samwise.clone(), Address::generate(&e), true, 0_1000000)),
+        Some((
+            samwise.clone(),
+            Address::generate(&e),
+            FeeMode::AprCap(0_1000000),
+        )),
     );
     let vault_client = FeeVaultClient::new(&e, &vault_address);
 
@@ -562,6 +700,290 @@ fn test_set_admin() {
     );
 }
 
+#[test]
+fn test_propose_admin() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            Address::generate(&e),
+            FeeMode::AprCap(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // Nothing to accept or cancel without a proposal
+    assert_eq!(
+        vault_client.try_accept_admin().err(),
+        Some(Ok(Error::from_contract_error(120)))
+    );
+    assert_eq!(
+        vault_client.try_cancel_admin_proposal().err(),
+        Some(Ok(Error::from_contract_error(120)))
+    );
+
+    // Only the admin signs the proposal
+    let expiration_ledger = vault_client.propose_admin(&frodo);
+    assert_eq!(
+        expiration_ledger,
+        e.ledger().sequence() + ADMIN_PROPOSAL_LEDGERS
+    );
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "propose_admin"),
+                    vec![&e, frodo.into_val(&e)],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "admin_proposed").into_val(&e),
+            samwise.into_val(&e)
+        ]
+    );
+    let data: (Address, u32) = event.2.into_val(&e);
+    assert_eq!(data, (frodo.clone(), expiration_ledger));
+    assert_eq!(
+        vault_client.get_pending_admin(),
+        Some(PendingAdmin {
+            admin: frodo.clone(),
+            expiration_ledger,
+        })
+    );
+
+    // The admin can cancel the proposal
+    vault_client.cancel_admin_proposal();
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "admin_proposal_cancelled").into_val(&e),
+            samwise.into_val(&e)
+        ]
+    );
+    let data: Address = event.2.into_val(&e);
+    assert_eq!(data, frodo);
+    assert_eq!(vault_client.get_pending_admin(), None);
+
+    // The proposal can't be accepted once it expires
+    vault_client.propose_admin(&frodo);
+    e.jump(ADMIN_PROPOSAL_LEDGERS + 1);
+    assert_eq!(
+        vault_client.try_accept_admin().err(),
+        Some(Ok(Error::from_contract_error(121)))
+    );
+
+    // A new proposal replaces the old one, and only the proposed admin signs the acceptance
+    vault_client.propose_admin(&merry);
+    vault_client.accept_admin();
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            merry.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "accept_admin"),
+                    vec![&e],
+                )),
+                sub_invocations: std::vec![],
+            }
+        )]
+    );
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "admin_update").into_val(&e),
+            samwise.into_val(&e)
+        ]
+    );
+    let data: Address = event.2.into_val(&e);
+    assert_eq!(data, merry);
+    e.as_contract(&vault_address, || {
+        assert_eq!(storage::get_admin(&e), merry);
+    });
+    assert_eq!(vault_client.get_pending_admin(), None);
+}
+
+#[test]
+fn test_set_reserve_fee_mode() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+    let init_b_rate = 1_000_000_000_000;
+
+    let mock_client = mockpool::register_mock_pool_with_b_rate(&e, init_b_rate);
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            mock_client.address.clone(),
+            FeeMode::TakeRate(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // -> verify reserve fee modes are validated
+    assert_eq!(
+        vault_client
+            .try_add_reserve_vault(&usdc, &false, &Some(FeeMode::TakeRate(1_000_0001)))
+            .err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    assert_eq!(
+        vault_client
+            .try_set_reserve_fee_mode(&usdc, &Some(FeeMode::TakeRate(0_5000000)))
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    // usdc has its own fee mode and xlm uses the global fee mode
+    vault_client.add_reserve_vault(&usdc, &false, &Some(FeeMode::TakeRate(0_5000000)));
+    vault_client.add_reserve_vault(&xlm, &false, &None);
+    assert_eq!(
+        vault_client.get_fee_mode(&usdc),
+        FeeMode::TakeRate(0_5000000)
+    );
+    assert_eq!(
+        vault_client.get_fee_mode(&xlm),
+        FeeMode::TakeRate(0_1000000)
+    );
+
+    e.as_contract(&vault_address, || {
+        for reserve in [usdc.clone(), xlm.clone()] {
+            storage::set_reserve_vault(
+                &e,
+                &reserve,
+                &ReserveVault {
+                    address: reserve.clone(),
+                    total_b_tokens: 1000_0000000,
+                    total_shares: 1000_0000000,
+                    b_rate: init_b_rate,
+                    high_water_b_rate: init_b_rate,
+                    last_update_timestamp: e.ledger().timestamp(),
+                    accrued_fees: 0,
+                },
+            );
+        }
+    });
+
+    // The pool has doubled in value, but interest hasn't been accrued yet
+    let new_b_rate = 2_000_000_000_000;
+    mock_client.set_b_rate(&new_b_rate);
+    e.jump(5);
+
+    // Changing usdc's fee mode only accrues usdc, under its previous fee mode
+    vault_client.set_reserve_fee_mode(&usdc, &Some(FeeMode::AprCap(0_0500000)));
+    assert_eq!(
+        e.auths()[0],
+        (
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    vault_address.clone(),
+                    Symbol::new(&e, "set_reserve_fee_mode"),
+                    vec![
+                        &e,
+                        usdc.into_val(&e),
+                        Some(FeeMode::AprCap(0_0500000)).into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(vault_client.get_fee_mode(&usdc), FeeMode::AprCap(0_0500000));
+    e.as_contract(&vault_address, || {
+        // 50% of the 1000 bTokens worth of interest, at the new b_rate
+        let usdc_vault = storage::get_reserve_vault(&e, &usdc);
+        assert_eq!(usdc_vault.accrued_fees, 250_0000000);
+        assert_eq!(usdc_vault.b_rate, new_b_rate);
+
+        let xlm_vault = storage::get_reserve_vault(&e, &xlm);
+        assert_eq!(xlm_vault.accrued_fees, 0);
+        assert_eq!(xlm_vault.b_rate, init_b_rate);
+    });
+
+    // xlm accrues under the global fee mode
+    assert_eq!(
+        vault_client.get_reserve_vault(&xlm).accrued_fees,
+        50_0000000
+    );
+
+    // Removing usdc's fee mode falls back to the global fee mode
+    vault_client.set_reserve_fee_mode(&usdc, &None);
+    assert_eq!(
+        vault_client.get_fee_mode(&usdc),
+        FeeMode::TakeRate(0_1000000)
+    );
+}
+
+#[test]
+fn test_get_drawdown() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let usdc = Address::generate(&e);
+
+    let mock_client = mockpool::register_mock_pool_with_b_rate(&e, 1_000_000_000_000);
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            mock_client.address.clone(),
+            FeeMode::TakeRate(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // Non-existent reserves have no drawdown
+    assert_eq!(vault_client.get_drawdown(&usdc), 0);
+
+    vault_client.add_reserve_vault(&usdc, &false, &None);
+    assert_eq!(vault_client.get_drawdown(&usdc), 0);
+
+    // The b_rate falls 20% below the rate the reserve vault was added at
+    mock_client.set_b_rate(&800_000_000_000);
+    e.jump(5);
+    assert_eq!(vault_client.get_drawdown(&usdc), 0_2000000);
+
+    // The b_rate recovers past its high water mark
+    mock_client.set_b_rate(&1_100_000_000_000);
+    e.jump(5);
+    assert_eq!(vault_client.get_drawdown(&usdc), 0);
+    assert_eq!(
+        vault_client.get_reserve_vault(&usdc).high_water_b_rate,
+        1_100_000_000_000
+    );
+}
+
 #[test]
 fn test_add_reserve_vault() {
     let e = Env::default();
@@ -576,8 +998,7 @@ fn test_add_reserve_vault() {
         Some((
             samwise.clone(),
             mock_client.address.clone(),
-            false,
-            0_1000000,
+            FeeMode::TakeRate(0_1000000),
         )),
     );
 
@@ -594,7 +1015,7 @@ fn test_add_reserve_vault() {
         Some(Ok(Error::from_contract_error(100)))
     );
 
-    vault_client.add_reserve_vault(&reserve);
+    vault_client.add_reserve_vault(&reserve, &false, &None);
     assert_eq!(
         e.auths()[0],
         (
@@ -603,7 +1024,12 @@ fn test_add_reserve_vault() {
                 function: AuthorizedFunction::Contract((
                     vault_address.clone(),
                     Symbol::new(&e, "add_reserve_vault"),
-                    vec![&e, reserve.into_val(&e),]
+                    vec![
+                        &e,
+                        reserve.into_val(&e),
+                        false.into_val(&e),
+                        Option::<FeeMode>::None.into_val(&e),
+                    ]
                 )),
                 sub_invocations: std::vec![]
             }
@@ -619,6 +1045,8 @@ fn test_add_reserve_vault() {
     assert_eq!(reserve_info.b_rate, 1_100_000_000_000);
     assert_eq!(reserve_info.accrued_fees, 0);
 
+    assert!(!vault_client.is_collateral_vault(&reserve));
+
     e.as_contract(&vault_address, || {
         // The reserve should also be added to the reserves list
         assert_eq!(storage::get_reserves(&e), vec![&e, reserve.clone()]);
@@ -626,9 +1054,32 @@ fn test_add_reserve_vault() {
 
     // Trying to add a vault for the same reserve should fail
     assert_eq!(
-        vault_client.try_add_reserve_vault(&reserve).err(),
+        vault_client
+            .try_add_reserve_vault(&reserve, &false, &None)
+            .err(),
+        Some(Ok(Error::from_contract_error(101)))
+    );
+    assert_eq!(
+        vault_client
+            .try_add_reserve_vault(&reserve, &true, &None)
+            .err(),
         Some(Ok(Error::from_contract_error(101)))
     );
+
+    // Add a collateral reserve vault
+    let collateral_reserve = Address::generate(&e);
+    vault_client.add_reserve_vault(&collateral_reserve, &true, &None);
+    assert!(vault_client.is_collateral_vault(&collateral_reserve));
+    assert!(!vault_client.is_collateral_vault(&reserve));
+    // Non-existent reserves are not collateral vaults
+    assert!(!vault_client.is_collateral_vault(&Address::generate(&e)));
+
+    e.as_contract(&vault_address, || {
+        assert_eq!(
+            storage::get_reserves(&e),
+            vec![&e, reserve.clone(), collateral_reserve.clone()]
+        );
+    });
 }
 
 #[test]
@@ -655,20 +1106,235 @@ fn test_add_invalid_reserve() {
 
     let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
     let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
-    let fee_vault = create_fee_vault(&e, &bombadil, &pool, false, 100_0000);
+    let fee_vault = create_fee_vault(&e, &bombadil, &pool, &blnd, FeeMode::TakeRate(100_0000));
     let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
 
     // Adding an existent reserve should succeed
-    assert_eq!(fee_vault_client.try_add_reserve_vault(&usdc).is_ok(), true);
+    assert_eq!(
+        fee_vault_client
+            .try_add_reserve_vault(&usdc, &false, &None)
+            .is_ok(),
+        true
+    );
     // Adding the same reserve again should fail
     assert_eq!(
-        fee_vault_client.try_add_reserve_vault(&usdc).err(),
+        fee_vault_client
+            .try_add_reserve_vault(&usdc, &false, &None)
+            .err(),
         Some(Ok(Error::from_contract_error(101)))
     );
 
     // Adding a different reserve should also succeed
-    assert_eq!(fee_vault_client.try_add_reserve_vault(&xlm).is_ok(), true);
+    assert_eq!(
+        fee_vault_client
+            .try_add_reserve_vault(&xlm, &false, &None)
+            .is_ok(),
+        true
+    );
 
     // Adding a non-existent reserve should fail
-    fee_vault_client.add_reserve_vault(&Address::generate(&e));
+    fee_vault_client.add_reserve_vault(&Address::generate(&e), &false, &None);
+}
+
+#[test]
+fn test_share_token() {
+    use std::string::ToString;
+
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let reserve = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let reserve_client = MockTokenClient::new(&e, &reserve);
+
+    let mock_client = mockpool::register_mock_pool_with_b_rate(&e, 1_000_000_000_000);
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            mock_client.address.clone(),
+            FeeMode::TakeRate(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // Share tokens are only deployed for reserve vaults
+    assert_eq!(
+        vault_client.try_get_share_token(&reserve).err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    vault_client.add_reserve_vault(&reserve, &false, &None);
+    e.as_contract(&vault_address, || {
+        let mut reserve_vault = storage::get_reserve_vault(&e, &reserve);
+        reserve_vault.total_b_tokens = 100_0000000;
+        reserve_vault.total_shares = 100_0000000;
+        storage::set_reserve_vault(&e, &reserve, &reserve_vault);
+        storage::set_reserve_vault_shares(&e, &reserve, &samwise, 100_0000000);
+    });
+    let share_token = vault_client.get_share_token(&reserve);
+    let share_token_client = ShareTokenClient::new(&e, &share_token);
+    assert_eq!(share_token_client.vault(), vault_address);
+    assert_eq!(share_token_client.reserve(), reserve);
+
+    // -> verify metadata is derived from the reserve
+    assert_eq!(share_token_client.decimals(), reserve_client.decimals());
+    let mut name = std::string::String::from("Fee Vault ");
+    name.push_str(&reserve_client.name().to_string());
+    assert_eq!(share_token_client.name().to_string(), name);
+    let mut symbol = std::string::String::from("fv");
+    symbol.push_str(&reserve_client.symbol().to_string());
+    assert_eq!(share_token_client.symbol().to_string(), symbol);
+
+    // -> verify transfer
+    share_token_client.transfer(&samwise, &frodo, &40_0000000);
+    assert_eq!(
+        e.auths()[0],
+        (
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    share_token.clone(),
+                    Symbol::new(&e, "transfer"),
+                    vec![
+                        &e,
+                        samwise.to_val(),
+                        frodo.to_val(),
+                        40_0000000i128.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, share_token.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "transfer").into_val(&e),
+            samwise.into_val(&e),
+            frodo.into_val(&e)
+        ]
+    );
+    let event_data: i128 = event.2.into_val(&e);
+    assert_eq!(event_data, 40_0000000);
+    assert_eq!(share_token_client.balance(&samwise), 60_0000000);
+    assert_eq!(share_token_client.balance(&frodo), 40_0000000);
+    assert_eq!(vault_client.get_shares(&reserve, &frodo), 40_0000000);
+    assert_eq!(
+        share_token_client
+            .try_transfer(&frodo, &samwise, &40_0000001)
+            .err(),
+        Some(Ok(Error::from_contract_error(10)))
+    );
+
+    // -> verify approve and transfer_from
+    let expiration_ledger = e.ledger().sequence() + 1000;
+    share_token_client.approve(&frodo, &merry, &30_0000000, &expiration_ledger);
+    assert_eq!(
+        e.auths()[0],
+        (
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    share_token.clone(),
+                    Symbol::new(&e, "approve"),
+                    vec![
+                        &e,
+                        frodo.to_val(),
+                        merry.to_val(),
+                        30_0000000i128.into_val(&e),
+                        expiration_ledger.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(share_token_client.allowance(&frodo, &merry), 30_0000000);
+
+    share_token_client.transfer_from(&merry, &frodo, &merry, &20_0000000);
+    assert_eq!(
+        e.auths()[0],
+        (
+            merry.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    share_token.clone(),
+                    Symbol::new(&e, "transfer_from"),
+                    vec![
+                        &e,
+                        merry.to_val(),
+                        frodo.to_val(),
+                        merry.to_val(),
+                        20_0000000i128.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )
+    );
+    assert_eq!(share_token_client.allowance(&frodo, &merry), 10_0000000);
+    assert_eq!(share_token_client.balance(&frodo), 20_0000000);
+    assert_eq!(share_token_client.balance(&merry), 20_0000000);
+
+    assert_eq!(
+        share_token_client
+            .try_transfer_from(&merry, &frodo, &merry, &10_0000001)
+            .err(),
+        Some(Ok(Error::from_contract_error(9)))
+    );
+
+    // -> verify an allowance over the owner's balance still fails on balance
+    share_token_client.approve(&frodo, &merry, &100_0000000, &expiration_ledger);
+    assert_eq!(
+        share_token_client
+            .try_transfer_from(&merry, &frodo, &merry, &20_0000001)
+            .err(),
+        Some(Ok(Error::from_contract_error(10)))
+    );
+
+    // -> verify the new owner can withdraw against the vault as normal
+    let vault = vault_client.get_reserve_vault(&reserve);
+    assert_eq!(
+        vault_client.get_b_tokens(&reserve, &merry),
+        vault.shares_to_b_tokens_down(20_0000000)
+    );
+
+    // -> verify burning leaves the bTokens to the remaining shares
+    share_token_client.burn(&samwise, &10_0000000);
+    assert_eq!(e.auths()[0].0, samwise);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "burn").into_val(&e),
+            samwise.into_val(&e)
+        ]
+    );
+    share_token_client.burn_from(&merry, &frodo, &10_0000000);
+    assert_eq!(share_token_client.allowance(&frodo, &merry), 90_0000000);
+    assert_eq!(share_token_client.balance(&samwise), 50_0000000);
+    assert_eq!(share_token_client.balance(&frodo), 10_0000000);
+    let vault = vault_client.get_reserve_vault(&reserve);
+    assert_eq!(vault.total_shares, 80_0000000);
+    assert_eq!(vault.total_b_tokens, 100_0000000);
+
+    // -> verify shares can only be moved through the share token
+    e.set_auths(&[]);
+    assert!(vault_client
+        .try_share_transfer(&reserve, &samwise, &frodo, &1_0000000)
+        .is_err());
+    assert!(vault_client
+        .try_share_burn(&reserve, &samwise, &1_0000000)
+        .is_err());
 }
@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use crate::constants::{SCALAR_12, SCALAR_7};
-use crate::storage::ONE_DAY_LEDGERS;
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
 use crate::testutils::{assert_approx_eq_rel, create_blend_pool, create_fee_vault, EnvTestUtils};
 use crate::FeeVaultClient;
 use blend_contract_sdk::pool::{Client as PoolClient, Request};
@@ -53,12 +53,12 @@ fn test_fee_accrual() {
     // emits to each reserve token evently, and starts emissions
     let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
     let pool_client = PoolClient::new(&e, &pool);
-    let fee_vault = create_fee_vault(&e, &bombadil, &pool, false, 100_0000);
+    let fee_vault = create_fee_vault(&e, &bombadil, &pool, &blnd, FeeMode::TakeRate(100_0000));
     let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
 
-    fee_vault_client.add_reserve_vault(&usdc);
-    fee_vault_client.add_reserve_vault(&xlm);
-    fee_vault_client.set_fee_mode(&false, &0_1000000);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+    fee_vault_client.set_fee_mode(&FeeMode::TakeRate(0_1000000));
 
     // Setup pool util rate
     // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
@@ -380,14 +380,14 @@ fn test_fee_accrual_capped_rate() {
     // emits to each reserve token evently, and starts emissions
     let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
     let pool_client = PoolClient::new(&e, &pool);
-    let fee_vault = create_fee_vault(&e, &bombadil, &pool, true, 0_0500000);
+    let fee_vault = create_fee_vault(&e, &bombadil, &pool, &blnd, FeeMode::AprCap(0_0500000));
     let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
 
-    fee_vault_client.add_reserve_vault(&usdc);
-    fee_vault_client.add_reserve_vault(&xlm);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
 
     // set fee mode to capped rate @ 5%
-    fee_vault_client.set_fee_mode(&true, &0_0500000);
+    fee_vault_client.set_fee_mode(&FeeMode::AprCap(0_0500000));
 
     // Setup pool util rate
     // -> USDC: deposit 200k tokens and borrow 120k tokens for 60% util rate, and 6% effective supply rate
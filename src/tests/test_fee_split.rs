@@ -0,0 +1,185 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, FeeRecipient, FeeRecipients, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_distribute_fees() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let treasury = Address::generate(&e);
+    let partner = Address::generate(&e);
+    let foundation = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo deposits 10k tokens into the vault and fees accrue for a week
+    usdc_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &10_000_0000000);
+    e.jump(7 * ONE_DAY_LEDGERS);
+
+    // -> verify fees can't be distributed without recipients
+    assert_eq!(
+        fee_vault_client.try_distribute_fees(&usdc).err(),
+        Some(Ok(Error::from_contract_error(123)))
+    );
+
+    /*
+     * Set the fee recipients
+     */
+    let recipient = |address: &Address, weight: u32| FeeRecipient {
+        address: address.clone(),
+        weight,
+    };
+    // -> verify the weights must sum to 100%, be positive, and not repeat a recipient
+    for recipients in [
+        Vec::new(&e),
+        vec![&e, recipient(&treasury, 5000), recipient(&partner, 3000)],
+        vec![
+            &e,
+            recipient(&treasury, 5000),
+            recipient(&partner, 5000),
+            recipient(&foundation, 0),
+        ],
+        vec![&e, recipient(&treasury, 5000), recipient(&treasury, 5000)],
+    ] {
+        assert_eq!(
+            fee_vault_client
+                .try_set_fee_recipients(&recipients, &treasury)
+                .err(),
+            Some(Ok(Error::from_contract_error(122)))
+        );
+    }
+
+    let recipients = vec![
+        &e,
+        recipient(&treasury, 5000),
+        recipient(&partner, 3333),
+        recipient(&foundation, 1667),
+    ];
+    fee_vault_client.set_fee_recipients(&recipients, &treasury);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let fee_recipients = FeeRecipients {
+        recipients,
+        dust_recipient: treasury.clone(),
+    };
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![&e, Symbol::new(&e, "fee_recipients_update").into_val(&e)]
+    );
+    let data: FeeRecipients = event.2.into_val(&e);
+    assert_eq!(data, fee_recipients);
+    assert_eq!(
+        fee_vault_client.get_fee_recipients(),
+        Some(fee_recipients.clone())
+    );
+
+    /*
+     * Distribute the fees
+     */
+    let fees = fee_vault_client.get_collected_fees(&usdc);
+    assert!(fees > 0);
+    let accrued_b_tokens = fee_vault_client.get_reserve_vault(&usdc).accrued_fees;
+
+    // anyone can distribute fees
+    let b_tokens_burnt = fee_vault_client.mock_auths(&[]).distribute_fees(&usdc);
+    assert_eq!(b_tokens_burnt, accrued_b_tokens);
+
+    let partner_fees = fees * 3333 / 10000;
+    let foundation_fees = fees * 1667 / 10000;
+    let treasury_fees = fees - partner_fees - foundation_fees;
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "vault_fee_distribution").into_val(&e),
+            usdc.into_val(&e)
+        ]
+    );
+    let data: (Vec<(Address, i128)>, i128) = event.2.into_val(&e);
+    assert_eq!(
+        data,
+        (
+            vec![
+                &e,
+                (treasury.clone(), treasury_fees),
+                (partner.clone(), partner_fees),
+                (foundation.clone(), foundation_fees),
+            ],
+            b_tokens_burnt
+        )
+    );
+
+    assert_eq!(usdc_client.balance(&treasury), treasury_fees);
+    assert_eq!(usdc_client.balance(&partner), partner_fees);
+    assert_eq!(usdc_client.balance(&foundation), foundation_fees);
+    assert_eq!(usdc_client.balance(&fee_vault), 0);
+    assert_eq!(fee_vault_client.get_reserve_vault(&usdc).accrued_fees, 0);
+
+    // -> verify there is nothing left to distribute
+    assert_eq!(
+        fee_vault_client.try_distribute_fees(&usdc).err(),
+        Some(Ok(Error::from_contract_error(103)))
+    );
+
+    // -> verify changing the fee recipients is timelocked once a delay is set
+    fee_vault_client.set_config_delay(&(24 * 60 * 60));
+    assert_eq!(
+        fee_vault_client
+            .try_set_fee_recipients(&vec![&e, recipient(&partner, 10000)], &partner)
+            .err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+}
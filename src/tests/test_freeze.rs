@@ -0,0 +1,235 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, PauseState, Role, ONE_DAY_LEDGERS};
+use crate::testutils::share_token_wasm::Client as ShareTokenClient;
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_freeze_and_force_exit() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let elrond = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let escrow = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    let share_token_client = ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&usdc));
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+    fee_vault_client.grant_role(&Role::Compliance, &elrond);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens of usdc for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo and samwise deposit and earn interest for a day
+    usdc_client.mint(&frodo, &10_000_0000000);
+    usdc_client.mint(&samwise, &10_000_0000000);
+    xlm_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &1_000_0000000);
+    fee_vault_client.deposit(&usdc, &samwise, &1_000_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &1_000_0000000);
+    e.jump(ONE_DAY_LEDGERS);
+
+    // -> verify only frozen positions can be forced out
+    assert_eq!(
+        fee_vault_client
+            .try_force_exit(&usdc, &frodo, &escrow, &1)
+            .err(),
+        Some(Ok(Error::from_contract_error(132)))
+    );
+
+    /*
+     * Freeze frodo's USDC position
+     */
+    fee_vault_client.freeze(&usdc, &frodo, &1);
+    assert_eq!(e.auths()[0].0, elrond);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "user_frozen").into_val(&e),
+            usdc.into_val(&e),
+            frodo.into_val(&e)
+        ]
+    );
+    let data: u32 = event.2.into_val(&e);
+    assert_eq!(data, 1);
+    assert!(fee_vault_client.is_frozen(&usdc, &frodo));
+    assert!(!fee_vault_client.is_frozen(&xlm, &frodo));
+    assert_eq!(
+        fee_vault_client.try_freeze(&usdc, &frodo, &1).err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+
+    // -> verify frodo can't move their USDC position
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        fee_vault_client.try_withdraw_all(&usdc, &frodo).err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_many(&frodo, &vec![&e, (usdc.clone(), 1_0000000)])
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        share_token_client
+            .try_transfer(&frodo, &samwise, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        share_token_client
+            .try_transfer(&samwise, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_for(&usdc, &samwise, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+
+    assert_eq!(
+        fee_vault_client
+            .try_claim_user_emissions(&usdc, &frodo, &frodo)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_convert_fees_to_shares(&usdc, &frodo)
+            .err(),
+        Some(Ok(Error::from_contract_error(131)))
+    );
+
+    // -> verify frodo's other positions and other users are unaffected
+    fee_vault_client.withdraw(&xlm, &frodo, &1_0000000);
+    fee_vault_client.claim_user_emissions(&xlm, &frodo, &frodo);
+    fee_vault_client.withdraw(&usdc, &samwise, &1_0000000);
+
+    /*
+     * Force frodo out of the USDC vault
+     * -> the USDC vault and the whole vault are paused, which does not block a forced exit
+     */
+    fee_vault_client.pause(&Some(usdc.clone()), &PauseState::Paused);
+    fee_vault_client.pause(&None, &PauseState::Paused);
+    let frodo_shares = fee_vault_client.get_shares(&usdc, &frodo);
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&usdc, &frodo);
+    let frodo_balance = usdc_client.balance(&frodo);
+
+    let action = fee_vault_client.force_exit(&usdc, &frodo, &escrow, &7);
+    assert_eq!(e.auths()[0].0, elrond);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "forced_exit").into_val(&e),
+            usdc.into_val(&e),
+            frodo.into_val(&e)
+        ]
+    );
+    let data: (Address, u32, i128, i128) = event.2.into_val(&e);
+    assert_eq!(data, (escrow.clone(), 7, frodo_underlying, frodo_shares));
+    assert_eq!(action.shares, frodo_shares);
+    assert_eq!(action.underlying, frodo_underlying);
+    assert_eq!(usdc_client.balance(&escrow), frodo_underlying);
+    assert_eq!(usdc_client.balance(&frodo), frodo_balance);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &frodo), 0);
+    assert!(fee_vault_client.is_frozen(&usdc, &frodo));
+    fee_vault_client.unpause(&Some(usdc.clone()), &PauseState::Active);
+    fee_vault_client.unpause(&None, &PauseState::Active);
+
+    // -> verify there is nothing left to force out
+    assert_eq!(
+        fee_vault_client
+            .try_force_exit(&usdc, &frodo, &escrow, &7)
+            .err(),
+        Some(Ok(Error::from_contract_error(10)))
+    );
+
+    /*
+     * Unfreeze frodo
+     */
+    fee_vault_client.unfreeze(&usdc, &frodo, &2);
+    assert_eq!(e.auths()[0].0, elrond);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "user_unfrozen").into_val(&e),
+            usdc.into_val(&e),
+            frodo.into_val(&e)
+        ]
+    );
+    let data: u32 = event.2.into_val(&e);
+    assert_eq!(data, 2);
+    assert!(!fee_vault_client.is_frozen(&usdc, &frodo));
+    assert_eq!(
+        fee_vault_client.try_unfreeze(&usdc, &frodo, &2).err(),
+        Some(Ok(Error::from_contract_error(132)))
+    );
+    fee_vault_client.deposit(&usdc, &frodo, &1_0000000);
+}
@@ -1,7 +1,7 @@
 #![cfg(test)]
 
 use crate::constants::SCALAR_7;
-use crate::storage::ONE_DAY_LEDGERS;
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
 use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
 use crate::FeeVaultClient;
 use blend_contract_sdk::pool::{Client as PoolClient, Request};
@@ -43,7 +43,7 @@ fn test_happy_path() {
     // emits to each reserve token evently, and starts emissions
     let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
     let pool_client = PoolClient::new(&e, &pool);
-    let fee_vault = create_fee_vault(&e, &bombadil, &pool, false, 100_0000);
+    let fee_vault = create_fee_vault(&e, &bombadil, &pool, &blnd, FeeMode::TakeRate(100_0000));
     let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
 
     // Setup pool util rate
@@ -75,7 +75,7 @@ fn test_happy_path() {
         .mock_all_auths()
         .submit(&bombadil, &bombadil, &bombadil, &requests);
 
-    fee_vault_client.add_reserve_vault(&usdc);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
     // -> verify add reserve vault auth
     assert_eq!(
         e.auths()[0],
@@ -85,7 +85,12 @@ fn test_happy_path() {
                 function: AuthorizedFunction::Contract((
                     fee_vault.clone(),
                     Symbol::new(&e, "add_reserve_vault"),
-                    vec![&e, usdc.to_val(),]
+                    vec![
+                        &e,
+                        usdc.to_val(),
+                        false.into_val(&e),
+                        Option::<FeeMode>::None.into_val(&e),
+                    ]
                 )),
                 sub_invocations: std::vec![]
             }
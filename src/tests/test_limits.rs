@@ -0,0 +1,158 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{vec, Address, Env, Error};
+
+#[test]
+fn test_slippage_and_deadline_limits() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    let starting_balance = 1_000_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    let deposit_amount = 100_0000000;
+    fee_vault_client.deposit(&usdc, &frodo, &deposit_amount);
+
+    /*
+     * Deposit with limits
+     * -> frodo previews a deposit, but it executes after a week of interest accrues
+     * -> the stale preview mints fewer shares than expected
+     */
+    let stale_shares = fee_vault_client.preview_deposit(&usdc, &deposit_amount);
+    e.jump(ONE_DAY_LEDGERS * 7);
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_with_limits(&usdc, &frodo, &deposit_amount, &Some(stale_shares), &None)
+            .err(),
+        Some(Ok(Error::from_contract_error(110)))
+    );
+
+    // -> verify the deadline is enforced
+    let now = e.ledger().timestamp();
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_with_limits(&usdc, &frodo, &deposit_amount, &None, &Some(now - 1))
+            .err(),
+        Some(Ok(Error::from_contract_error(112)))
+    );
+
+    let preview_shares = fee_vault_client.preview_deposit(&usdc, &deposit_amount);
+    let frodo_shares = fee_vault_client.get_shares(&usdc, &frodo);
+    let shares = fee_vault_client.deposit_with_limits(
+        &usdc,
+        &frodo,
+        &deposit_amount,
+        &Some(preview_shares),
+        &Some(now),
+    );
+    assert_eq!(shares, preview_shares);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &frodo),
+        frodo_shares + shares
+    );
+    assert_eq!(
+        usdc_client.balance(&frodo),
+        starting_balance - deposit_amount * 2
+    );
+
+    /*
+     * Withdraw with limits
+     * -> verify withdrawals that burn more than max_shares_in fail
+     * -> verify the deadline is enforced
+     */
+    let withdraw_amount = 50_0000000;
+    let preview_shares = fee_vault_client.preview_withdraw(&usdc, &withdraw_amount);
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_with_limits(
+                &usdc,
+                &frodo,
+                &withdraw_amount,
+                &Some(preview_shares - 1),
+                &None
+            )
+            .err(),
+        Some(Ok(Error::from_contract_error(111)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_with_limits(&usdc, &frodo, &withdraw_amount, &None, &Some(now - 1))
+            .err(),
+        Some(Ok(Error::from_contract_error(112)))
+    );
+
+    let frodo_shares = fee_vault_client.get_shares(&usdc, &frodo);
+    let shares = fee_vault_client.withdraw_with_limits(
+        &usdc,
+        &frodo,
+        &withdraw_amount,
+        &Some(preview_shares),
+        &Some(now),
+    );
+    assert_eq!(shares, preview_shares);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &frodo),
+        frodo_shares - shares
+    );
+    assert_eq!(
+        usdc_client.balance(&frodo),
+        starting_balance - deposit_amount * 2 + withdraw_amount
+    );
+
+    // -> verify the limits are optional
+    fee_vault_client.deposit_with_limits(&usdc, &frodo, &deposit_amount, &None, &None);
+    fee_vault_client.withdraw_with_limits(&usdc, &frodo, &withdraw_amount, &None, &None);
+}
@@ -0,0 +1,148 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env, Error};
+
+#[test]
+fn test_mint_redeem() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    // -> verify the share priced functions validate their inputs
+    assert_eq!(
+        fee_vault_client.try_mint(&usdc, &frodo, &0).err(),
+        Some(Ok(Error::from_contract_error(102)))
+    );
+    assert_eq!(
+        fee_vault_client.try_redeem(&usdc, &frodo, &0).err(),
+        Some(Ok(Error::from_contract_error(102)))
+    );
+    assert_eq!(
+        fee_vault_client.try_withdraw_all(&usdc, &frodo).err(),
+        Some(Ok(Error::from_contract_error(10)))
+    );
+    assert_eq!(
+        fee_vault_client.try_mint(&xlm, &frodo, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    /*
+     * Mint shares
+     * -> frodo deposits 100 tokens
+     * -> samwise mints the same number of shares as frodo after a week of interest
+     */
+    let starting_balance = 100_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    let frodo_shares = fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    usdc_client.mint(&samwise, &(starting_balance * 2));
+    let mint_result = fee_vault_client.mint(&usdc, &samwise, &frodo_shares);
+    assert_eq!(mint_result.shares, frodo_shares);
+    assert!(mint_result.underlying > starting_balance);
+    assert_eq!(
+        usdc_client.balance(&samwise),
+        starting_balance * 2 - mint_result.underlying
+    );
+    assert_eq!(fee_vault_client.get_shares(&usdc, &samwise), frodo_shares);
+
+    // -> verify the vault's pool position covers the bTokens credited to the vault
+    let reserve_vault = fee_vault_client.get_reserve_vault(&usdc);
+    let vault_positions = pool_client.get_positions(&fee_vault);
+    assert!(
+        vault_positions.supply.get(0).unwrap_optimized()
+            >= reserve_vault.total_b_tokens + reserve_vault.accrued_fees
+    );
+
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    /*
+     * Redeem shares
+     * -> verify an over redeem fails
+     * -> frodo redeems half of their shares
+     * -> frodo withdraws the rest of their position without leaving dust
+     */
+    assert_eq!(
+        fee_vault_client
+            .try_redeem(&usdc, &frodo, &(frodo_shares + 1))
+            .err(),
+        Some(Ok(Error::from_contract_error(10)))
+    );
+
+    let redeem_result = fee_vault_client.redeem(&usdc, &frodo, &(frodo_shares / 2));
+    assert_eq!(redeem_result.shares, frodo_shares / 2);
+    assert_eq!(usdc_client.balance(&frodo), redeem_result.underlying);
+
+    let frodo_underlying = fee_vault_client.get_underlying_tokens(&usdc, &frodo);
+    let withdraw_all_result = fee_vault_client.withdraw_all(&usdc, &frodo);
+    assert_eq!(withdraw_all_result.shares, frodo_shares - frodo_shares / 2);
+    assert_eq!(withdraw_all_result.underlying, frodo_underlying);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &frodo), 0);
+    assert_eq!(
+        usdc_client.balance(&frodo),
+        redeem_result.underlying + withdraw_all_result.underlying
+    );
+    assert!(usdc_client.balance(&frodo) > starting_balance);
+
+    // samwise exits fully, leaving only the admin fees in the vault
+    fee_vault_client.withdraw_all(&usdc, &samwise);
+    let reserve_vault = fee_vault_client.get_reserve_vault(&usdc);
+    assert_eq!(reserve_vault.total_shares, 0);
+    assert_eq!(reserve_vault.total_b_tokens, 0);
+    assert!(reserve_vault.accrued_fees > 0);
+}
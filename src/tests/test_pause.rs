@@ -0,0 +1,244 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, PauseState, Role, ONE_DAY_LEDGERS};
+use crate::testutils::share_token_wasm::Client as ShareTokenClient;
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_pause_states() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let guardian = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+    fee_vault_client.grant_role(&Role::Guardian, &guardian);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens of each reserve for a 50% util rate
+    let mut requests = Vec::new(&e);
+    for reserve in [usdc.clone(), xlm.clone()] {
+        requests.push_back(Request {
+            address: reserve.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        });
+        requests.push_back(Request {
+            address: reserve,
+            amount: 100_000_0000000,
+            request_type: 4,
+        });
+    }
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo deposits into both vaults and fees accrue for a day
+    usdc_client.mint(&frodo, &10_000_0000000);
+    xlm_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &1_000_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &1_000_0000000);
+    e.jump(ONE_DAY_LEDGERS);
+
+    assert_eq!(fee_vault_client.get_pause_state(&None), PauseState::Active);
+
+    /*
+     * Block USDC deposits
+     */
+    fee_vault_client.pause(&Some(usdc.clone()), &PauseState::DepositsBlocked);
+    assert_eq!(e.auths()[0].0, guardian);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "pause_state_update").into_val(&e),
+            guardian.into_val(&e)
+        ]
+    );
+    let data: (Option<Address>, PauseState) = event.2.into_val(&e);
+    assert_eq!(data, (Some(usdc.clone()), PauseState::DepositsBlocked));
+    assert_eq!(
+        fee_vault_client.get_pause_state(&Some(usdc.clone())),
+        PauseState::DepositsBlocked
+    );
+
+    // -> verify the guardian can only make the pause state stricter
+    assert_eq!(
+        fee_vault_client
+            .try_pause(&Some(usdc.clone()), &PauseState::DepositsBlocked)
+            .err(),
+        Some(Ok(Error::from_contract_error(127)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_pause(&Some(Address::generate(&e)), &PauseState::Paused)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    // -> verify only USDC deposits are blocked
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit_many(
+                &frodo,
+                &vec![&e, (xlm.clone(), 1_0000000), (usdc.clone(), 1_0000000)]
+            )
+            .err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+    fee_vault_client.withdraw(&usdc, &frodo, &1_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &1_0000000);
+    fee_vault_client.claim_fees(&usdc, &gandalf);
+
+    /*
+     * Make the whole vault withdraw only
+     */
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.pause(&None, &PauseState::WithdrawOnly);
+    assert_eq!(e.auths()[0].0, guardian);
+    assert_eq!(
+        fee_vault_client.get_pause_state(&None),
+        PauseState::WithdrawOnly
+    );
+
+    // -> verify the stricter of the vault and reserve states applies
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(126)))
+    );
+    assert_eq!(
+        fee_vault_client.try_mint(&xlm, &frodo, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(126)))
+    );
+    assert_eq!(
+        fee_vault_client.try_claim_fees(&xlm, &gandalf).err(),
+        Some(Ok(Error::from_contract_error(126)))
+    );
+    // -> verify claiming all fees skips reserve vaults with fee claims paused
+    assert_eq!(
+        fee_vault_client.claim_all_fees(&gandalf),
+        Vec::<(Address, i128)>::new(&e)
+    );
+    fee_vault_client.withdraw(&usdc, &frodo, &1_0000000);
+    fee_vault_client.withdraw(&xlm, &frodo, &1_0000000);
+
+    /*
+     * Fully pause XLM
+     */
+    fee_vault_client.pause(&Some(xlm.clone()), &PauseState::Paused);
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw(&xlm, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(124)))
+    );
+    assert_eq!(
+        fee_vault_client.try_withdraw_all(&xlm, &frodo).err(),
+        Some(Ok(Error::from_contract_error(124)))
+    );
+    assert_eq!(
+        fee_vault_client.try_deposit(&xlm, &frodo, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(124)))
+    );
+    // -> verify shares can't be transferred or burnt
+    let xlm_share_token_client = ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&xlm));
+    assert_eq!(
+        xlm_share_token_client
+            .try_transfer(&frodo, &samwise, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(124)))
+    );
+    assert_eq!(
+        xlm_share_token_client.try_burn(&frodo, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(124)))
+    );
+    fee_vault_client.withdraw(&usdc, &frodo, &1_0000000);
+    let usdc_share_token_client =
+        ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&usdc));
+    usdc_share_token_client.transfer(&frodo, &samwise, &1_0000000);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &samwise), 1_0000000);
+
+    /*
+     * Unpause
+     */
+    // -> verify the admin can only make the pause state less strict
+    assert_eq!(
+        fee_vault_client
+            .try_unpause(&Some(xlm.clone()), &PauseState::Paused)
+            .err(),
+        Some(Ok(Error::from_contract_error(127)))
+    );
+
+    fee_vault_client.unpause(&Some(xlm.clone()), &PauseState::Active);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "pause_state_update").into_val(&e),
+            gandalf.into_val(&e)
+        ]
+    );
+    let data: (Option<Address>, PauseState) = event.2.into_val(&e);
+    assert_eq!(data, (Some(xlm.clone()), PauseState::Active));
+
+    // -> verify XLM is back to the vault's withdraw only state
+    fee_vault_client.withdraw(&xlm, &frodo, &1_0000000);
+    assert_eq!(
+        fee_vault_client.try_deposit(&xlm, &frodo, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(126)))
+    );
+
+    fee_vault_client.unpause(&None, &PauseState::Active);
+    assert_eq!(e.auths()[0].0, gandalf);
+    fee_vault_client.deposit(&xlm, &frodo, &1_0000000);
+    fee_vault_client.claim_all_fees(&gandalf);
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(125)))
+    );
+}
@@ -0,0 +1,218 @@
+#![cfg(test)]
+
+use crate::constants::{SCALAR_12, SCALAR_7};
+use crate::storage::{FeeMode, PauseState, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_fixed_point_math::FixedPoint;
+use soroban_sdk::testutils::Address as _;
+use soroban_sdk::{unwrap::UnwrapOptimized, vec, Address, Env};
+
+#[test]
+fn test_previews_and_limits() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    // and have a max_util of 90%
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    // -> verify max_deposit is limited by the supply cap
+    let pool_reserve = pool_client.get_reserve(&usdc);
+    let total_supply = pool_reserve
+        .data
+        .b_supply
+        .fixed_mul_ceil(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    assert_eq!(
+        fee_vault_client.max_deposit(&usdc, &frodo),
+        pool_reserve.config.supply_cap - total_supply
+    );
+
+    // -> verify previews don't panic against an empty vault, where shares are 1:1 with bTokens
+    assert_eq!(
+        fee_vault_client.preview_redeem(&usdc, &1_000_0000000),
+        1_000_0000000
+            .fixed_mul_floor(pool_reserve.data.b_rate, SCALAR_12)
+            .unwrap_optimized()
+    );
+    assert_eq!(
+        fee_vault_client.preview_mint(&usdc, &1_000_0000000),
+        1_000_0000000
+            .fixed_mul_ceil(pool_reserve.data.b_rate, SCALAR_12)
+            .unwrap_optimized()
+    );
+
+    // -> verify max_deposit is limited by the vault's deposit caps
+    fee_vault_client.set_deposit_caps(&usdc, &Some(5_000_0000000), &Some(1_000_0000000));
+    assert_eq!(fee_vault_client.max_deposit(&usdc, &frodo), 1_000_0000000);
+    fee_vault_client.set_deposit_caps(&usdc, &None, &None);
+
+    // -> verify max_deposit is 0 when the user can't deposit
+    fee_vault_client.pause(&Some(usdc.clone()), &PauseState::DepositsBlocked);
+    assert_eq!(fee_vault_client.max_deposit(&usdc, &frodo), 0);
+    fee_vault_client.unpause(&Some(usdc.clone()), &PauseState::Active);
+    fee_vault_client.set_allowlist_enabled(&true);
+    assert_eq!(fee_vault_client.max_deposit(&usdc, &frodo), 0);
+    fee_vault_client.set_allowlist_enabled(&false);
+    fee_vault_client.freeze(&usdc, &frodo, &1);
+    assert_eq!(fee_vault_client.max_deposit(&usdc, &frodo), 0);
+    assert!(fee_vault_client.max_deposit(&usdc, &samwise) > 0);
+    fee_vault_client.unfreeze(&usdc, &frodo, &1);
+
+    /*
+     * Verify previews match the executed actions
+     * -> frodo and samwise deposit 10k tokens
+     * -> a week passes
+     * -> frodo withdraws, redeems and mints
+     */
+    let starting_balance = 10_000_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    usdc_client.mint(&samwise, &starting_balance);
+
+    let preview_shares = fee_vault_client.preview_deposit(&usdc, &starting_balance);
+    let shares = fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+    assert_eq!(preview_shares, shares);
+    fee_vault_client.deposit(&usdc, &samwise, &starting_balance);
+
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    let withdraw_amount = 1_000_0000000;
+    let preview_shares = fee_vault_client.preview_withdraw(&usdc, &withdraw_amount);
+    let shares = fee_vault_client.withdraw(&usdc, &frodo, &withdraw_amount);
+    assert_eq!(preview_shares, shares);
+
+    let redeem_shares = 1_000_0000000;
+    let preview_underlying = fee_vault_client.preview_redeem(&usdc, &redeem_shares);
+    let result = fee_vault_client.redeem(&usdc, &frodo, &redeem_shares);
+    assert_eq!(preview_underlying, result.underlying);
+
+    let preview_underlying = fee_vault_client.preview_mint(&usdc, &redeem_shares);
+    let result = fee_vault_client.mint(&usdc, &frodo, &redeem_shares);
+    assert_eq!(preview_underlying, result.underlying);
+
+    // -> verify max_withdraw is the user's full position when the pool has liquidity
+    assert_eq!(
+        fee_vault_client.max_withdraw(&usdc, &frodo),
+        fee_vault_client.get_underlying_tokens(&usdc, &frodo)
+    );
+
+    /*
+     * Verify max_withdraw is limited by pool liquidity
+     * -> merry borrows usdc against xlm collateral until the pool is near max_util
+     * -> samwise can only withdraw the liquidity left under max_util
+     */
+    let merry_collateral = 10_000_000_0000000;
+    xlm_client.mint(&merry, &merry_collateral);
+    let pool_reserve = pool_client.get_reserve(&usdc);
+    let total_supply = pool_reserve
+        .data
+        .b_supply
+        .fixed_mul_floor(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    let total_liabilities = pool_reserve
+        .data
+        .d_supply
+        .fixed_mul_ceil(pool_reserve.data.d_rate, SCALAR_12)
+        .unwrap_optimized();
+    // leave 4.5k tokens of borrowable liquidity under the 90% max_util, ~5k tokens of withdrawable supply
+    let borrow_amount = total_supply
+        .fixed_mul_floor(0_9000000, SCALAR_7)
+        .unwrap_optimized()
+        - total_liabilities
+        - 4_500_0000000;
+    pool_client.submit(
+        &merry,
+        &merry,
+        &merry,
+        &vec![
+            &e,
+            Request {
+                request_type: 2,
+                address: xlm.clone(),
+                amount: merry_collateral,
+            },
+            Request {
+                request_type: 4,
+                address: usdc.clone(),
+                amount: borrow_amount,
+            },
+        ],
+    );
+
+    let samwise_underlying = fee_vault_client.get_underlying_tokens(&usdc, &samwise);
+    let max_withdraw = fee_vault_client.max_withdraw(&usdc, &samwise);
+    assert!(max_withdraw < samwise_underlying);
+    assert!(max_withdraw > 4_900_0000000 && max_withdraw <= 5_000_0000000);
+
+    // -> verify withdrawing the max leaves the pool at or below max_util
+    fee_vault_client.withdraw(&usdc, &samwise, &max_withdraw);
+    assert_eq!(usdc_client.balance(&samwise), max_withdraw);
+    let pool_reserve = pool_client.get_reserve(&usdc);
+    let total_supply = pool_reserve
+        .data
+        .b_supply
+        .fixed_mul_floor(pool_reserve.data.b_rate, SCALAR_12)
+        .unwrap_optimized();
+    let total_liabilities = pool_reserve
+        .data
+        .d_supply
+        .fixed_mul_ceil(pool_reserve.data.d_rate, SCALAR_12)
+        .unwrap_optimized();
+    assert!(
+        total_liabilities
+            .fixed_div_ceil(total_supply, SCALAR_7)
+            .unwrap_optimized()
+            <= 0_9000000
+    );
+    assert!(fee_vault_client.max_withdraw(&usdc, &samwise) < 1_0000000);
+}
@@ -0,0 +1,331 @@
+#![cfg(test)]
+
+use crate::storage::{self, FeeMode, Role, ONE_DAY_LEDGERS};
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol, Vec};
+
+#[test]
+fn test_deprecate_and_remove_reserve_vault() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.add_reserve_vault(&xlm, &false, &None);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens of each reserve for a 50% util rate
+    let mut requests = Vec::new(&e);
+    for reserve in [usdc.clone(), xlm.clone()] {
+        requests.push_back(Request {
+            address: reserve.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        });
+        requests.push_back(Request {
+            address: reserve,
+            amount: 100_000_0000000,
+            request_type: 4,
+        });
+    }
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo deposits into both vaults and fees accrue for a week
+    usdc_client.mint(&frodo, &10_000_0000000);
+    xlm_client.mint(&frodo, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &1_000_0000000);
+    fee_vault_client.deposit(&xlm, &frodo, &1_000_0000000);
+    e.jump(7 * ONE_DAY_LEDGERS);
+
+    // -> verify a reserve vault must be deprecated before it is removed
+    assert_eq!(
+        fee_vault_client.try_remove_reserve_vault(&usdc).err(),
+        Some(Ok(Error::from_contract_error(134)))
+    );
+
+    /*
+     * Deprecate the USDC vault
+     */
+    fee_vault_client.deprecate_reserve_vault(&usdc);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "reserve_vault_deprecated").into_val(&e),
+            usdc.into_val(&e)
+        ]
+    );
+    assert!(fee_vault_client.is_deprecated_vault(&usdc));
+    assert!(!fee_vault_client.is_deprecated_vault(&xlm));
+    assert_eq!(
+        fee_vault_client.try_deprecate_reserve_vault(&usdc).err(),
+        Some(Ok(Error::from_contract_error(133)))
+    );
+
+    // -> verify deposits are blocked
+    assert_eq!(fee_vault_client.max_deposit(&usdc, &frodo), 0);
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(133)))
+    );
+    assert_eq!(
+        fee_vault_client.try_mint(&usdc, &frodo, &1_0000000).err(),
+        Some(Ok(Error::from_contract_error(133)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_convert_fees_to_shares(&usdc, &gandalf)
+            .err(),
+        Some(Ok(Error::from_contract_error(133)))
+    );
+    fee_vault_client.deposit(&xlm, &frodo, &1_0000000);
+
+    /*
+     * Empty the USDC vault
+     */
+    // -> verify a reserve vault with shares can't be removed
+    assert_eq!(
+        fee_vault_client.try_remove_reserve_vault(&usdc).err(),
+        Some(Ok(Error::from_contract_error(135)))
+    );
+    fee_vault_client.withdraw_all(&usdc, &frodo);
+    assert_eq!(fee_vault_client.get_reserve_vault(&usdc).total_shares, 0);
+
+    // -> verify a reserve vault with fees can't be removed
+    assert_eq!(
+        fee_vault_client.try_remove_reserve_vault(&usdc).err(),
+        Some(Ok(Error::from_contract_error(135)))
+    );
+    fee_vault_client.claim_fees(&usdc, &gandalf);
+
+    /*
+     * Remove the USDC vault
+     */
+    let usdc_share_token = fee_vault_client.get_share_token(&usdc);
+    fee_vault_client.remove_reserve_vault(&usdc);
+    assert_eq!(e.auths()[0].0, gandalf);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "reserve_vault_removed").into_val(&e),
+            usdc.into_val(&e),
+            gandalf.into_val(&e)
+        ]
+    );
+    e.as_contract(&fee_vault, || {
+        assert_eq!(storage::get_reserves(&e), vec![&e, xlm.clone()]);
+        assert!(!storage::get_reserve_is_deprecated(&e, &usdc));
+    });
+    assert_eq!(
+        fee_vault_client.try_get_reserve_vault(&usdc).err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_deposit(&usdc, &frodo, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    // -> verify the remaining reserve vaults are unaffected
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.claim_all_fees(&gandalf);
+    fee_vault_client.withdraw_all(&xlm, &frodo);
+
+    // -> verify the reserve can be added back, and reuses its share token
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    assert!(!fee_vault_client.is_deprecated_vault(&usdc));
+    assert_eq!(fee_vault_client.get_share_token(&usdc), usdc_share_token);
+    fee_vault_client.deposit(&usdc, &frodo, &1_0000000);
+    e.as_contract(&fee_vault, || {
+        assert_eq!(
+            storage::get_reserves(&e),
+            vec![&e, xlm.clone(), usdc.clone()]
+        );
+    });
+}
+
+#[test]
+fn test_remove_reserve_vault_with_pending_emissions() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let treasurer = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let blnd_client = MockTokenClient::new(&e, &blnd);
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+    fee_vault_client.grant_role(&Role::Treasurer, &treasurer);
+    fee_vault_client.set_emissions_take_rate(&0_1000000);
+
+    // start emissions to the pool's reserves
+    blend_fixture.backstop.distribute();
+    e.jump(ONE_DAY_LEDGERS);
+    blend_fixture.emitter.distribute();
+    blend_fixture.backstop.distribute();
+    pool_client.gulp_emissions();
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    // frodo earns emissions, then samwise earns emissions held for compounding
+    usdc_client.mint(&frodo, &10_000_0000000);
+    usdc_client.mint(&samwise, &10_000_0000000);
+    fee_vault_client.deposit(&usdc, &frodo, &1_000_0000000);
+    fee_vault_client.deposit(&usdc, &samwise, &1_000_0000000);
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.withdraw_all(&usdc, &frodo);
+    fee_vault_client.set_compounding(&usdc, &true);
+    e.jump(ONE_DAY_LEDGERS);
+    fee_vault_client.withdraw_all(&usdc, &samwise);
+
+    fee_vault_client.deprecate_reserve_vault(&usdc);
+    // leave some bTokens without shares, as rounding dust
+    e.as_contract(&fee_vault, || {
+        let mut vault = storage::get_reserve_vault(&e, &usdc);
+        vault.total_b_tokens += 1000;
+        vault.accrued_fees -= 1000;
+        storage::set_reserve_vault(&e, &usdc, &vault);
+    });
+    fee_vault_client.claim_fees(&usdc, &treasurer);
+    e.jump(ONE_DAY_LEDGERS);
+
+    let frodo_accrued = fee_vault_client.get_user_emissions(&usdc, &frodo);
+    assert!(frodo_accrued > 0);
+    let (admin_accrued, compound_accrued, dust) = e.as_contract(&fee_vault, || {
+        let data = storage::get_reserve_emissions(&e, &usdc);
+        (
+            data.admin_accrued,
+            data.compound_accrued,
+            storage::get_reserve_vault(&e, &usdc).total_b_tokens,
+        )
+    });
+    assert!(admin_accrued > 0);
+    assert!(compound_accrued > 0);
+    assert!(dust > 0);
+
+    /*
+     * Remove the USDC vault
+     */
+    let blnd_pre = blnd_client.balance(&fee_vault);
+    let usdc_pre = usdc_client.balance(&treasurer);
+    fee_vault_client.remove_reserve_vault(&usdc);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "reserve_vault_removed").into_val(&e),
+            usdc.into_val(&e),
+            treasurer.into_val(&e)
+        ]
+    );
+    let event_data: (i128, i128) = event.2.into_val(&e);
+
+    // -> verify the settled emissions and the dust are sent to the treasurer
+    let emissions = blnd_client.balance(&treasurer);
+    assert!(emissions >= admin_accrued + compound_accrued);
+    let swept = usdc_client.balance(&treasurer) - usdc_pre;
+    assert!(swept > 0);
+    assert_eq!(event_data, (swept, emissions));
+    // -> verify the depositors' emissions stay in the vault
+    assert_eq!(
+        blnd_client.balance(&fee_vault),
+        blnd_pre - admin_accrued - compound_accrued
+    );
+    e.as_contract(&fee_vault, || {
+        let data = storage::get_reserve_emissions(&e, &usdc);
+        assert_eq!(data.index, 0);
+        assert_eq!(data.admin_accrued, 0);
+        assert_eq!(data.compound_accrued, 0);
+    });
+
+    // -> verify frodo can still claim their emissions once
+    assert_eq!(
+        fee_vault_client.claim_user_emissions(&usdc, &frodo, &frodo),
+        frodo_accrued
+    );
+    assert_eq!(blnd_client.balance(&frodo), frodo_accrued);
+    assert_eq!(
+        fee_vault_client
+            .try_claim_user_emissions(&usdc, &frodo, &frodo)
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+}
@@ -0,0 +1,133 @@
+#![cfg(test)]
+
+use crate::{
+    storage::{ConfigChange, ConfigKey, FeeMode, Role},
+    testutils::{mockpool, register_fee_vault, EnvTestUtils},
+    FeeVaultClient,
+};
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Env, Error, IntoVal, Symbol,
+};
+
+#[test]
+fn test_roles() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let merry = Address::generate(&e);
+    let pippin = Address::generate(&e);
+    let usdc = Address::generate(&e);
+    let xlm = Address::generate(&e);
+
+    let mock_client = mockpool::register_mock_pool_with_b_rate(&e, 1_000_000_000_000);
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            mock_client.address.clone(),
+            FeeMode::TakeRate(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+
+    // The admin holds every role by default
+    for role in [
+        Role::FeeManager,
+        Role::ReserveManager,
+        Role::Treasurer,
+        Role::Guardian,
+        Role::Compliance,
+    ] {
+        assert_eq!(vault_client.get_role(&role), samwise);
+    }
+    vault_client.add_reserve_vault(&usdc, &false, &None);
+    assert_eq!(e.auths()[0].0, samwise);
+    vault_client.set_fee_mode(&FeeMode::TakeRate(0_2000000));
+    assert_eq!(e.auths()[0].0, samwise);
+    vault_client.claim_all_emissions(&samwise);
+    assert_eq!(e.auths()[0].0, samwise);
+
+    /*
+     * Grant roles
+     */
+    vault_client.grant_role(&Role::FeeManager, &frodo);
+    assert_eq!(e.auths()[0].0, samwise);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, vault_address.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "role_granted").into_val(&e),
+            Role::FeeManager.into_val(&e)
+        ]
+    );
+    let data: Address = event.2.into_val(&e);
+    assert_eq!(data, frodo);
+    assert_eq!(vault_client.get_role(&Role::FeeManager), frodo);
+
+    vault_client.grant_role(&Role::ReserveManager, &merry);
+    vault_client.grant_role(&Role::Treasurer, &pippin);
+
+    // -> verify each privileged action requires its role holder
+    vault_client.set_fee_mode(&FeeMode::TakeRate(0_1000000));
+    assert_eq!(e.auths()[0].0, frodo);
+    vault_client.set_reserve_fee_mode(&usdc, &Some(FeeMode::AprCap(0_0500000)));
+    assert_eq!(e.auths()[0].0, frodo);
+    vault_client.set_emissions_take_rate(&0_1000000);
+    assert_eq!(e.auths()[0].0, frodo);
+    vault_client.queue_config_change(&ConfigChange::EmissionsTakeRate(0_2000000));
+    assert_eq!(e.auths()[0].0, frodo);
+    vault_client.cancel_config_change(&ConfigKey::EmissionsTakeRate);
+    assert_eq!(e.auths()[0].0, frodo);
+
+    vault_client.add_reserve_vault(&xlm, &false, &None);
+    assert_eq!(e.auths()[0].0, merry);
+    vault_client.set_compound_config(&Address::generate(&e), &Address::generate(&e));
+    assert_eq!(e.auths()[0].0, merry);
+    vault_client.set_compounding(&xlm, &true);
+    assert_eq!(e.auths()[0].0, merry);
+
+    vault_client.claim_all_emissions(&pippin);
+    assert_eq!(e.auths()[0].0, pippin);
+    vault_client.claim_emissions(&vec![&e, 1], &pippin);
+    assert_eq!(e.auths()[0].0, pippin);
+
+    // -> verify the config delay stays with the admin
+    vault_client.set_config_delay(&0);
+    assert_eq!(e.auths()[0].0, samwise);
+
+    /*
+     * Revoke roles
+     */
+    vault_client.revoke_role(&Role::FeeManager);
+    assert_eq!(e.auths()[0].0, samwise);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, vault_address.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "role_revoked").into_val(&e),
+            Role::FeeManager.into_val(&e)
+        ]
+    );
+    assert_eq!(vault_client.get_role(&Role::FeeManager), samwise);
+    vault_client.set_fee_mode(&FeeMode::TakeRate(0_2000000));
+    assert_eq!(e.auths()[0].0, samwise);
+
+    // -> verify a role that has not been granted can't be revoked
+    assert_eq!(
+        vault_client.try_revoke_role(&Role::Guardian).err(),
+        Some(Ok(Error::from_contract_error(119)))
+    );
+
+    // -> verify the admin keeps roles that have not been granted after an admin change
+    vault_client.set_admin(&frodo);
+    assert_eq!(vault_client.get_role(&Role::FeeManager), frodo);
+    assert_eq!(vault_client.get_role(&Role::ReserveManager), merry);
+}
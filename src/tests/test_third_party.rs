@@ -0,0 +1,363 @@
+#![cfg(test)]
+
+use crate::storage::{FeeMode, ONE_DAY_LEDGERS};
+use crate::testutils::share_token_wasm::Client as ShareTokenClient;
+use crate::testutils::{create_blend_pool, create_fee_vault, EnvTestUtils};
+use crate::FeeVaultClient;
+use blend_contract_sdk::pool::{Client as PoolClient, Request};
+use blend_contract_sdk::testutils::BlendFixture;
+use sep_41_token::testutils::MockTokenClient;
+use soroban_sdk::testutils::{Address as _, AuthorizedFunction, AuthorizedInvocation, Events};
+use soroban_sdk::{vec, Address, Env, Error, IntoVal, Symbol};
+
+#[test]
+fn test_deposit_for_and_withdraw_to() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    /*
+     * Deposit on behalf of another user
+     * -> frodo deposits 100 tokens, crediting the shares to samwise
+     */
+    let starting_balance = 100_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    let shares = fee_vault_client.deposit_for(&usdc, &frodo, &samwise, &starting_balance);
+
+    // -> verify the event records both the payer and the receiver of the shares
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "vault_deposit").into_val(&e),
+            usdc.into_val(&e),
+            frodo.into_val(&e),
+            samwise.into_val(&e),
+        ]
+    );
+
+    // -> verify only frodo authorizes the deposit and the tokens are pulled from frodo
+    let deposit_request = vec![
+        &e,
+        Request {
+            request_type: 0,
+            address: usdc.clone(),
+            amount: starting_balance,
+        },
+    ];
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            frodo.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "deposit_for"),
+                    vec![
+                        &e,
+                        usdc.to_val(),
+                        frodo.to_val(),
+                        samwise.to_val(),
+                        starting_balance.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![AuthorizedInvocation {
+                    function: AuthorizedFunction::Contract((
+                        pool.clone(),
+                        Symbol::new(&e, "submit"),
+                        vec![
+                            &e,
+                            fee_vault.to_val(),
+                            frodo.to_val(),
+                            frodo.to_val(),
+                            deposit_request.to_val(),
+                        ]
+                    )),
+                    sub_invocations: std::vec![AuthorizedInvocation {
+                        function: AuthorizedFunction::Contract((
+                            usdc.clone(),
+                            Symbol::new(&e, "transfer"),
+                            vec![
+                                &e,
+                                frodo.to_val(),
+                                pool.to_val(),
+                                starting_balance.into_val(&e)
+                            ]
+                        )),
+                        sub_invocations: std::vec![]
+                    }]
+                }]
+            }
+        )]
+    );
+    assert_eq!(usdc_client.balance(&frodo), 0);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &frodo), 0);
+    assert_eq!(fee_vault_client.get_shares(&usdc, &samwise), shares);
+
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    /*
+     * Withdraw to a separate recipient
+     * -> samwise withdraws 50 tokens, sending the tokens to merry
+     */
+    let withdraw_amount = 50_0000000;
+    let burnt_shares = fee_vault_client.withdraw_to(&usdc, &samwise, &merry, &withdraw_amount);
+
+    // -> verify the event records both the share owner and the receiver of the tokens
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, fee_vault.clone());
+    assert_eq!(
+        event.1,
+        vec![
+            &e,
+            Symbol::new(&e, "vault_withdraw").into_val(&e),
+            usdc.into_val(&e),
+            samwise.into_val(&e),
+            merry.into_val(&e),
+        ]
+    );
+
+    // -> verify only samwise authorizes the withdrawal
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            samwise.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "withdraw_to"),
+                    vec![
+                        &e,
+                        usdc.to_val(),
+                        samwise.to_val(),
+                        merry.to_val(),
+                        withdraw_amount.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(usdc_client.balance(&merry), withdraw_amount);
+    assert_eq!(usdc_client.balance(&samwise), 0);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &samwise),
+        shares - burnt_shares
+    );
+    assert_eq!(fee_vault_client.get_shares(&usdc, &merry), 0);
+}
+
+#[test]
+fn test_withdraw_from() {
+    let e = Env::default();
+    e.cost_estimate().budget().reset_unlimited();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let bombadil = Address::generate(&e);
+    let gandalf = Address::generate(&e);
+    let frodo = Address::generate(&e);
+    let samwise = Address::generate(&e);
+    let merry = Address::generate(&e);
+
+    let blnd = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let xlm = e
+        .register_stellar_asset_contract_v2(bombadil.clone())
+        .address();
+    let usdc_client = MockTokenClient::new(&e, &usdc);
+    let xlm_client = MockTokenClient::new(&e, &xlm);
+
+    let blend_fixture = BlendFixture::deploy(&e, &bombadil, &blnd, &usdc);
+
+    // usdc (0) and xlm (1) charge a fixed 10% borrow rate with 0% backstop take rate
+    let pool = create_blend_pool(&e, &blend_fixture, &bombadil, &usdc_client, &xlm_client);
+    let pool_client = PoolClient::new(&e, &pool);
+    let fee_vault = create_fee_vault(&e, &gandalf, &pool, &blnd, FeeMode::TakeRate(0_1000000));
+    let fee_vault_client = FeeVaultClient::new(&e, &fee_vault);
+
+    // Setup pool util rate
+    // Bomadil deposits 200k tokens and borrows 100k tokens for a 50% util rate
+    let requests = vec![
+        &e,
+        Request {
+            address: usdc.clone(),
+            amount: 200_000_0000000,
+            request_type: 2,
+        },
+        Request {
+            address: usdc.clone(),
+            amount: 100_000_0000000,
+            request_type: 4,
+        },
+    ];
+    pool_client
+        .mock_all_auths()
+        .submit(&bombadil, &bombadil, &bombadil, &requests);
+
+    fee_vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    let starting_balance = 100_0000000;
+    usdc_client.mint(&frodo, &starting_balance);
+    let shares = fee_vault_client.deposit(&usdc, &frodo, &starting_balance);
+
+    // -> verify a spender cannot withdraw without an allowance
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_from(&usdc, &merry, &frodo, &merry, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(9)))
+    );
+
+    /*
+     * Approve a spender
+     * -> frodo approves merry to withdraw half of their shares
+     */
+    let expiration_ledger = e.ledger().sequence() + ONE_DAY_LEDGERS * 10;
+    fee_vault_client.approve_withdraw(&usdc, &frodo, &merry, &(shares / 2), &expiration_ledger);
+    assert_eq!(e.auths()[0].0, frodo);
+    assert_eq!(
+        fee_vault_client.withdraw_allowance(&usdc, &frodo, &merry),
+        shares / 2
+    );
+    // -> verify the withdraw allowance does not grant a share token allowance
+    let share_token_client = ShareTokenClient::new(&e, &fee_vault_client.get_share_token(&usdc));
+    assert_eq!(share_token_client.allowance(&frodo, &merry), 0);
+
+    // -> verify expirations past the max TTL are rejected
+    assert_eq!(
+        fee_vault_client
+            .try_approve_withdraw(
+                &usdc,
+                &frodo,
+                &merry,
+                &(shares / 2),
+                &(e.ledger().max_live_until_ledger() + 1)
+            )
+            .err(),
+        Some(Ok(Error::from_contract_error(109)))
+    );
+
+    e.jump(ONE_DAY_LEDGERS * 7);
+
+    /*
+     * Withdraw on behalf of the owner
+     * -> merry withdraws 20 tokens from frodo's position to samwise
+     */
+    let withdraw_amount = 20_0000000;
+    let burnt_shares =
+        fee_vault_client.withdraw_from(&usdc, &merry, &frodo, &samwise, &withdraw_amount);
+    assert_eq!(
+        e.auths(),
+        std::vec![(
+            merry.clone(),
+            AuthorizedInvocation {
+                function: AuthorizedFunction::Contract((
+                    fee_vault.clone(),
+                    Symbol::new(&e, "withdraw_from"),
+                    vec![
+                        &e,
+                        usdc.to_val(),
+                        merry.to_val(),
+                        frodo.to_val(),
+                        samwise.to_val(),
+                        withdraw_amount.into_val(&e),
+                    ]
+                )),
+                sub_invocations: std::vec![]
+            }
+        )]
+    );
+    assert_eq!(usdc_client.balance(&samwise), withdraw_amount);
+    assert_eq!(usdc_client.balance(&merry), 0);
+    assert_eq!(
+        fee_vault_client.get_shares(&usdc, &frodo),
+        shares - burnt_shares
+    );
+    assert_eq!(
+        fee_vault_client.withdraw_allowance(&usdc, &frodo, &merry),
+        shares / 2 - burnt_shares
+    );
+
+    // -> verify the spender cannot withdraw more than the remaining allowance
+    let remaining_underlying = fee_vault_client.preview_redeem(&usdc, &(shares / 2 - burnt_shares));
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_from(
+                &usdc,
+                &merry,
+                &frodo,
+                &merry,
+                &(remaining_underlying + 1_0000000)
+            )
+            .err(),
+        Some(Ok(Error::from_contract_error(9)))
+    );
+
+    // -> verify the allowance cannot be used once it expires
+    e.jump(ONE_DAY_LEDGERS * 4);
+    assert_eq!(
+        fee_vault_client.withdraw_allowance(&usdc, &frodo, &merry),
+        0
+    );
+    assert_eq!(
+        fee_vault_client
+            .try_withdraw_from(&usdc, &merry, &frodo, &merry, &1_0000000)
+            .err(),
+        Some(Ok(Error::from_contract_error(9)))
+    );
+}
@@ -0,0 +1,187 @@
+#![cfg(test)]
+
+use crate::{
+    storage::{ConfigChange, ConfigKey, FeeMode, QueuedConfigChange},
+    testutils::{mockpool, register_fee_vault, EnvTestUtils},
+    FeeVaultClient,
+};
+use soroban_sdk::{
+    testutils::{Address as _, Events},
+    vec, Address, Env, Error, IntoVal, Symbol,
+};
+
+#[test]
+fn test_timelocked_config() {
+    let e = Env::default();
+    e.mock_all_auths();
+    e.set_default_info();
+
+    let samwise = Address::generate(&e);
+    let usdc = Address::generate(&e);
+
+    let mock_client = mockpool::register_mock_pool_with_b_rate(&e, 1_000_000_000_000);
+    let vault_address = register_fee_vault(
+        &e,
+        Some((
+            samwise.clone(),
+            mock_client.address.clone(),
+            FeeMode::TakeRate(0_1000000),
+        )),
+    );
+    let vault_client = FeeVaultClient::new(&e, &vault_address);
+    vault_client.add_reserve_vault(&usdc, &false, &None);
+
+    // Without a delay, changes apply immediately
+    assert_eq!(vault_client.get_config_delay(), 0);
+    vault_client.set_emissions_take_rate(&0_2000000);
+    assert_eq!(vault_client.get_emissions_take_rate(), 0_2000000);
+
+    // Lengthening the delay applies immediately
+    let delay = 7 * 24 * 60 * 60;
+    vault_client.set_config_delay(&delay);
+    assert_eq!(vault_client.get_config_delay(), delay);
+
+    /*
+     * Fee increases and other sensitive changes require the timelock
+     */
+    assert_eq!(
+        vault_client
+            .try_set_fee_mode(&FeeMode::TakeRate(0_2000000))
+            .err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+    assert_eq!(
+        vault_client
+            .try_set_fee_mode(&FeeMode::Hybrid(0_1000000, 0_0800000))
+            .err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+    assert_eq!(
+        vault_client
+            .try_set_reserve_fee_mode(&usdc, &Some(FeeMode::TakeRate(0_2000000)))
+            .err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+    assert_eq!(
+        vault_client.try_set_emissions_take_rate(&0_3000000).err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+    assert_eq!(
+        vault_client
+            .try_set_compound_config(&Address::generate(&e), &samwise)
+            .err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+    assert_eq!(
+        vault_client.try_set_config_delay(&(delay - 1)).err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+
+    /*
+     * Fee decreases apply immediately
+     */
+    vault_client.set_fee_mode(&FeeMode::TakeRate(0_0500000));
+    assert_eq!(
+        vault_client.get_fee_mode(&usdc),
+        FeeMode::TakeRate(0_0500000)
+    );
+    vault_client.set_reserve_fee_mode(&usdc, &Some(FeeMode::TakeRate(0_0200000)));
+    assert_eq!(
+        vault_client.get_fee_mode(&usdc),
+        FeeMode::TakeRate(0_0200000)
+    );
+    vault_client.set_emissions_take_rate(&0_1000000);
+    assert_eq!(vault_client.get_emissions_take_rate(), 0_1000000);
+
+    /*
+     * Queue a fee increase
+     */
+    // -> verify queued changes are validated
+    assert_eq!(
+        vault_client
+            .try_queue_config_change(&ConfigChange::FeeMode(FeeMode::TakeRate(1_000_0001)))
+            .err(),
+        Some(Ok(Error::from_contract_error(104)))
+    );
+    assert_eq!(
+        vault_client
+            .try_queue_config_change(&ConfigChange::ClearReserveFeeMode(Address::generate(&e)))
+            .err(),
+        Some(Ok(Error::from_contract_error(100)))
+    );
+
+    let change = ConfigChange::FeeMode(FeeMode::TakeRate(0_2000000));
+    let unlock_time = vault_client.queue_config_change(&change);
+    assert_eq!(unlock_time, e.ledger().timestamp() + delay);
+    let event = e.events().all().last().unwrap();
+    assert_eq!(event.0, vault_address.clone());
+    assert_eq!(
+        event.1,
+        vec![&e, Symbol::new(&e, "config_change_queued").into_val(&e)]
+    );
+    let data: (ConfigChange, u64) = event.2.into_val(&e);
+    assert_eq!(data, (change.clone(), unlock_time));
+    assert_eq!(
+        vault_client.get_queued_config_change(&ConfigKey::FeeMode),
+        Some(QueuedConfigChange {
+            change: change.clone(),
+            unlock_time,
+        })
+    );
+
+    // -> verify the change can't be applied before it unlocks
+    e.jump_time(delay - 1);
+    assert_eq!(
+        vault_client
+            .try_apply_config_change(&ConfigKey::FeeMode)
+            .err(),
+        Some(Ok(Error::from_contract_error(118)))
+    );
+
+    // -> verify the change can be cancelled
+    vault_client.cancel_config_change(&ConfigKey::FeeMode);
+    assert_eq!(
+        vault_client.get_queued_config_change(&ConfigKey::FeeMode),
+        None
+    );
+    e.jump_time(1);
+    assert_eq!(
+        vault_client
+            .try_apply_config_change(&ConfigKey::FeeMode)
+            .err(),
+        Some(Ok(Error::from_contract_error(117)))
+    );
+    assert_eq!(
+        vault_client
+            .try_cancel_config_change(&ConfigKey::FeeMode)
+            .err(),
+        Some(Ok(Error::from_contract_error(117)))
+    );
+
+    // -> verify the change is applied once it unlocks
+    vault_client.queue_config_change(&change);
+    vault_client.queue_config_change(&ConfigChange::ConfigDelay(0));
+    e.jump_time(delay);
+    vault_client.apply_config_change(&ConfigKey::FeeMode);
+    assert_eq!(
+        vault_client.get_fee_mode(&usdc),
+        FeeMode::TakeRate(0_0200000)
+    );
+    assert_eq!(
+        vault_client.get_queued_config_change(&ConfigKey::FeeMode),
+        None
+    );
+
+    // -> verify clearing a reserve fee mode that raises fees is timelocked until the delay is removed
+    assert_eq!(
+        vault_client.try_set_reserve_fee_mode(&usdc, &None).err(),
+        Some(Ok(Error::from_contract_error(116)))
+    );
+    vault_client.apply_config_change(&ConfigKey::ConfigDelay);
+    assert_eq!(vault_client.get_config_delay(), 0);
+    vault_client.set_reserve_fee_mode(&usdc, &None);
+    assert_eq!(
+        vault_client.get_fee_mode(&usdc),
+        FeeMode::TakeRate(0_2000000)
+    );
+}
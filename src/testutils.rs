@@ -2,7 +2,11 @@
 
 use core::i64;
 
-use crate::{constants::SCALAR_7, storage::ONE_DAY_LEDGERS, FeeVault};
+use crate::{
+    constants::SCALAR_7,
+    storage::{FeeMode, ONE_DAY_LEDGERS},
+    FeeVault,
+};
 use blend_contract_sdk::pool::{Client as PoolClient, ReserveConfig, ReserveEmissionMetadata};
 use blend_contract_sdk::testutils::BlendFixture;
 use sep_41_token::testutils::MockTokenClient;
@@ -13,19 +17,33 @@ use soroban_sdk::{
     vec, Address, BytesN, Env, String, Symbol,
 };
 
-// Defaults to a mock pool with a b_rate of 1_100_000_000 and a take_rate of 0_1000000.
+pub(crate) mod share_token_wasm {
+    soroban_sdk::contractimport!(
+        file = "target/wasm32-unknown-unknown/release/fee_vault_share_token.wasm"
+    );
+}
+
+// Defaults to a mock pool with a b_rate of 1_100_000_000 and a take_rate of 0_1000000. The BLND
+// token is a random address, as the mock pool never emits.
 pub(crate) fn register_fee_vault(
     e: &Env,
-    constructor_args: Option<(Address, Address, bool, i128)>,
+    constructor_args: Option<(Address, Address, FeeMode)>,
 ) -> Address {
+    let (admin, pool, fee_mode) = constructor_args.unwrap_or((
+        Address::generate(e),
+        mockpool::register_mock_pool_with_b_rate(e, 1_100_000_000_000).address,
+        FeeMode::TakeRate(0_1000000),
+    ));
+    let share_token_wasm_hash = e.deployer().upload_contract_wasm(share_token_wasm::WASM);
     e.register(
         FeeVault {},
-        constructor_args.unwrap_or((
+        (
+            admin,
+            pool,
             Address::generate(e),
-            mockpool::register_mock_pool_with_b_rate(e, 1_100_000_000_000).address,
-            false,
-            0_1000000,
-        )),
+            fee_mode,
+            share_token_wasm_hash,
+        ),
     )
 }
 
@@ -126,10 +144,20 @@ pub(crate) fn create_fee_vault(
     e: &Env,
     admin: &Address,
     pool: &Address,
-    apr_capped: bool,
-    value: i128,
+    blnd: &Address,
+    fee_mode: FeeMode,
 ) -> Address {
-    register_fee_vault(e, Some((admin.clone(), pool.clone(), apr_capped, value)))
+    let share_token_wasm_hash = e.deployer().upload_contract_wasm(share_token_wasm::WASM);
+    e.register(
+        FeeVault {},
+        (
+            admin.clone(),
+            pool.clone(),
+            blnd.clone(),
+            fee_mode,
+            share_token_wasm_hash,
+        ),
+    )
 }
 
 pub trait EnvTestUtils {
@@ -229,7 +257,9 @@ pub fn create_mock_oracle<'a>(e: &Env) -> (Address, MockPriceOracleClient<'a>) {
 /// Mock pool to test b_rate updates
 pub mod mockpool {
 
-    use soroban_sdk::{contract, contractimpl, contracttype, symbol_short, Address, Env, Symbol};
+    use soroban_sdk::{
+        contract, contractimpl, contracttype, symbol_short, Address, Env, Symbol, Vec,
+    };
 
     const BRATE: Symbol = symbol_short!("b_rate");
     #[derive(Clone, Debug)]
@@ -284,6 +314,11 @@ pub mod mockpool {
             e.storage().instance().set(&BRATE, &b_rate);
         }
 
+        /// Note: The mock pool never emits BLND
+        pub fn claim(_e: Env, _from: Address, _reserve_token_ids: Vec<u32>, _to: Address) -> i128 {
+            0
+        }
+
         /// Note: We're only interested in the `b_rate`
         pub fn get_reserve(e: Env, reserve: Address) -> Reserve {
             let mut r_data = ReserveData::default();
@@ -302,3 +337,64 @@ pub mod mockpool {
         MockPoolClient::new(e, &pool_address)
     }
 }
+
+/// Mock swap router that swaps at a fixed rate out of its own balance
+pub mod mockrouter {
+    use crate::constants::SCALAR_7;
+    use soroban_fixed_point_math::FixedPoint;
+    use soroban_sdk::{
+        contract, contractimpl, panic_with_error, symbol_short, token::TokenClient, Address, Env,
+        Symbol,
+    };
+
+    use crate::errors::FeeVaultError;
+
+    const RATE: Symbol = symbol_short!("rate");
+    const BONUS: Symbol = symbol_short!("bonus");
+
+    #[contract]
+    pub struct MockRouter;
+
+    #[contractimpl]
+    impl MockRouter {
+        /// Set the amount of `token_out` paid per `token_in`, with 7 decimals
+        pub fn set_rate(e: Env, rate: i128) {
+            e.storage().instance().set(&RATE, &rate);
+        }
+
+        /// Set an amount the router over-reports its output by, without paying it
+        pub fn set_bonus(e: Env, bonus: i128) {
+            e.storage().instance().set(&BONUS, &bonus);
+        }
+
+        /// Note: The `token_in` is expected to already be held by the router
+        pub fn swap_exact_in(
+            e: Env,
+            _token_in: Address,
+            token_out: Address,
+            amount_in: i128,
+            min_amount_out: i128,
+            to: Address,
+        ) -> i128 {
+            let rate: i128 = e.storage().instance().get(&RATE).unwrap_or(SCALAR_7);
+            let bonus: i128 = e.storage().instance().get(&BONUS).unwrap_or(0);
+            let amount_out = amount_in.fixed_mul_floor(rate, SCALAR_7).unwrap();
+            if amount_out + bonus < min_amount_out {
+                panic_with_error!(&e, FeeVaultError::InvalidAmount);
+            }
+            TokenClient::new(&e, &token_out).transfer(
+                &e.current_contract_address(),
+                &to,
+                &amount_out,
+            );
+            amount_out + bonus
+        }
+    }
+
+    pub fn register_mock_router(e: &Env, rate: i128) -> MockRouterClient {
+        let router_address = e.register(MockRouter {}, ());
+        let client = MockRouterClient::new(e, &router_address);
+        client.set_rate(&rate);
+        client
+    }
+}
@@ -1,6 +1,10 @@
-use soroban_sdk::{panic_with_error, Address, Env};
+use soroban_sdk::{panic_with_error, unwrap::UnwrapOptimized, Address, Env, Vec};
 
-use crate::{errors::FeeVaultError, storage::has_reserve_vault};
+use crate::{
+    constants::{MAX_BPS, SCALAR_7},
+    errors::FeeVaultError,
+    storage::{self, has_reserve_vault, FeeMode, FeeRecipients, PauseState, Role},
+};
 
 /// Require that an incoming amount is positive
 ///
@@ -16,6 +20,19 @@ pub fn require_positive(e: &Env, amount: i128, err: FeeVaultError) {
     }
 }
 
+/// Require that an incoming amount is not negative
+///
+/// ### Arguments
+/// * `amount` - The amount to check
+///
+/// ### Panics
+/// * `NegativeAmountError` - If the number is negative
+pub fn require_nonnegative(e: &Env, amount: i128) {
+    if amount < 0 {
+        panic_with_error!(e, FeeVaultError::NegativeAmountError);
+    }
+}
+
 /// Require that the reserve exists in the fee vault
 ///
 /// ### Arguments
@@ -28,3 +45,265 @@ pub fn require_has_reserve(e: &Env, reserve: &Address) {
         panic_with_error!(e, FeeVaultError::ReserveNotFound);
     }
 }
+
+/// Require that a batch of (reserve, amount) pairs is not empty, that each reserve exists in the
+/// fee vault and appears once, and that each amount is positive
+///
+/// ### Arguments
+/// * `actions` - The (reserve, amount) pairs to check
+///
+/// ### Panics
+/// * `InvalidAmount` - If the batch is empty or an amount is less than or equal to 0
+/// * `ReserveNotFound` - If a reserve doesn't exist
+/// * `DuplicateReserve` - If a reserve appears more than once
+pub fn require_valid_batch(e: &Env, actions: &Vec<(Address, i128)>) {
+    if actions.is_empty() {
+        panic_with_error!(e, FeeVaultError::InvalidAmount);
+    }
+    for (index, (reserve, amount)) in actions.iter().enumerate() {
+        require_has_reserve(e, &reserve);
+        require_positive(e, amount, FeeVaultError::InvalidAmount);
+        if actions
+            .iter()
+            .skip(index + 1)
+            .any(|(other, _)| other == reserve)
+        {
+            panic_with_error!(e, FeeVaultError::DuplicateReserve);
+        }
+    }
+}
+
+/// Require that the ledger timestamp has not passed the deadline, if one is provided
+///
+/// ### Arguments
+/// * `deadline` - The optional ledger timestamp the action must execute by
+///
+/// ### Panics
+/// * `DeadlineExpired` - If the ledger timestamp is past the deadline
+pub fn require_before_deadline(e: &Env, deadline: Option<u64>) {
+    if let Some(deadline) = deadline {
+        if e.ledger().timestamp() > deadline {
+            panic_with_error!(e, FeeVaultError::DeadlineExpired);
+        }
+    }
+}
+
+/// Require that the shares minted meet the minimum, if one is provided
+///
+/// ### Arguments
+/// * `shares` - The number of shares minted
+/// * `min_shares_out` - The optional minimum number of shares to mint
+///
+/// ### Panics
+/// * `MinSharesOutNotMet` - If fewer shares than the minimum were minted
+pub fn require_min_shares_out(e: &Env, shares: i128, min_shares_out: Option<i128>) {
+    if let Some(min_shares_out) = min_shares_out {
+        if shares < min_shares_out {
+            panic_with_error!(e, FeeVaultError::MinSharesOutNotMet);
+        }
+    }
+}
+
+/// Require that the shares burnt do not exceed the maximum, if one is provided
+///
+/// ### Arguments
+/// * `shares` - The number of shares burnt
+/// * `max_shares_in` - The optional maximum number of shares to burn
+///
+/// ### Panics
+/// * `MaxSharesInExceeded` - If more shares than the maximum were burnt
+pub fn require_max_shares_in(e: &Env, shares: i128, max_shares_in: Option<i128>) {
+    if let Some(max_shares_in) = max_shares_in {
+        if shares > max_shares_in {
+            panic_with_error!(e, FeeVaultError::MaxSharesInExceeded);
+        }
+    }
+}
+
+/// Require that every value of a fee mode is within 0 and 1_000_0000
+///
+/// ### Arguments
+/// * `fee_mode` - The fee mode to check
+///
+/// ### Panics
+/// * `InvalidFeeModeValue` - If a value is not within 0 and 1_000_0000
+pub fn require_valid_fee_mode(e: &Env, fee_mode: &FeeMode) {
+    let is_valid = |value: i128| (0..=SCALAR_7).contains(&value);
+    let valid = match fee_mode {
+        FeeMode::TakeRate(take_rate) => is_valid(*take_rate),
+        FeeMode::AprCap(apr_cap) => is_valid(*apr_cap),
+        FeeMode::Hybrid(take_rate, apr_cap) => is_valid(*take_rate) && is_valid(*apr_cap),
+    };
+    if !valid {
+        panic_with_error!(e, FeeVaultError::InvalidFeeModeValue);
+    }
+}
+
+/// Require that fee recipients are not empty, that each recipient appears once with a positive
+/// weight, and that the weights sum to 100%
+///
+/// ### Arguments
+/// * `fee_recipients` - The fee recipients to check
+///
+/// ### Panics
+/// * `InvalidFeeRecipients` - If the fee recipients are not valid
+pub fn require_valid_fee_recipients(e: &Env, fee_recipients: &FeeRecipients) {
+    let recipients = &fee_recipients.recipients;
+    let mut total_weight: u32 = 0;
+    for (index, recipient) in recipients.iter().enumerate() {
+        let is_duplicate = recipients
+            .iter()
+            .skip(index + 1)
+            .any(|other| other.address == recipient.address);
+        if recipient.weight == 0 || is_duplicate {
+            panic_with_error!(e, FeeVaultError::InvalidFeeRecipients);
+        }
+        total_weight = total_weight.saturating_add(recipient.weight);
+    }
+    if total_weight != MAX_BPS {
+        panic_with_error!(e, FeeVaultError::InvalidFeeRecipients);
+    }
+}
+
+/// Require auth from the holder of a role. The admin holds the role if it has not been granted.
+///
+/// ### Arguments
+/// * `role` - The role to require
+///
+/// ### Returns
+/// * `Address` - The address holding the role
+pub fn require_role(e: &Env, role: &Role) -> Address {
+    let holder = storage::get_role(e, role).unwrap_or_else(|| storage::get_admin(e));
+    holder.require_auth();
+    holder
+}
+
+/// Require that a reserve vault is not deprecated
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+///
+/// ### Panics
+/// * `ReserveDeprecated` - If the reserve vault is deprecated
+pub fn require_not_deprecated(e: &Env, reserve: &Address) {
+    if storage::get_reserve_is_deprecated(e, reserve) {
+        panic_with_error!(e, FeeVaultError::ReserveDeprecated);
+    }
+}
+
+/// Require that deposits into a reserve vault are not paused, either for the reserve vault or for
+/// the whole vault, and that the reserve vault is not deprecated
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+///
+/// ### Panics
+/// * `DepositsBlocked` - If deposits are blocked
+/// * `WithdrawOnly` - If only withdrawals are allowed
+/// * `VaultPaused` - If the reserve vault is paused
+/// * `ReserveDeprecated` - If the reserve vault is deprecated
+pub fn require_deposits_allowed(e: &Env, reserve: &Address) {
+    require_not_deprecated(e, reserve);
+    match get_pause_state(e, reserve) {
+        PauseState::Active => {}
+        PauseState::DepositsBlocked => panic_with_error!(e, FeeVaultError::DepositsBlocked),
+        PauseState::WithdrawOnly => panic_with_error!(e, FeeVaultError::WithdrawOnly),
+        PauseState::Paused => panic_with_error!(e, FeeVaultError::VaultPaused),
+    }
+}
+
+/// Check if deposits into a reserve vault are allowed, either for the reserve vault or for the
+/// whole vault, and the reserve vault is not deprecated
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+pub fn is_deposits_allowed(e: &Env, reserve: &Address) -> bool {
+    !storage::get_reserve_is_deprecated(e, reserve)
+        && get_pause_state(e, reserve) == PauseState::Active
+}
+
+/// Require that withdrawals from a reserve vault are not paused, either for the reserve vault or
+/// for the whole vault
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+///
+/// ### Panics
+/// * `VaultPaused` - If the reserve vault is paused
+pub fn require_withdrawals_allowed(e: &Env, reserve: &Address) {
+    if get_pause_state(e, reserve) == PauseState::Paused {
+        panic_with_error!(e, FeeVaultError::VaultPaused);
+    }
+}
+
+/// Require that fee claims from a reserve vault are not paused, either for the reserve vault or
+/// for the whole vault
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+///
+/// ### Panics
+/// * `WithdrawOnly` - If only withdrawals are allowed
+/// * `VaultPaused` - If the reserve vault is paused
+pub fn require_fee_claims_allowed(e: &Env, reserve: &Address) {
+    match get_pause_state(e, reserve) {
+        PauseState::Active | PauseState::DepositsBlocked => {}
+        PauseState::WithdrawOnly => panic_with_error!(e, FeeVaultError::WithdrawOnly),
+        PauseState::Paused => panic_with_error!(e, FeeVaultError::VaultPaused),
+    }
+}
+
+/// Check if fee claims from a reserve vault are allowed, either for the reserve vault or for the
+/// whole vault
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+pub fn is_fee_claims_allowed(e: &Env, reserve: &Address) -> bool {
+    matches!(
+        get_pause_state(e, reserve),
+        PauseState::Active | PauseState::DepositsBlocked
+    )
+}
+
+/// Require that the caller is a reserve vault's share token
+///
+/// ### Arguments
+/// * `reserve` - The reserve the share token belongs to
+pub fn require_share_token(e: &Env, reserve: &Address) {
+    storage::get_share_token(e, reserve)
+        .unwrap_optimized()
+        .require_auth();
+}
+
+/// Require that a user is allowlisted, if deposits are restricted to the allowlist
+///
+/// ### Arguments
+/// * `user` - The user receiving the shares
+///
+/// ### Panics
+/// * `NotAllowlisted` - If the allowlist is enabled and the user is not on it
+pub fn require_allowlisted(e: &Env, user: &Address) {
+    if storage::get_allowlist_enabled(e) && !storage::is_allowlisted(e, user) {
+        panic_with_error!(e, FeeVaultError::NotAllowlisted);
+    }
+}
+
+/// Require that a user's position in a reserve vault is not frozen
+///
+/// ### Arguments
+/// * `reserve` - The reserve to check
+/// * `user` - The user to check
+///
+/// ### Panics
+/// * `UserFrozen` - If the user's position is frozen
+pub fn require_not_frozen(e: &Env, reserve: &Address, user: &Address) {
+    if storage::is_frozen(e, reserve, user) {
+        panic_with_error!(e, FeeVaultError::UserFrozen);
+    }
+}
+
+/// Fetch the pause state that applies to a reserve vault, which is the stricter of its own and
+/// the whole vault's
+fn get_pause_state(e: &Env, reserve: &Address) -> PauseState {
+    storage::get_pause_state(e).max(storage::get_reserve_pause_state(e, reserve))
+}